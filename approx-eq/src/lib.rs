@@ -1,11 +1,39 @@
 pub const EPSILON: f64 = 0.00001;
 
+pub fn approx_eq_eps(a: f64, b: f64, eps: f64) -> bool {
+    (a - b).abs() < eps
+}
+
 pub trait ApproxEq {
-    fn approx_eq(&self, other: f64) -> bool;
+    fn approx_eq_within(&self, other: f64, eps: f64) -> bool;
+
+    fn approx_eq(&self, other: f64) -> bool {
+        self.approx_eq_within(other, EPSILON)
+    }
 }
 
 impl ApproxEq for f64 {
-    fn approx_eq(&self, other: f64) -> bool {
-        (*self - other).abs() < EPSILON
+    fn approx_eq_within(&self, other: f64, eps: f64) -> bool {
+        approx_eq_eps(*self, other, eps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_can_be_equal_within_a_looser_epsilon() {
+        let (a, b) = (1000.0, 1000.001);
+        assert!(!a.approx_eq(b));
+        assert!(a.approx_eq_within(b, 0.01));
+        assert!(approx_eq_eps(a, b, 0.01));
+    }
+
+    #[test]
+    fn values_can_differ_within_a_tighter_epsilon() {
+        let (a, b) = (1.0, 1.000001);
+        assert!(a.approx_eq(b));
+        assert!(!a.approx_eq_within(b, 1e-9));
     }
 }
@@ -3,7 +3,7 @@ use std::{
     ops::{Add, Div, Mul, Sub},
 };
 
-use approx_eq::ApproxEq;
+use approx_eq::{ApproxEq, EPSILON};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -43,24 +43,47 @@ impl Color {
         self.b = self.b.clamp(min, max);
     }
 
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(f(self.r), f(self.g), f(self.b))
+    }
+
     pub fn round(&mut self) {
         self.r = self.r.round();
         self.g = self.g.round();
         self.b = self.b.round();
     }
 
-    pub fn as_255_string(&self) -> String {
+    pub fn approx_eq(&self, other: &Color) -> bool {
+        self.approx_eq_within(other, EPSILON)
+    }
+
+    pub fn approx_eq_within(&self, other: &Color, eps: f64) -> bool {
+        self.r.approx_eq_within(other.r, eps)
+            && self.g.approx_eq_within(other.g, eps)
+            && self.b.approx_eq_within(other.b, eps)
+    }
+
+    pub fn as_255_bytes(&self) -> [u8; 3] {
         let mut color_as_255 = self * 255.0;
         color_as_255.normalize(0.0, 255.0);
         color_as_255.round();
 
-        format!("{} {} {}", color_as_255.r, color_as_255.g, color_as_255.b)
+        [
+            color_as_255.r as u8,
+            color_as_255.g as u8,
+            color_as_255.b as u8,
+        ]
+    }
+
+    pub fn as_255_string(&self) -> String {
+        let [r, g, b] = self.as_255_bytes();
+        format!("{} {} {}", r, g, b)
     }
 }
 
 impl PartialEq for Color {
     fn eq(&self, other: &Self) -> bool {
-        self.r.approx_eq(other.r) && self.g.approx_eq(other.g) && self.b.approx_eq(other.b)
+        self.approx_eq(other)
     }
 }
 
@@ -132,6 +155,12 @@ impl From<[f64; 3]> for Color {
     }
 }
 
+impl From<Color> for [f64; 3] {
+    fn from(value: Color) -> Self {
+        [value.r, value.g, value.b]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,4 +199,18 @@ mod tests {
         let color2 = Color::new(0.9, 1.0, 0.1);
         assert_eq!(&color1 * &color2, Color::new(0.9, 0.2, 0.04));
     }
+
+    #[test]
+    fn color_channels_can_be_mapped() {
+        let color = Color::new(0.25, -1.0, 4.0);
+        assert_eq!(color.map(|c| c * 2.0), Color::new(0.5, -2.0, 8.0));
+    }
+
+    #[test]
+    fn colors_closer_than_epsilon_are_approximately_equal() {
+        let color = Color::new(0.5, 0.4, 0.3);
+        assert!(color.approx_eq(&Color::new(0.500001, 0.4, 0.299999)));
+        assert!(!color.approx_eq(&Color::new(0.5001, 0.4, 0.3)));
+        assert!(color.approx_eq_within(&Color::new(0.5001, 0.4, 0.3), 0.001));
+    }
 }
@@ -3,7 +3,7 @@ use std::{f64::consts::PI, fs};
 use colo_rs::colors::Color;
 use ray_tracer::{
     camera::Camera,
-    lights::PointLight,
+    lights::{Light, PointLight, SpotLight},
     materials::Material,
     patterns::Pattern,
     ppm::PPM,
@@ -139,10 +139,20 @@ fn main() {
 
     let light_2 = PointLight::new(Point::new(5.0, 5.0, -10.0), Color::new(1.0, 1.0, 1.0) / 2.0);
 
-    let light_3 = PointLight::new(Point::new(0.0, 25.0, 100.0), Color::new(0.7, 0.0, 0.0));
+    let light_3 = SpotLight::new(
+        Point::new(0.0, 25.0, 100.0),
+        Vector::new(0.0, -25.0, -100.0),
+        PI / 24.0,
+        PI / 12.0,
+        Color::new(0.7, 0.0, 0.0),
+    );
 
     let w = World::new()
-        .with_lights(vec![light_1, light_2, light_3])
+        .with_lights(vec![
+            Light::from(light_1),
+            Light::from(light_2),
+            Light::from(light_3),
+        ])
         .with_objects(vec![floor, g]);
 
     let c = Camera::new(1024, 768, PI / 3.0).with_transform(
@@ -54,12 +54,9 @@ fn main() {
         )
         .with_material(Material::new().with_pattern(Pattern::new_solid_pattern(Color::blue())))
         .build();
-    let csg_union = ObjectBuilder::new_csg(
-        CSGKind::Union,
-        cyl1,
-        ObjectBuilder::new_csg(CSGKind::Union, cyl2, cyl3).build(),
-    )
-    .build();
+    let csg_union = ObjectBuilder::new_csg_many(CSGKind::Union, vec![cyl1, cyl2, cyl3])
+        .unwrap()
+        .build();
 
     let csg_difference = ObjectBuilder::new_csg(CSGKind::Difference, csg_intersection, csg_union)
         .with_transform(
@@ -38,7 +38,11 @@ fn main() {
         )
         .build();
 
-    g.divide(100);
+    let stats = g.divide_with_stats(100);
+    println!(
+        "Divided into {} groups, {} levels deep, {} leaves",
+        stats.groups_created, stats.depth, stats.leaves
+    );
 
     let room = ObjectBuilder::new_cube()
         .with_transform(
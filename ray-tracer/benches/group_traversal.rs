@@ -0,0 +1,34 @@
+use std::{hint::black_box, time::Instant};
+
+use ray_tracer::{
+    rays::Ray,
+    shapes::{Object, ObjectBuilder},
+    transformations::Transformation,
+    tuples::{points::Point, vectors::Vector, Tuple},
+};
+
+const DEPTH: usize = 8;
+const RAYS: u32 = 200_000;
+
+fn nested_groups(depth: usize) -> Object {
+    (0..depth).fold(ObjectBuilder::new_sphere().build(), |child, _| {
+        ObjectBuilder::new_group()
+            .add_child(child)
+            .with_transform(Transformation::new_transform().scaling(1.1, 1.1, 1.1))
+            .build()
+    })
+}
+
+fn main() {
+    let group = nested_groups(DEPTH);
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+    let start = Instant::now();
+    let hits = (0..RAYS)
+        .filter(|_| !black_box(&group).intersects(black_box(&r)).is_empty())
+        .count();
+    let elapsed = start.elapsed();
+    println!(
+        "{RAYS} rays through {DEPTH} nested groups: {elapsed:?} ({:?} per ray, {hits} hits)",
+        elapsed / RAYS
+    );
+}
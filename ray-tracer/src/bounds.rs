@@ -31,6 +31,12 @@ impl Bounds {
         &self.max
     }
 
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|p| p.x().is_finite() && p.y().is_finite() && p.z().is_finite())
+    }
+
     fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
         let tmin_numerator = min - origin;
         let tmax_numerator = max - origin;
@@ -44,6 +50,10 @@ impl Bounds {
     }
 
     pub fn intersects(&self, ray: &Ray) -> bool {
+        self.intersects_range(ray).is_some()
+    }
+
+    pub fn intersects_range(&self, ray: &Ray) -> Option<(f64, f64)> {
         let (xtmin, xtmax) = Bounds::check_axis(
             ray.origin.x(),
             ray.direction.x(),
@@ -65,11 +75,11 @@ impl Bounds {
 
         let tmax = xtmax.min(ytmax.min(ztmax));
         if tmax < 0.0 {
-            false
+            None
         } else {
             let tmin = xtmin.max(ytmin.max(ztmin));
 
-            tmin <= tmax
+            (tmin <= tmax).then_some((tmin, tmax))
         }
     }
 
@@ -276,6 +286,27 @@ mod tests {
         assert_eq!(b.intersects(&r), result);
     }
 
+    #[parameterized(
+        from_pos_x = {Point::new(5.0, 0.5, 0.0), Vector::new(-1.0, 0.0, 0.0), Some((4.0, 6.0))},
+        from_neg_x = {Point::new(-5.0, 0.5, 0.0), Vector::new(1.0, 0.0, 0.0), Some((4.0, 6.0))},
+        from_pos_y = {Point::new(0.5, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0), Some((4.0, 6.0))},
+        from_neg_y = {Point::new(0.5, -5.0, 0.0), Vector::new(0.0, 1.0, 0.0), Some((4.0, 6.0))},
+        from_pos_z = {Point::new(0.5, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0), Some((4.0, 6.0))},
+        from_neg_z = {Point::new(0.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), Some((4.0, 6.0))},
+        from_inside = {Point::new(0.0, 0.5, 0.0), Vector::new(0.0, 0.0, 1.0), Some((-1.0, 1.0))},
+        behind = {Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0), None},
+        miss = {Point::new(2.0, 0.0, 2.0), Vector::new(0.0, 0.0, -1.0), None},
+    )]
+    fn the_range_of_a_ray_through_a_bounding_box(
+        origin: Point,
+        direction: Vector,
+        range: Option<(f64, f64)>,
+    ) {
+        let b = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let r = Ray::new(origin, direction);
+        assert_eq!(b.intersects_range(&r), range);
+    }
+
     #[parameterized(
         intersecting_1 = {Point::new(15.0, 1.0, 2.0), Vector::new(-1.0, 0.0, 0.0), true},
         intersecting_2 = {Point::new(-5.0, 1.0, 4.0), Vector::new(1.0, 0.0, 0.0), true},
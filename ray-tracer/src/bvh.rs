@@ -0,0 +1,155 @@
+use crate::{
+    bounds::Bounds,
+    rays::Ray,
+    shapes::Object,
+    tuples::{points::Point, Tuple},
+};
+
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone)]
+enum BvhNode {
+    Leaf {
+        bounds: Bounds,
+        objects: Vec<usize>,
+    },
+    Branch {
+        bounds: Bounds,
+        left: Box<BvhNode>,
+        right: Box<BvhNode>,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+    root: Option<BvhNode>,
+    unbounded: Vec<usize>,
+}
+
+fn center(bounds: &Bounds) -> Point {
+    let (min, max) = (bounds.min(), bounds.max());
+    Point::new(
+        (min.x() + max.x()) / 2.0,
+        (min.y() + max.y()) / 2.0,
+        (min.z() + max.z()) / 2.0,
+    )
+}
+
+impl BvhNode {
+    fn build(objects: &[Object], indices: Vec<usize>) -> Self {
+        let bounds = indices
+            .iter()
+            .fold(Bounds::default(), |b, &idx| b + *objects[idx].bounds());
+        if indices.len() <= LEAF_SIZE {
+            return BvhNode::Leaf {
+                bounds,
+                objects: indices,
+            };
+        }
+
+        let (left_box, _) = bounds.split();
+        let (left, right): (Vec<usize>, Vec<usize>) = indices
+            .iter()
+            .partition(|&&idx| left_box.contains_point(&center(objects[idx].bounds())));
+        if left.is_empty() || right.is_empty() {
+            return BvhNode::Leaf {
+                bounds,
+                objects: indices,
+            };
+        }
+        BvhNode::Branch {
+            bounds,
+            left: Box::new(Self::build(objects, left)),
+            right: Box::new(Self::build(objects, right)),
+        }
+    }
+
+    fn collect(&self, ray: &Ray, candidates: &mut Vec<usize>) {
+        match self {
+            BvhNode::Leaf { bounds, objects } => {
+                if bounds.intersects(ray) {
+                    candidates.extend(objects);
+                }
+            }
+            BvhNode::Branch {
+                bounds,
+                left,
+                right,
+            } => {
+                if bounds.intersects(ray) {
+                    left.collect(ray, candidates);
+                    right.collect(ray, candidates);
+                }
+            }
+        }
+    }
+}
+
+impl Bvh {
+    pub fn new(objects: &[Object]) -> Self {
+        let (bounded, unbounded): (Vec<usize>, Vec<usize>) =
+            (0..objects.len()).partition(|&idx| objects[idx].bounds().is_finite());
+        let root = if bounded.is_empty() {
+            None
+        } else {
+            Some(BvhNode::build(objects, bounded))
+        };
+        Self { root, unbounded }
+    }
+
+    pub fn candidates(&self, ray: &Ray) -> Vec<usize> {
+        let mut candidates = self.unbounded.clone();
+        if let Some(root) = &self.root {
+            root.collect(ray, &mut candidates);
+        }
+        candidates.sort_unstable();
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{shapes::ObjectBuilder, transformations::Transformation, tuples::vectors::Vector};
+
+    use super::*;
+
+    fn grid_of_spheres() -> Vec<Object> {
+        (0..27)
+            .map(|i| {
+                let (x, y, z) = ((i % 3) as f64, ((i / 3) % 3) as f64, (i / 9) as f64);
+                ObjectBuilder::new_sphere()
+                    .with_transform(
+                        Transformation::new_transform()
+                            .scaling(0.5, 0.5, 0.5)
+                            .translation(x * 3.0, y * 3.0, z * 3.0),
+                    )
+                    .build()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_ray_missing_all_geometry_visits_no_objects() {
+        let bvh = Bvh::new(&grid_of_spheres());
+        let r = Ray::new(Point::new(20.0, 20.0, -5.0), Vector::z_norm());
+        assert!(bvh.candidates(&r).is_empty());
+    }
+
+    #[test]
+    fn a_ray_only_visits_objects_along_its_path() {
+        let bvh = Bvh::new(&grid_of_spheres());
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        let candidates = bvh.candidates(&r);
+        assert!(candidates.len() < 27);
+        assert!([0, 9, 18].iter().all(|idx| candidates.contains(idx)));
+    }
+
+    #[test]
+    fn unbounded_objects_are_always_candidates() {
+        let mut objects = grid_of_spheres();
+        objects.push(ObjectBuilder::new_plane().build());
+        let bvh = Bvh::new(&objects);
+        let r = Ray::new(Point::new(20.0, 20.0, -5.0), Vector::z_norm());
+        assert_eq!(bvh.candidates(&r), vec![27]);
+    }
+}
@@ -1,12 +1,23 @@
-use std::{sync::Mutex, time::Instant};
+use std::{f64::consts::PI, sync::Mutex, time::Instant};
 
+use anyhow::{anyhow, Result};
 use rayon::iter::{ParallelBridge, ParallelIterator};
 
+use colo_rs::colors::Color;
+
 use crate::{
-    canvas::Canvas, rays::Ray, transformations::Transformation, tuples::points::Point,
-    tuples::Tuple, world::World,
+    canvas::Canvas,
+    rays::Ray,
+    shapes::Object,
+    transformations::Transformation,
+    tuples::{points::Point, vectors::Vector, Tuple},
+    world::World,
 };
 
+const AUTO_FRAME_FIELD_OF_VIEW: f64 = PI / 3.0;
+const DEFAULT_MAX_DEPTH: usize = 5;
+
+#[derive(Clone)]
 pub struct Camera {
     h_size: usize,
     v_size: usize,
@@ -15,6 +26,8 @@ pub struct Camera {
     half_width: f64,
     half_height: f64,
     pixel_size: f64,
+    samples: usize,
+    max_depth: usize,
 }
 
 impl Camera {
@@ -36,6 +49,8 @@ impl Camera {
             half_width,
             half_height,
             pixel_size,
+            samples: 1,
+            max_depth: DEFAULT_MAX_DEPTH,
         }
     }
 
@@ -44,9 +59,62 @@ impl Camera {
         self
     }
 
-    pub fn ray_for_pixel(&self, px: f64, py: f64) -> Ray {
-        let x_offset = (px + 0.5) * self.pixel_size;
-        let y_offset = (py + 0.5) * self.pixel_size;
+    pub fn with_size(self, h_size: usize, v_size: usize) -> Self {
+        Self {
+            samples: self.samples,
+            max_depth: self.max_depth,
+            ..Self::new(h_size, v_size, self.field_of_view).with_transform(self.transform)
+        }
+    }
+
+    // Each pixel is sampled on a samples x samples grid and averaged.
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn auto_frame(object: &Object, h_size: usize, v_size: usize) -> Result<Self> {
+        let camera = Self::new(h_size, v_size, AUTO_FRAME_FIELD_OF_VIEW);
+        let (center, radius) = object
+            .bounding_sphere()
+            .ok_or(anyhow!("Cannot frame an object with infinite bounds"))?;
+        let half_angle = f64::atan(camera.half_width.min(camera.half_height));
+        let distance = radius / f64::sin(half_angle);
+        let from = center + Vector::new(0.0, 0.0, -distance);
+        Ok(camera.with_transform(Transformation::view_transform(
+            from,
+            center,
+            Vector::y_norm(),
+        )))
+    }
+
+    /// Returns the ray from the camera through the centre of pixel `(x, y)`.
+    ///
+    /// ```
+    /// use std::f64::consts::PI;
+    ///
+    /// use ray_tracer::camera::Camera;
+    /// use ray_tracer::tuples::{points::Point, vectors::Vector, Tuple};
+    ///
+    /// let camera = Camera::new(201, 101, PI / 2.0);
+    /// let ray = camera.ray_for_pixel(100, 50);
+    /// assert_eq!(ray.origin, Point::zero());
+    /// assert_eq!(ray.direction, Vector::new(0.0, 0.0, -1.0));
+    /// ```
+    pub fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
+        self.ray_for_pixel_offset(x, y, 0.5, 0.5)
+    }
+
+    /// Like `ray_for_pixel`, but aims at `(dx, dy)` within the pixel, where
+    /// `(0, 0)` is its top-left corner and `(1, 1)` its bottom-right one.
+    pub fn ray_for_pixel_offset(&self, x: usize, y: usize, dx: f64, dy: f64) -> Ray {
+        let x_offset = (x as f64 + dx) * self.pixel_size;
+        let y_offset = (y as f64 + dy) * self.pixel_size;
         let world_x = self.half_width - x_offset;
         let world_y = self.half_height - y_offset;
         let pixel = self.transform.inverse().unwrap() * &Point::new(world_x, world_y, -1.0);
@@ -55,6 +123,22 @@ impl Camera {
         Ray::new(origin, direction)
     }
 
+    fn color_for_pixel(&self, w: &World, x: usize, y: usize) -> Color {
+        if self.samples == 1 {
+            return w.color_at(self.ray_for_pixel(x, y), self.max_depth);
+        }
+        let step = 1.0 / self.samples as f64;
+        let offset = |i: usize| (i as f64 + 0.5) * step;
+        let total: Color = (0..self.samples)
+            .flat_map(|j| (0..self.samples).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let ray = self.ray_for_pixel_offset(x, y, offset(i), offset(j));
+                w.color_at(ray, self.max_depth)
+            })
+            .sum();
+        total * (step * step)
+    }
+
     pub fn render(&self, w: World) -> Canvas {
         let image = Canvas::new(self.h_size, self.v_size);
 
@@ -67,8 +151,7 @@ impl Camera {
         let ys = 0..self.v_size;
         let cross = ys.flat_map(|y| xs.clone().map(move |x| (x, y)));
         cross.par_bridge().for_each(|(x, y)| {
-            let ray = self.ray_for_pixel(x as f64, y as f64);
-            let color = w.color_at(ray, 5);
+            let color = self.color_for_pixel(&w, x, y);
             let mut canvas = image_mutex.lock().unwrap();
             canvas.write_pixel(x, y, color);
         });
@@ -82,6 +165,26 @@ impl Camera {
     pub fn field_of_view(&self) -> f64 {
         self.field_of_view
     }
+
+    pub fn h_size(&self) -> usize {
+        self.h_size
+    }
+
+    pub fn v_size(&self) -> usize {
+        self.v_size
+    }
+
+    pub fn transform(&self) -> &Transformation {
+        &self.transform
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
 }
 
 #[cfg(test)]
@@ -89,9 +192,8 @@ mod tests {
     use std::f64::consts::PI;
 
     use approx_eq::ApproxEq;
-    use colo_rs::colors::Color;
 
-    use crate::{matrix::Matrix, tuples::vectors::Vector};
+    use crate::{matrix::Matrix, shapes::ObjectBuilder};
 
     use super::*;
 
@@ -122,7 +224,7 @@ mod tests {
     #[test]
     fn constructing_a_ray_through_the_center_of_the_canvas() {
         let c = Camera::new(201, 101, PI / 2.0);
-        let r = c.ray_for_pixel(100.0, 50.0);
+        let r = c.ray_for_pixel(100, 50);
         assert_eq!(r.origin, Point::zero());
         assert_eq!(r.direction, Vector::new(0.0, 0.0, -1.0));
     }
@@ -130,18 +232,31 @@ mod tests {
     #[test]
     fn constructing_a_ray_through_a_corner_of_the_canvas() {
         let c = Camera::new(201, 101, PI / 2.0);
-        let r = c.ray_for_pixel(0.0, 0.0);
+        let r = c.ray_for_pixel(0, 0);
         assert_eq!(r.origin, Point::zero());
         assert_eq!(r.direction, Vector::new(0.66519, 0.33259, -0.66851));
     }
 
+    #[test]
+    fn subpixel_offsets_are_measured_from_the_pixel_corner() {
+        let c = Camera::new(201, 101, PI / 2.0);
+        assert_eq!(
+            c.ray_for_pixel_offset(100, 50, 0.5, 0.5).direction,
+            c.ray_for_pixel(100, 50).direction
+        );
+        assert_eq!(
+            c.ray_for_pixel_offset(3, 4, 1.0, 1.0).direction,
+            c.ray_for_pixel_offset(4, 5, 0.0, 0.0).direction
+        );
+    }
+
     #[test]
     fn constructing_a_ray_when_the_camera_is_transformed() {
         let t = Transformation::new_transform()
             .translation(0.0, -2.0, 5.0)
             .rotation_y(PI / 4.0);
         let c = Camera::new(201, 101, PI / 2.0).with_transform(t);
-        let r = c.ray_for_pixel(100.0, 50.0);
+        let r = c.ray_for_pixel(100, 50);
         assert_eq!(r.origin, Point::new(0.0, 2.0, -5.0));
         assert_eq!(
             r.direction,
@@ -160,4 +275,59 @@ mod tests {
         let image = c.render(w);
         assert_eq!(image.pixel_at(5, 5), Color::new(0.38066, 0.47583, 0.2855));
     }
+
+    #[test]
+    fn supersampling_averages_colors_within_a_pixel() {
+        let w = World::default();
+        let t = Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::zero(),
+            Vector::y_norm(),
+        );
+        let c = Camera::new(11, 11, PI / 2.0).with_transform(t);
+        let aliased = c.clone().render(World::default());
+        let smoothed = c.with_samples(3).render(w);
+        assert_eq!(smoothed.pixel_at(0, 0), Color::black());
+        let edge = (0..11)
+            .find(|&x| aliased.pixel_at(x, 5) != Color::black())
+            .unwrap();
+        assert_ne!(smoothed.pixel_at(edge, 5), aliased.pixel_at(edge, 5));
+    }
+
+    #[test]
+    fn resizing_a_camera_keeps_its_render_settings() {
+        let c = Camera::new(10, 10, PI / 2.0)
+            .with_samples(4)
+            .with_max_depth(2)
+            .with_size(20, 20);
+        assert_eq!(c.h_size(), 20);
+        assert_eq!(c.samples(), 4);
+        assert_eq!(c.max_depth(), 2);
+    }
+
+    #[test]
+    fn auto_framing_a_unit_sphere() {
+        let s = ObjectBuilder::new_sphere().build();
+        let c = Camera::auto_frame(&s, 41, 41).unwrap();
+        let center = c.ray_for_pixel(20, 20);
+        assert_eq!(center.direction, Vector::z_norm());
+        assert!(!s.intersects(&center).is_empty());
+        assert!(s.intersects(&c.ray_for_pixel(0, 0)).is_empty());
+
+        let hits = (0..c.v_size)
+            .flat_map(|y| (0..c.h_size).map(move |x| (x, y)))
+            .filter(|(x, y)| !s.intersects(&c.ray_for_pixel(*x, *y)).is_empty())
+            .count();
+        let coverage = hits as f64 / (c.h_size * c.v_size) as f64;
+        let distance = center.origin.z().abs();
+        let image_radius = f64::tan(f64::asin(1.0 / distance)) / c.pixel_size;
+        let expected = PI * image_radius.powi(2) / (c.h_size * c.v_size) as f64;
+        assert!((coverage - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn auto_framing_an_unbounded_object_is_an_error() {
+        let plane = ObjectBuilder::new_plane().build();
+        assert!(Camera::auto_frame(&plane, 41, 41).is_err());
+    }
 }
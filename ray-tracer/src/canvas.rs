@@ -1,6 +1,9 @@
+use anyhow::Result;
 use colo_rs::colors::Color;
 
-#[derive(Clone)]
+use crate::ppm::PPM;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Canvas {
     width: usize,
     height: usize,
@@ -16,6 +19,22 @@ impl Canvas {
         }
     }
 
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> Color) -> Self {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn from_ppm(bytes: &[u8]) -> Result<Self> {
+        Ok(PPM::parse(bytes)?.into())
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
@@ -24,10 +43,14 @@ impl Canvas {
         self.height
     }
 
-    pub fn pixels(&self) -> &Vec<Color> {
+    pub fn pixels(&self) -> &[Color] {
         &self.pixels
     }
 
+    pub fn to_rgb8(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|c| c.as_255_bytes()).collect()
+    }
+
     pub fn pixels_mut(&mut self) -> &mut Vec<Color> {
         &mut self.pixels
     }
@@ -37,13 +60,66 @@ impl Canvas {
     }
 
     pub fn write_pixel(&mut self, x: usize, y: usize, c: Color) {
+        self.try_write_pixel(x, y, c);
+    }
+
+    pub fn try_write_pixel(&mut self, x: usize, y: usize, c: Color) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
         let idx = self.xy_to_idx(x, y);
         self.pixels[idx] = c;
+        true
     }
 
     pub fn pixel_at(&self, x: usize, y: usize) -> Color {
         self.pixels[self.xy_to_idx(x, y)]
     }
+
+    pub fn subcanvas(&self, x0: usize, y0: usize, w: usize, h: usize) -> Canvas {
+        let w = w.min(self.width.saturating_sub(x0));
+        let h = h.min(self.height.saturating_sub(y0));
+        Canvas::from_fn(w, h, |x, y| self.pixel_at(x0 + x, y0 + y))
+    }
+
+    pub fn blit(&mut self, src: &Canvas, x: usize, y: usize) {
+        let w = src.width.min(self.width.saturating_sub(x));
+        let h = src.height.min(self.height.saturating_sub(y));
+        for sy in 0..h {
+            for sx in 0..w {
+                self.write_pixel(x + sx, y + sy, src.pixel_at(sx, sy));
+            }
+        }
+    }
+
+    pub fn downsample(&self, factor: usize) -> Canvas {
+        assert!(
+            factor > 0 && self.width.is_multiple_of(factor) && self.height.is_multiple_of(factor),
+            "Cannot downsample a {}x{} canvas by a factor of {}",
+            self.width,
+            self.height,
+            factor
+        );
+        Canvas::from_fn(self.width / factor, self.height / factor, |x, y| {
+            let block = (0..factor)
+                .flat_map(|dy| (0..factor).map(move |dx| (x * factor + dx, y * factor + dy)))
+                .map(|(px, py)| self.pixel_at(px, py))
+                .sum::<Color>();
+            block / (factor * factor) as f64
+        })
+    }
+
+    pub fn tone_map_reinhard(&self) -> Canvas {
+        Self {
+            width: self.width,
+            height: self.height,
+            pixels: self
+                .pixels
+                .iter()
+                .map(|p| p.map(|c| c.max(0.0) / (1.0 + c.max(0.0))))
+                .collect(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -58,6 +134,18 @@ mod tests {
         assert_eq!(canvas.pixels.iter().all(|p| *p == Color::black()), true);
     }
 
+    #[test]
+    fn writing_out_of_bounds_pixels_is_ignored() {
+        let mut canvas = Canvas::new(10, 20);
+        canvas.write_pixel(10, 20, Color::red());
+        canvas.write_pixel(10, 0, Color::red());
+        canvas.write_pixel(0, 20, Color::red());
+        assert!(canvas.pixels.iter().all(|p| *p == Color::black()));
+        assert!(!canvas.try_write_pixel(10, 20, Color::red()));
+        assert!(canvas.try_write_pixel(9, 19, Color::red()));
+        assert_eq!(canvas.pixel_at(9, 19), Color::red());
+    }
+
     #[test]
     fn writing_pixel_to_a_canvas() {
         let mut canvas = Canvas::new(10, 20);
@@ -65,4 +153,129 @@ mod tests {
         assert_eq!(canvas.pixel_at(2, 3), Color::red());
         assert_eq!(canvas.pixel_at(1, 3), Color::black());
     }
+
+    #[test]
+    fn initializing_a_canvas_from_a_function() {
+        let canvas = Canvas::from_fn(4, 2, |x, _| Color::new(x as f64 / 4.0, 0.0, 0.0));
+        assert_eq!(canvas.width(), 4);
+        assert_eq!(canvas.height(), 2);
+        for y in 0..2 {
+            for x in 0..4 {
+                assert_eq!(canvas.pixel_at(x, y), Color::new(x as f64 / 4.0, 0.0, 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn reinhard_tone_mapping_compresses_highlights() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write_pixel(0, 0, Color::new(4.0, 0.0, 1.0));
+        canvas.write_pixel(1, 0, Color::new(-0.5, 0.25, 0.5));
+        let mapped = canvas.tone_map_reinhard();
+        assert_eq!(mapped.pixel_at(0, 0), Color::new(0.8, 0.0, 0.5));
+        assert_eq!(mapped.pixel_at(1, 0), Color::new(0.0, 0.2, 1.0 / 3.0));
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(4.0, 0.0, 1.0));
+    }
+
+    fn gradient() -> Canvas {
+        Canvas::from_fn(4, 4, |x, y| Color::new(x as f64 / 3.0, y as f64 / 3.0, 0.0))
+    }
+
+    #[test]
+    fn cropping_a_canvas() {
+        let cropped = gradient().subcanvas(2, 2, 2, 2);
+        assert_eq!(cropped.width(), 2);
+        assert_eq!(cropped.height(), 2);
+        assert_eq!(
+            cropped.pixel_at(0, 0),
+            Color::new(2.0 / 3.0, 2.0 / 3.0, 0.0)
+        );
+        assert_eq!(cropped.pixel_at(1, 0), Color::new(1.0, 2.0 / 3.0, 0.0));
+        assert_eq!(cropped.pixel_at(0, 1), Color::new(2.0 / 3.0, 1.0, 0.0));
+        assert_eq!(cropped.pixel_at(1, 1), Color::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn cropping_a_canvas_clamps_to_its_bounds() {
+        let cropped = gradient().subcanvas(3, 1, 5, 5);
+        assert_eq!(cropped.width(), 1);
+        assert_eq!(cropped.height(), 3);
+        assert_eq!(cropped.pixel_at(0, 2), Color::new(1.0, 1.0, 0.0));
+        assert_eq!(gradient().subcanvas(4, 0, 2, 2).width(), 0);
+    }
+
+    #[test]
+    fn downsampling_averages_blocks_of_pixels() {
+        let canvas = Canvas::from_fn(4, 4, |x, _| {
+            if x % 2 == 0 {
+                Color::black()
+            } else {
+                Color::white()
+            }
+        });
+        let small = canvas.downsample(2);
+        assert_eq!(small.width(), 2);
+        assert_eq!(small.height(), 2);
+        assert!(small
+            .pixels()
+            .iter()
+            .all(|p| *p == Color::new(0.5, 0.5, 0.5)));
+    }
+
+    #[test]
+    fn downsampling_by_one_keeps_the_canvas() {
+        assert_eq!(gradient().downsample(1), gradient());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot downsample a 4x4 canvas by a factor of 3")]
+    fn downsampling_requires_divisible_dimensions() {
+        gradient().downsample(3);
+    }
+
+    #[test]
+    fn blitting_a_canvas_onto_another() {
+        let mut canvas = Canvas::new(4, 4);
+        let mut sprite = Canvas::new(2, 2);
+        sprite.pixels_mut().fill(Color::red());
+        canvas.blit(&sprite, 1, 1);
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = if (1..3).contains(&x) && (1..3).contains(&y) {
+                    Color::red()
+                } else {
+                    Color::black()
+                };
+                assert_eq!(canvas.pixel_at(x, y), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn blitting_clips_out_of_bounds_pixels() {
+        let mut canvas = Canvas::new(4, 4);
+        let mut sprite = Canvas::new(2, 2);
+        sprite.pixels_mut().fill(Color::red());
+        canvas.blit(&sprite, 3, 3);
+        canvas.blit(&sprite, 5, 0);
+        assert_eq!(canvas.pixel_at(3, 3), Color::red());
+        assert_eq!(
+            canvas
+                .pixels()
+                .iter()
+                .filter(|p| **p == Color::red())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn converting_a_canvas_to_rgb_bytes() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.write_pixel(0, 0, Color::new(1.5, 0.5, -0.5));
+        let bytes = canvas.to_rgb8();
+        assert_eq!(bytes.len(), 5 * 3 * 3);
+        assert_eq!(&bytes[..3], &[255, 128, 0]);
+        assert!(bytes[3..].iter().all(|b| *b == 0));
+    }
 }
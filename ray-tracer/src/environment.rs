@@ -0,0 +1,112 @@
+use std::{f64::consts::PI, fs, path::Path};
+
+use anyhow::Result;
+use colo_rs::colors::Color;
+
+use crate::{
+    canvas::Canvas,
+    tuples::{vectors::Vector, Tuple},
+};
+
+const DEFAULT_SAMPLES: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct Environment {
+    map: Canvas,
+    samples: usize,
+}
+
+impl Environment {
+    pub fn new(map: Canvas) -> Self {
+        Self {
+            map,
+            samples: DEFAULT_SAMPLES,
+        }
+    }
+
+    pub fn from_ppm(path: &Path) -> Result<Self> {
+        Ok(Self::new(Canvas::from_ppm(&fs::read(path)?)?))
+    }
+
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn color_at(&self, direction: Vector) -> Color {
+        if self.map.width() == 0 || self.map.height() == 0 {
+            return Color::black();
+        }
+        let d = direction.normalize();
+        let u = 0.5 + f64::atan2(d.x(), -d.z()) / (2.0 * PI);
+        let v = d.y().clamp(-1.0, 1.0).acos() / PI;
+        let x = ((u * self.map.width() as f64) as usize).min(self.map.width() - 1);
+        let y = ((v * self.map.height() as f64) as usize).min(self.map.height() - 1);
+        self.map.pixel_at(x, y)
+    }
+
+    pub fn irradiance(&self, normal: Vector) -> Color {
+        let n = normal.normalize();
+        let helper = if n.x().abs() > 0.9 {
+            Vector::y_norm()
+        } else {
+            Vector::x_norm()
+        };
+        let tangent = (helper * n).normalize();
+        let bitangent = n * tangent;
+        let strata = ((self.samples as f64).sqrt().round() as usize).max(1);
+        let colors: Color = (0..strata)
+            .flat_map(|i| (0..strata).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                let u1 = (i as f64 + 0.5) / strata as f64;
+                let u2 = (j as f64 + 0.5) / strata as f64;
+                let r = u1.sqrt();
+                let phi = 2.0 * PI * u2;
+                let direction =
+                    tangent * (r * phi.cos()) + n * (1.0 - u1).sqrt() + bitangent * (r * phi.sin());
+                self.color_at(direction)
+            })
+            .sum();
+        colors / (strata * strata) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sky() -> Environment {
+        let mut map = Canvas::new(4, 2);
+        for x in 0..4 {
+            map.write_pixel(x, 0, Color::white());
+        }
+        Environment::new(map)
+    }
+
+    #[test]
+    fn looking_up_an_equirectangular_environment() {
+        let env = sky();
+        assert_eq!(env.color_at(Vector::new(0.3, 1.0, -0.2)), Color::white());
+        assert_eq!(env.color_at(Vector::new(0.3, -1.0, 0.2)), Color::black());
+    }
+
+    #[test]
+    fn an_empty_environment_is_black() {
+        let path = std::env::temp_dir().join(format!("{}-empty-env.ppm", std::process::id()));
+        fs::write(&path, "P3\n0 0\n255\n").unwrap();
+        let env = Environment::from_ppm(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(env.color_at(Vector::y_norm()), Color::black());
+        assert_eq!(env.irradiance(Vector::y_norm()), Color::black());
+    }
+
+    #[test]
+    fn irradiance_is_gathered_from_the_hemisphere_around_the_normal() {
+        let env = sky();
+        assert_eq!(env.irradiance(Vector::y_norm()), Color::white());
+        assert_eq!(env.irradiance(-Vector::y_norm()), Color::black());
+        let sideways = env.irradiance(Vector::x_norm());
+        assert_ne!(sideways, Color::white());
+        assert_ne!(sideways, Color::black());
+    }
+}
@@ -1,4 +1,7 @@
-use std::ops::{Deref, DerefMut, Index};
+use std::{
+    cmp::Ordering,
+    ops::{Deref, DerefMut, Index},
+};
 
 use approx_eq::EPSILON;
 
@@ -29,14 +32,24 @@ pub struct Computation<'a> {
     pub reflect_v: Vector,
     pub n1: f64,
     pub n2: f64,
+    pub wavelength: Option<f64>,
+    pub uv: Option<(f64, f64)>,
 }
 
 impl<'a> Computation<'a> {
+    fn sin2_t(&self) -> f64 {
+        let cos = self.eye_v.dot(self.normal_v);
+        (self.n1 / self.n2).powi(2) * (1.0 - cos.powi(2))
+    }
+
+    pub fn is_total_internal_reflection(&self) -> bool {
+        self.sin2_t() > 1.0
+    }
+
     pub fn schlick(&self) -> f64 {
         let mut cos = self.eye_v.dot(self.normal_v);
         if self.n1 > self.n2 {
-            let n = self.n1 / self.n2;
-            let sin2_t = n.powi(2) * (1.0 - cos.powi(2));
+            let sin2_t = self.sin2_t();
             if sin2_t > 1.0 {
                 return 1.0;
             }
@@ -46,6 +59,10 @@ impl<'a> Computation<'a> {
         let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
         r0 + (1.0 - r0) * (1.0 - cos).powi(5)
     }
+
+    pub fn reflectance(&self) -> f64 {
+        self.schlick()
+    }
 }
 
 impl<'a> Intersection<'a> {
@@ -65,6 +82,15 @@ impl<'a> Intersection<'a> {
     }
 
     pub fn prepare_computations(&self, r: Ray, xs: &Intersections) -> Computation {
+        self.prepare_computations_with_bias(r, xs, EPSILON)
+    }
+
+    pub fn prepare_computations_with_bias(
+        &self,
+        r: Ray,
+        xs: &Intersections,
+        bias: f64,
+    ) -> Computation<'_> {
         let t = self.t;
         let object = self.object;
         let point = r.position(t);
@@ -75,38 +101,41 @@ impl<'a> Intersection<'a> {
         if inside {
             normal_v = -normal_v;
         }
-        let over_point = point + normal_v * EPSILON;
-        let under_point = point - normal_v * EPSILON;
+        let over_point = point + normal_v * bias;
+        let under_point = point - normal_v * bias;
         let reflect_v = r.direction.reflect(normal_v);
 
+        let index_of = |containers: &[&Object]| match containers.last() {
+            Some(obj) => obj.material().refractive_index_at(r.wavelength),
+            None => 1.0,
+        };
+        let coincident = |x: &Intersection| (x.t - t).abs() < EPSILON;
         let mut n1 = 0.0;
         let mut n2 = 0.0;
-        let mut containers: Vec<&Object> = Vec::new();
-        for x in xs.iter() {
-            if x == self {
-                if containers.is_empty() {
-                    n1 = 1.0;
-                } else {
-                    let obj = *containers.last().unwrap();
-                    n1 = obj.material().refractive_index;
+        if xs.contains(self) {
+            // Surfaces meeting at the hit point are crossed together, so the
+            // order they were reported in cannot change n1 or n2. Thin surfaces
+            // enclose no volume, so crossing one leaves the containers as is.
+            let mut containers: Vec<&Object> = Vec::new();
+            let mut entered_hit = false;
+            for x in xs.iter() {
+                if coincident(x) && !entered_hit {
+                    n1 = index_of(&containers);
+                    entered_hit = true;
+                } else if entered_hit && !coincident(x) {
+                    break;
                 }
-            };
 
-            if containers.contains(&x.object) {
-                containers.retain(|o| *o != x.object);
-            } else {
-                containers.push(x.object);
-            }
-
-            if x == self {
-                if containers.is_empty() {
-                    n2 = 1.0;
+                if x.object.material().thin() {
+                    continue;
+                }
+                if containers.contains(&x.object) {
+                    containers.retain(|o| *o != x.object);
                 } else {
-                    let obj = *containers.last().unwrap();
-                    n2 = obj.material().refractive_index;
+                    containers.push(x.object);
                 }
-                break;
             }
+            n2 = index_of(&containers);
         }
 
         Computation {
@@ -121,6 +150,11 @@ impl<'a> Intersection<'a> {
             reflect_v,
             n1,
             n2,
+            wavelength: r.wavelength,
+            uv: self
+                .u
+                .zip(self.v)
+                .and_then(|(u, v)| object.texture_uv(u, v)),
         }
     }
 }
@@ -148,7 +182,7 @@ impl<'a> Intersections<'a> {
     }
 
     pub fn push_all(&mut self, xs: Intersections<'a>) {
-        xs.iter().for_each(|i| self.intersections.push(*i));
+        self.intersections.extend(xs.intersections);
     }
 
     pub fn hit(&self) -> Option<&Intersection> {
@@ -157,6 +191,37 @@ impl<'a> Intersections<'a> {
             .filter(|i| i.t > 0.0)
             .min_by(|i, j| i.t.total_cmp(&j.t))
     }
+
+    pub fn merge_sorted(self, other: Intersections<'a>) -> Self {
+        self.merge_sorted_by(other, |i1, i2| i1.t.total_cmp(&i2.t))
+    }
+
+    fn merge_sorted_by<F>(self, other: Intersections<'a>, mut compare: F) -> Self
+    where
+        F: FnMut(&Intersection<'a>, &Intersection<'a>) -> Ordering,
+    {
+        if other.is_empty() {
+            return self;
+        }
+        if self.is_empty() {
+            return other;
+        }
+        let mut merged = Vec::with_capacity(self.len() + other.len());
+        let mut left = self.intersections.into_iter().peekable();
+        let mut right = other.intersections.into_iter().peekable();
+        while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+            if compare(r, l) == Ordering::Less {
+                merged.extend(right.next());
+            } else {
+                merged.extend(left.next());
+            }
+        }
+        merged.extend(left);
+        merged.extend(right);
+        Self {
+            intersections: merged,
+        }
+    }
 }
 
 impl<'a> Deref for Intersections<'a> {
@@ -191,6 +256,8 @@ impl<'a> FromIterator<Intersection<'a>> for Intersections<'a> {
 
 #[cfg(test)]
 mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
     use approx_eq::{ApproxEq, EPSILON};
     use colo_rs::colors::Color;
 
@@ -413,6 +480,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn n1_and_n2_at_tangent_spheres_do_not_depend_on_order() {
+        let a = ObjectBuilder::new_glass_sphere().build();
+        let b = ObjectBuilder::new_glass_sphere()
+            .with_transform(Transformation::new_transform().translation(0.0, 0.0, 2.0))
+            .with_material(Material::new().with_refractive_index(2.0))
+            .build();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        for world_objects in [vec![a.clone(), b.clone()], vec![b.clone(), a.clone()]] {
+            let w = World::new().with_objects(world_objects);
+            let xs = w.intersect_world(r);
+            assert_eq!(xs.len(), 4);
+            for x in [xs[1], xs[2]] {
+                let comps = x.prepare_computations(r, &xs);
+                assert_eq!(comps.n1, 1.5);
+                assert_eq!(comps.n2, 2.0);
+            }
+            let comps = xs[3].prepare_computations(r, &xs);
+            assert_eq!(comps.n1, 2.0);
+            assert_eq!(comps.n2, 1.0);
+        }
+    }
+
     #[test]
     fn the_under_point_is_offset_below_the_surface() {
         let r = Ray::new(Point::new(0.0, 0.0, -0.5), Vector::z_norm());
@@ -461,4 +551,105 @@ mod tests {
         let reflectance = comps.schlick();
         assert!(reflectance.approx_eq(0.48873));
     }
+
+    #[test]
+    fn reflectance_under_total_internal_reflection() {
+        let shape = ObjectBuilder::new_glass_sphere().build();
+        let r = Ray::new(Point::new(0.0, 0.0, FRAC_1_SQRT_2), Vector::y_norm());
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(-FRAC_1_SQRT_2, &shape));
+        xs.push(Intersection::new(FRAC_1_SQRT_2, &shape));
+        let comps = xs[1].prepare_computations(r, &xs);
+        assert!(comps.is_total_internal_reflection());
+        assert_eq!(comps.reflectance(), 1.0);
+    }
+
+    #[test]
+    fn reflectance_with_a_perpendicular_viewing_angle() {
+        let shape = ObjectBuilder::new_glass_sphere().build();
+        let r = Ray::new(Point::zero(), Vector::y_norm());
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(-1.0, &shape));
+        xs.push(Intersection::new(1.0, &shape));
+        let comps = xs[1].prepare_computations(r, &xs);
+        assert!(!comps.is_total_internal_reflection());
+        assert!(comps.reflectance().approx_eq(0.04));
+    }
+
+    #[test]
+    fn reflectance_with_small_angle_and_n2_gt_n1() {
+        let shape = ObjectBuilder::new_glass_sphere().build();
+        let r = Ray::new(Point::new(0.0, 0.99, -2.0), Vector::z_norm());
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(1.8589, &shape));
+        let comps = xs[0].prepare_computations(r, &xs);
+        assert!(!comps.is_total_internal_reflection());
+        assert!(comps.reflectance().approx_eq(0.48873));
+    }
+
+    #[test]
+    fn merging_two_sorted_lists_of_intersections() {
+        let s1 = ObjectBuilder::new_sphere().build();
+        let s2 = ObjectBuilder::new_sphere().build();
+        let xs1: Intersections = [-1.0, 2.0, 3.0, 7.0]
+            .into_iter()
+            .map(|t| Intersection::new(t, &s1))
+            .collect();
+        let xs2: Intersections = [0.5, 2.0, 4.0]
+            .into_iter()
+            .map(|t| Intersection::new(t, &s2))
+            .collect();
+        let xs = xs1.merge_sorted(xs2);
+        let ts: Vec<f64> = xs.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![-1.0, 0.5, 2.0, 2.0, 3.0, 4.0, 7.0]);
+        assert!(std::ptr::eq(xs[2].object, &s1));
+        assert!(std::ptr::eq(xs[3].object, &s2));
+    }
+
+    #[test]
+    fn merging_sorted_intersections_compares_less_than_a_full_sort() {
+        let s = ObjectBuilder::new_sphere().build();
+        let xs1: Intersections = (0..50)
+            .map(|t| Intersection::new(2.0 * t as f64, &s))
+            .collect();
+        let xs2: Intersections = (0..50)
+            .map(|t| Intersection::new(2.0 * t as f64 + 1.0, &s))
+            .collect();
+        let mut sorted: Intersections = xs1.iter().chain(xs2.iter()).copied().collect();
+        let mut sort_comparisons = 0;
+        sorted.sort_by(|i1, i2| {
+            sort_comparisons += 1;
+            i1.t.total_cmp(&i2.t)
+        });
+        let mut merge_comparisons = 0;
+        let merged = xs1.merge_sorted_by(xs2, |i1, i2| {
+            merge_comparisons += 1;
+            i1.t.total_cmp(&i2.t)
+        });
+        assert_eq!(merged.len(), 100);
+        assert!(merged.iter().zip(sorted.iter()).all(|(m, s)| m.t == s.t));
+        assert!(merge_comparisons < 100);
+        assert!(merge_comparisons < sort_comparisons);
+    }
+
+    #[test]
+    fn a_ray_with_a_wavelength_uses_the_cauchy_refractive_index() {
+        let shape = ObjectBuilder::new_glass_sphere()
+            .with_material(
+                Material::new()
+                    .with_transparency(1.0)
+                    .with_refractive_index(1.5)
+                    .with_dispersion(0.004),
+            )
+            .build();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(4.0, &shape));
+        xs.push(Intersection::new(6.0, &shape));
+        let comps = xs[0].prepare_computations(r, &xs);
+        assert!(comps.n2.approx_eq(1.5));
+        let comps = xs[0].prepare_computations(r.with_wavelength(0.4), &xs);
+        assert!(comps.n2.approx_eq(1.525));
+        assert_eq!(comps.wavelength, Some(0.4));
+    }
 }
@@ -1,6 +1,8 @@
 pub mod bounds;
+pub mod bvh;
 pub mod camera;
 pub mod canvas;
+pub mod environment;
 pub mod intersections;
 pub mod lights;
 pub mod materials;
@@ -8,11 +10,15 @@ pub mod matrix;
 pub mod obj_parser;
 pub mod octree;
 pub mod patterns;
+pub mod png;
 pub mod ppm;
 pub mod rays;
 pub mod registry;
+pub mod render;
 pub mod shapes;
 pub mod transformations;
 pub mod tuples;
 pub mod world;
 pub mod yaml_loader;
+
+pub use render::{render_scene, RenderSettings, RenderStats};
@@ -1,6 +1,84 @@
 use colo_rs::colors::Color;
 
-use crate::tuples::points::Point;
+use crate::tuples::{points::Point, vectors::Vector, Tuple};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Light {
+    Point(PointLight),
+    Spot(SpotLight),
+    Directional(DirectionalLight),
+    Ambient(AmbientLight),
+    Area(AreaLight),
+}
+
+impl Light {
+    pub fn direction_from(&self, p: Point) -> Vector {
+        match self {
+            Light::Point(l) => (l.position - p).normalize(),
+            Light::Spot(l) => (l.position - p).normalize(),
+            Light::Area(l) => (l.position - p).normalize(),
+            Light::Directional(l) => -l.direction,
+            Light::Ambient(_) => Vector::zero(),
+        }
+    }
+
+    pub fn distance_from(&self, p: Point) -> f64 {
+        match self {
+            Light::Point(l) => (l.position - p).magnitude(),
+            Light::Spot(l) => (l.position - p).magnitude(),
+            Light::Area(l) => (l.position - p).magnitude(),
+            Light::Directional(_) => f64::INFINITY,
+            Light::Ambient(_) => 0.0,
+        }
+    }
+
+    pub fn intensity(&self) -> Color {
+        match self {
+            Light::Point(l) => l.intensity,
+            Light::Spot(l) => l.intensity,
+            Light::Directional(l) => l.intensity,
+            Light::Ambient(l) => l.intensity,
+            Light::Area(l) => l.intensity,
+        }
+    }
+
+    pub fn attenuation_at(&self, p: Point) -> f64 {
+        match self {
+            Light::Point(_) | Light::Directional(_) | Light::Ambient(_) | Light::Area(_) => 1.0,
+            Light::Spot(l) => l.attenuation_at(p),
+        }
+    }
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Self {
+        Light::Point(light)
+    }
+}
+
+impl From<SpotLight> for Light {
+    fn from(light: SpotLight) -> Self {
+        Light::Spot(light)
+    }
+}
+
+impl From<DirectionalLight> for Light {
+    fn from(light: DirectionalLight) -> Self {
+        Light::Directional(light)
+    }
+}
+
+impl From<AmbientLight> for Light {
+    fn from(light: AmbientLight) -> Self {
+        Light::Ambient(light)
+    }
+}
+
+impl From<AreaLight> for Light {
+    fn from(light: AreaLight) -> Self {
+        Light::Area(light)
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PointLight {
@@ -15,11 +93,140 @@ impl PointLight {
             intensity,
         }
     }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn intensity(&self) -> Color {
+        self.intensity
+    }
+}
+
+impl From<(Point, Color)> for PointLight {
+    fn from((position, intensity): (Point, Color)) -> Self {
+        PointLight::new(position, intensity)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Vector,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub intensity: Color,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Point,
+        direction: Vector,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Color,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+        }
+    }
+
+    pub fn attenuation_at(&self, p: Point) -> f64 {
+        let cos_angle = (p - self.position).normalize().dot(self.direction);
+        let cos_inner = self.inner_angle.cos();
+        let cos_outer = self.outer_angle.cos();
+        if cos_angle >= cos_inner {
+            1.0
+        } else if cos_angle <= cos_outer {
+            0.0
+        } else {
+            let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    pub direction: Vector,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, intensity: Color) -> Self {
+        Self {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientLight {
+    pub intensity: Color,
+}
+
+impl AmbientLight {
+    pub fn new(intensity: Color) -> Self {
+        Self { intensity }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AreaLight {
+    pub corner: Point,
+    pub uvec: Vector,
+    pub usteps: usize,
+    pub vvec: Vector,
+    pub vsteps: usize,
+    pub position: Point,
+    pub intensity: Color,
+}
+
+impl AreaLight {
+    pub fn new(
+        corner: Point,
+        full_uvec: Vector,
+        usteps: usize,
+        full_vvec: Vector,
+        vsteps: usize,
+        intensity: Color,
+    ) -> Self {
+        let usteps = usteps.max(1);
+        let vsteps = vsteps.max(1);
+        Self {
+            corner,
+            uvec: full_uvec / usteps as f64,
+            usteps,
+            vvec: full_vvec / vsteps as f64,
+            vsteps,
+            position: corner + full_uvec / 2.0 + full_vvec / 2.0,
+            intensity,
+        }
+    }
+
+    pub fn point_on(&self, u: usize, v: usize) -> Point {
+        self.corner + self.uvec * (u as f64 + 0.5) + self.vvec * (v as f64 + 0.5)
+    }
+
+    pub fn samples(&self) -> Vec<Point> {
+        (0..self.vsteps)
+            .flat_map(|v| (0..self.usteps).map(move |u| (u, v)))
+            .map(|(u, v)| self.point_on(u, v))
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tuples::Tuple;
+    use std::f64::consts::PI;
+
+    use approx_eq::ApproxEq;
+    use yare::parameterized;
 
     use super::*;
 
@@ -30,5 +237,82 @@ mod tests {
         let light = PointLight::new(position, intensity);
         assert_eq!(light.position, position);
         assert_eq!(light.intensity, intensity);
+        assert_eq!(light.position(), position);
+        assert_eq!(light.intensity(), intensity);
+    }
+
+    #[test]
+    fn a_point_light_can_be_built_from_a_position_and_intensity() {
+        let position = Point::new(-10.0, 10.0, -10.0);
+        let intensity = Color::new(0.5, 0.5, 0.5);
+        let light: PointLight = (position, intensity).into();
+        assert_eq!(light, PointLight::new(position, intensity));
+    }
+
+    #[test]
+    fn spot_light_attenuates_between_inner_and_outer_cones() {
+        let light = SpotLight::new(
+            Point::zero(),
+            Vector::new(0.0, -2.0, 0.0),
+            PI / 8.0,
+            PI / 4.0,
+            Color::white(),
+        );
+        assert_eq!(light.direction, Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(light.attenuation_at(Point::new(0.0, -5.0, 0.0)), 1.0);
+        assert_eq!(light.attenuation_at(Point::new(2.0, -1.0, 0.0)), 0.0);
+        assert_eq!(light.attenuation_at(Point::new(5.0, 1.0, 0.0)), 0.0);
+        let inside_penumbra = (3.0 * PI / 16.0).tan();
+        let attenuation = light.attenuation_at(Point::new(inside_penumbra, -1.0, 0.0));
+        assert!(attenuation > 0.0 && attenuation < 1.0);
+        assert!(Light::from(light)
+            .attenuation_at(Point::new(0.0, -1.0, 0.0))
+            .approx_eq(1.0));
+    }
+
+    #[test]
+    fn directional_light_shines_from_the_same_direction_everywhere() {
+        let light: Light =
+            DirectionalLight::new(Vector::new(0.0, -3.0, 0.0), Color::white()).into();
+        assert_eq!(light.direction_from(Point::zero()), Vector::y_norm());
+        assert_eq!(
+            light.direction_from(Point::new(100.0, -50.0, 7.0)),
+            Vector::y_norm()
+        );
+        assert_eq!(light.distance_from(Point::zero()), f64::INFINITY);
+    }
+
+    #[test]
+    fn area_light_is_split_into_cells() {
+        let light = AreaLight::new(
+            Point::zero(),
+            Vector::new(2.0, 0.0, 0.0),
+            4,
+            Vector::new(0.0, 0.0, 1.0),
+            2,
+            Color::white(),
+        );
+        assert_eq!(light.uvec, Vector::new(0.5, 0.0, 0.0));
+        assert_eq!(light.vvec, Vector::new(0.0, 0.0, 0.5));
+        assert_eq!(light.position, Point::new(1.0, 0.0, 0.5));
+        assert_eq!(light.samples().len(), 8);
+    }
+
+    #[parameterized(
+        first_cell = {0, 0, Point::new(0.25, 0.0, 0.25)},
+        second_u = {1, 0, Point::new(0.75, 0.0, 0.25)},
+        second_v = {0, 1, Point::new(0.25, 0.0, 0.75)},
+        last_cell = {3, 1, Point::new(1.75, 0.0, 0.75)},
+    )]
+    fn finding_a_point_on_an_area_light(u: usize, v: usize, expected: Point) {
+        let light = AreaLight::new(
+            Point::zero(),
+            Vector::new(2.0, 0.0, 0.0),
+            4,
+            Vector::new(0.0, 0.0, 1.0),
+            2,
+            Color::white(),
+        );
+        assert_eq!(light.point_on(u, v), expected);
     }
 }
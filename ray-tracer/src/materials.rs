@@ -1,23 +1,32 @@
 use crate::{
-    lights::PointLight,
+    intersections::Computation,
+    lights::Light,
     patterns::Pattern,
     shapes::Object,
     tuples::{points::Point, vectors::Vector},
 };
+use approx_eq::ApproxEq;
 use colo_rs::colors::Color;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Material {
-    pub ambient: f64,
-    pub diffuse: f64,
-    pub specular: f64,
-    pub shininess: f64,
-    pub pattern: Pattern,
-    pub reflective: f64,
-    pub transparency: f64,
-    pub refractive_index: f64,
-    pub cast_shadows: bool,
-    pub receive_shadows: bool,
+    ambient: f64,
+    diffuse: f64,
+    specular: f64,
+    shininess: f64,
+    pattern: Pattern,
+    reflective: f64,
+    transparency: f64,
+    refractive_index: f64,
+    cast_shadows: bool,
+    receive_shadows: bool,
+    anisotropy: f64,
+    anisotropy_direction: Vector,
+    dispersion: f64,
+    emission: Color,
+    reflective_color: Color,
+    fresnel: bool,
+    thin: bool,
 }
 
 impl Material {
@@ -33,26 +42,33 @@ impl Material {
             refractive_index: 1.0,
             cast_shadows: true,
             receive_shadows: true,
+            anisotropy: 0.0,
+            anisotropy_direction: Vector::x_norm(),
+            dispersion: 0.0,
+            emission: Color::black(),
+            reflective_color: Color::white(),
+            fresnel: false,
+            thin: false,
         }
     }
 
     pub fn with_ambient(mut self, ambient: f64) -> Self {
-        self.ambient = ambient;
+        self.ambient = ambient.max(0.0);
         self
     }
 
     pub fn with_diffuse(mut self, diffuse: f64) -> Self {
-        self.diffuse = diffuse;
+        self.diffuse = diffuse.max(0.0);
         self
     }
 
     pub fn with_specular(mut self, specular: f64) -> Self {
-        self.specular = specular;
+        self.specular = specular.max(0.0);
         self
     }
 
     pub fn with_shininess(mut self, shininess: f64) -> Self {
-        self.shininess = shininess;
+        self.shininess = shininess.max(0.0);
         self
     }
 
@@ -67,12 +83,27 @@ impl Material {
     }
 
     pub fn with_reflective(mut self, reflective: f64) -> Self {
-        self.reflective = reflective;
+        self.reflective = reflective.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_reflective_color(mut self, reflective_color: Color) -> Self {
+        self.reflective_color = reflective_color;
+        self
+    }
+
+    pub fn with_fresnel(mut self, fresnel: bool) -> Self {
+        self.fresnel = fresnel;
+        self
+    }
+
+    pub fn with_thin(mut self, thin: bool) -> Self {
+        self.thin = thin;
         self
     }
 
     pub fn with_transparency(mut self, transparency: f64) -> Self {
-        self.transparency = transparency;
+        self.transparency = transparency.clamp(0.0, 1.0);
         self
     }
 
@@ -81,6 +112,91 @@ impl Material {
         self
     }
 
+    pub fn with_dispersion(mut self, dispersion: f64) -> Self {
+        self.dispersion = dispersion.max(0.0);
+        self
+    }
+
+    pub fn with_emission(mut self, emission: Color) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    pub fn ambient(&self) -> f64 {
+        self.ambient
+    }
+
+    pub fn diffuse(&self) -> f64 {
+        self.diffuse
+    }
+
+    pub fn specular(&self) -> f64 {
+        self.specular
+    }
+
+    pub fn shininess(&self) -> f64 {
+        self.shininess
+    }
+
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    pub fn reflective(&self) -> f64 {
+        self.reflective
+    }
+
+    pub fn transparency(&self) -> f64 {
+        self.transparency
+    }
+
+    pub fn refractive_index(&self) -> f64 {
+        self.refractive_index
+    }
+
+    pub fn cast_shadows(&self) -> bool {
+        self.cast_shadows
+    }
+
+    pub fn receive_shadows(&self) -> bool {
+        self.receive_shadows
+    }
+
+    pub fn anisotropy(&self) -> f64 {
+        self.anisotropy
+    }
+
+    pub fn anisotropy_direction(&self) -> Vector {
+        self.anisotropy_direction
+    }
+
+    pub fn dispersion(&self) -> f64 {
+        self.dispersion
+    }
+
+    pub fn emission(&self) -> Color {
+        self.emission
+    }
+
+    pub fn reflective_color(&self) -> Color {
+        self.reflective_color
+    }
+
+    pub fn fresnel(&self) -> bool {
+        self.fresnel
+    }
+
+    pub fn thin(&self) -> bool {
+        self.thin
+    }
+
+    pub fn refractive_index_at(&self, wavelength: Option<f64>) -> f64 {
+        match wavelength {
+            Some(wavelength) => self.refractive_index + self.dispersion / wavelength.powi(2),
+            None => self.refractive_index,
+        }
+    }
+
     pub fn with_cast_shadows(mut self, cast_shadow: bool) -> Self {
         self.cast_shadows = cast_shadow;
         self
@@ -91,35 +207,100 @@ impl Material {
         self
     }
 
+    pub fn with_anisotropy(mut self, strength: f64, direction: Vector) -> Self {
+        self.anisotropy = strength.clamp(0.0, 1.0);
+        self.anisotropy_direction = direction;
+        self
+    }
+
+    fn anisotropic_factor(&self, light_vector: Vector, eye: Vector, normal: Vector) -> f64 {
+        let half = (light_vector + eye).normalize();
+        let half_dot_normal = half.dot(normal);
+        if half_dot_normal <= 0.0 {
+            return 0.0;
+        }
+        let tangent = self.anisotropy_direction - normal * self.anisotropy_direction.dot(normal);
+        let sin2_half = 1.0 - half_dot_normal * half_dot_normal;
+        if tangent.magnitude().approx_eq(0.0) || sin2_half.approx_eq(0.0) {
+            return half_dot_normal.powf(self.shininess);
+        }
+        let tangent = tangent.normalize();
+        let bitangent = normal * tangent;
+        let shininess_along = self.shininess * (1.0 - self.anisotropy);
+        let exponent = (shininess_along * half.dot(tangent).powi(2)
+            + self.shininess * half.dot(bitangent).powi(2))
+            / sin2_half;
+        half_dot_normal.powf(exponent)
+    }
+
     pub fn lighting(
         &self,
-        light: PointLight,
+        light: Light,
         position: Point,
         eye: Vector,
         normal: Vector,
         in_shadow: bool,
         object: &Object,
     ) -> Color {
-        let effective_color = &self.pattern.pattern_at_object(object, position) * &light.intensity;
+        let surface_color = self.pattern.pattern_at_object(object, position);
+        &self.emission
+            + &self.reflected_light(light, surface_color, position, eye, normal, in_shadow)
+    }
+
+    /// Like `lighting`, but UV-mapped patterns are sampled at the hit's texture UV
+    /// when the intersection carries one.
+    pub fn lighting_at_hit(&self, light: Light, comps: &Computation, in_shadow: bool) -> Color {
+        let surface_color = self
+            .pattern
+            .color_at_hit(comps.object, comps.over_point, comps.uv);
+        &self.emission
+            + &self.reflected_light(
+                light,
+                surface_color,
+                comps.over_point,
+                comps.eye_v,
+                comps.normal_v,
+                in_shadow,
+            )
+    }
+
+    pub(crate) fn reflected_light(
+        &self,
+        light: Light,
+        surface_color: Color,
+        position: Point,
+        eye: Vector,
+        normal: Vector,
+        in_shadow: bool,
+    ) -> Color {
+        let effective_color = &surface_color * &light.intensity();
         let ambient = effective_color * self.ambient;
-        let (diffuse, specular) = if in_shadow {
+        let attenuation = light.attenuation_at(position);
+        let (diffuse, specular) = if in_shadow || attenuation <= 0.0 {
             (Color::black(), Color::black())
         } else {
-            let light_vector = (light.position - position).normalize();
+            let light_vector = light.direction_from(position);
             let light_dot_normal = light_vector.dot(normal);
             if light_dot_normal < 0.0 {
                 (Color::black(), Color::black())
             } else {
-                let diffuse = effective_color * self.diffuse * light_dot_normal;
+                let diffuse = effective_color * self.diffuse * light_dot_normal * attenuation;
                 //let reflect_vector = (-light_vector).reflect(normal);
                 let reflect_vector = -light_vector - normal * 2.0 * -light_vector.dot(normal);
                 let reflect_dot_eye = reflect_vector.dot(eye);
-                let specular = if reflect_dot_eye <= 0.0 {
-                    Color::black()
+                let phong = if reflect_dot_eye <= 0.0 {
+                    0.0
+                } else {
+                    reflect_dot_eye.powf(self.shininess)
+                };
+                // Blending keeps the highlight continuous as anisotropy leaves 0.
+                let factor = if self.anisotropy > 0.0 {
+                    let anisotropic = self.anisotropic_factor(light_vector, eye, normal);
+                    phong + (anisotropic - phong) * self.anisotropy
                 } else {
-                    let factor = reflect_dot_eye.powf(self.shininess);
-                    light.intensity * self.specular * factor
+                    phong
                 };
+                let specular = light.intensity() * self.specular * factor * attenuation;
                 (diffuse, specular)
             }
         };
@@ -129,7 +310,18 @@ impl Material {
 
 #[cfg(test)]
 mod tests {
-    use crate::{shapes::ObjectBuilder, tuples::Tuple};
+    use std::f64::consts::PI;
+
+    use yare::parameterized;
+
+    use crate::{
+        intersections::{Intersection, Intersections},
+        lights::{PointLight, SpotLight},
+        patterns::uv_checker_pattern::UvMapping,
+        rays::Ray,
+        shapes::ObjectBuilder,
+        tuples::Tuple,
+    };
 
     use super::*;
 
@@ -152,7 +344,8 @@ mod tests {
         let position = Point::zero();
         let eyev = Vector::new(0.0, 0.0, -1.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light: Light =
+            PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0)).into();
         let in_shadow = false;
         let object = ObjectBuilder::new_test_shape().build();
         let result = m.lighting(light, position, eyev, normalv, in_shadow, &object);
@@ -165,7 +358,8 @@ mod tests {
         let position = Point::zero();
         let eyev = Vector::new(0.0, f64::sqrt(2.0) / 2.0, -f64::sqrt(2.0) / 2.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light: Light =
+            PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0)).into();
         let in_shadow = false;
         let object = ObjectBuilder::new_test_shape().build();
         let result = m.lighting(light, position, eyev, normalv, in_shadow, &object);
@@ -178,7 +372,8 @@ mod tests {
         let position = Point::zero();
         let eyev = Vector::new(0.0, 0.0, -1.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light: Light =
+            PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0)).into();
         let in_shadow = false;
         let object = ObjectBuilder::new_test_shape().build();
         let result = m.lighting(light, position, eyev, normalv, in_shadow, &object);
@@ -191,7 +386,8 @@ mod tests {
         let position = Point::zero();
         let eyev = Vector::new(0.0, -f64::sqrt(2.0) / 2.0, -f64::sqrt(2.0) / 2.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light: Light =
+            PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0)).into();
         let in_shadow = false;
         let object = ObjectBuilder::new_test_shape().build();
         let result = m.lighting(light, position, eyev, normalv, in_shadow, &object);
@@ -204,7 +400,8 @@ mod tests {
         let position = Point::zero();
         let eyev = Vector::new(0.0, 0.0, -1.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0));
+        let light: Light =
+            PointLight::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0)).into();
         let in_shadow = false;
         let object = ObjectBuilder::new_test_shape().build();
         let result = m.lighting(light, position, eyev, normalv, in_shadow, &object);
@@ -217,7 +414,8 @@ mod tests {
         let position = Point::zero();
         let eye_v = Vector::new(0.0, 0.0, -1.0);
         let normal_v = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light: Light =
+            PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0)).into();
         let in_shadow = true;
         let object = ObjectBuilder::new_test_shape().build();
         let result = m.lighting(light, position, eye_v, normal_v, in_shadow, &object);
@@ -236,7 +434,7 @@ mod tests {
             .with_specular(0.0);
         let eye_v = Vector::new(0.0, 0.0, -1.0);
         let normal_v = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
+        let light: Light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()).into();
         let object1 = ObjectBuilder::new_test_shape().build();
         let object2 = ObjectBuilder::new_test_shape().build();
 
@@ -272,4 +470,164 @@ mod tests {
         assert_eq!(m.transparency, 0.0);
         assert_eq!(m.refractive_index, 1.0);
     }
+
+    #[test]
+    fn lighting_outside_a_spot_light_cone_leaves_only_ambient() {
+        let m = Material::new();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light: Light = SpotLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Vector::new(0.0, 0.0, 1.0),
+            PI / 16.0,
+            PI / 8.0,
+            Color::white(),
+        )
+        .into();
+        let object = ObjectBuilder::new_test_shape().build();
+        let lit = m.lighting(light, Point::zero(), eyev, normalv, false, &object);
+        assert_eq!(lit, Color::new(1.9, 1.9, 1.9));
+        let dark = m.lighting(
+            light,
+            Point::new(5.0, 0.0, 0.0),
+            eyev,
+            normalv,
+            false,
+            &object,
+        );
+        assert_eq!(dark, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn anisotropic_highlights_stretch_along_the_anisotropy_direction() {
+        let position = Point::zero();
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light: Light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()).into();
+        let object = ObjectBuilder::new_test_shape().build();
+        let eye_along = Vector::new(0.1, 0.0, -1.0).normalize();
+        let eye_across = Vector::new(0.0, 0.1, -1.0).normalize();
+        let m = Material::new().with_ambient(0.0).with_diffuse(0.0);
+        let iso_along = m.lighting(light, position, eye_along, normalv, false, &object);
+        let iso_across = m.lighting(light, position, eye_across, normalv, false, &object);
+        assert_eq!(iso_along, iso_across);
+        let m = m.with_anisotropy(0.9, Vector::x_norm());
+        let light_vector = light.direction_from(position);
+        let along = m.anisotropic_factor(light_vector, eye_along, normalv);
+        let across = m.anisotropic_factor(light_vector, eye_across, normalv);
+        assert!(along > across);
+        let aniso_along = m.lighting(light, position, eye_along, normalv, false, &object);
+        assert_ne!(aniso_along, iso_along);
+    }
+
+    #[test]
+    fn the_highlight_is_continuous_as_anisotropy_leaves_zero() {
+        let position = Point::zero();
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let eyev = Vector::new(0.1, 0.0, -1.0).normalize();
+        let light: Light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()).into();
+        let object = ObjectBuilder::new_test_shape().build();
+        let m = Material::new().with_ambient(0.0).with_diffuse(0.0);
+        let isotropic = m.lighting(light, position, eyev, normalv, false, &object);
+        let barely = m
+            .with_anisotropy(0.001, Vector::x_norm())
+            .lighting(light, position, eyev, normalv, false, &object);
+        assert!(barely.approx_eq_within(&isotropic, 0.001));
+        assert_ne!(isotropic, Color::black());
+    }
+
+    #[test]
+    fn an_emissive_material_glows_regardless_of_light() {
+        let m = Material::new()
+            .with_color(Color::black())
+            .with_emission(Color::white());
+        let object = ObjectBuilder::new_sphere().build();
+        let position = Point::zero();
+        let eye = -Vector::z_norm();
+        let normal = -Vector::z_norm();
+        let lights: [Light; 2] = [
+            PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()).into(),
+            PointLight::new(Point::new(0.0, 0.0, 10.0), Color::black()).into(),
+        ];
+        for light in lights {
+            for in_shadow in [false, true] {
+                let result = m.lighting(light, position, eye, normal, in_shadow, &object);
+                assert_eq!(result.as_255_bytes(), [255, 255, 255]);
+            }
+        }
+    }
+
+    #[test]
+    fn lighting_at_a_hit_samples_uv_patterns_by_the_texture_uv() {
+        let m = Material::new().with_pattern(Pattern::new_uv_checker(
+            4.0,
+            4.0,
+            Color::black(),
+            Color::white(),
+            UvMapping::Planar,
+        ));
+        let tri = ObjectBuilder::new_smooth_triangle()
+            .set_uvs([(0.0, 0.0), (0.5, 0.0), (0.0, 0.5)])
+            .with_material(m.clone())
+            .build();
+        let light: Light =
+            PointLight::new(Point::new(0.0, 0.5, -10.0), Color::new(1.0, 1.0, 1.0)).into();
+        let r = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::z_norm());
+        let xs = Intersections::new();
+        let shade = |u, v| {
+            let hit = Intersection::new(2.0, &tri).with_uv(u, v);
+            let comps = hit.prepare_computations(r, &xs);
+            m.lighting_at_hit(light, &comps, false)
+        };
+        // Barycentrics (0.6, 0.1) map to texture (0.3, 0.05), a white square,
+        // while sampling the raw barycentrics would land on a black one.
+        let dark = shade(0.2, 0.2);
+        let bright = shade(0.6, 0.1);
+        assert_ne!(dark, bright);
+        assert_eq!(dark, Color::black());
+    }
+
+    #[test]
+    fn lighting_at_a_hit_without_uv_matches_lighting() {
+        let m = Material::new();
+        let object = ObjectBuilder::new_sphere().with_material(m.clone()).build();
+        let light: Light =
+            PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0)).into();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        let xs = object.intersects(&r);
+        let comps = xs[0].prepare_computations(r, &xs);
+        assert_eq!(
+            m.lighting_at_hit(light, &comps, false),
+            m.lighting(
+                light,
+                comps.over_point,
+                comps.eye_v,
+                comps.normal_v,
+                false,
+                &object
+            )
+        );
+    }
+
+    #[parameterized(
+        negative = {-0.5, 0.0},
+        in_range = {0.5, 0.5},
+        above_one = {1.5, 1.0},
+    )]
+    fn reflectivity_is_clamped_to_the_unit_range(reflective: f64, expected: f64) {
+        let m = Material::new().with_reflective(reflective);
+        assert_eq!(m.reflective(), expected);
+    }
+
+    #[test]
+    fn lighting_coefficients_cannot_be_negative() {
+        let m = Material::new()
+            .with_ambient(-1.0)
+            .with_diffuse(-1.0)
+            .with_specular(-1.0)
+            .with_transparency(-1.0);
+        assert_eq!(m.ambient(), 0.0);
+        assert_eq!(m.diffuse(), 0.0);
+        assert_eq!(m.specular(), 0.0);
+        assert_eq!(m.transparency(), 0.0);
+    }
 }
@@ -6,16 +6,29 @@ use crate::tuples::Tuple;
 
 #[derive(Debug, Clone)]
 pub struct Matrix {
-    data: Vec<Vec<f64>>,
+    size: usize,
+    data: Vec<f64>,
 }
 
 impl Matrix {
     pub fn new(data: Vec<Vec<f64>>) -> Self {
-        Self { data }
+        let size = data.len();
+        assert!(
+            data.iter().all(|row| row.len() == size),
+            "Matrix rows must have {} columns",
+            size
+        );
+        Self {
+            size,
+            data: data.concat(),
+        }
     }
 
     pub fn zero(size: usize) -> Self {
-        Self::new(vec![vec![0.0; size]; size])
+        Self {
+            size,
+            data: vec![0.0; size * size],
+        }
     }
 
     pub fn identity(size: usize) -> Self {
@@ -27,7 +40,7 @@ impl Matrix {
     }
 
     pub fn size(&self) -> usize {
-        self.data.len()
+        self.size
     }
 
     pub fn transpose(&self) -> Self {
@@ -41,6 +54,24 @@ impl Matrix {
     }
 
     pub fn determinant(&self) -> f64 {
+        if self.is_affine() {
+            self.affine_determinant()
+        } else {
+            self.general_determinant()
+        }
+    }
+
+    fn is_affine(&self) -> bool {
+        self.size() == 4 && self.data[12..] == [0.0, 0.0, 0.0, 1.0]
+    }
+
+    fn affine_determinant(&self) -> f64 {
+        self[(0, 0)] * (self[(1, 1)] * self[(2, 2)] - self[(1, 2)] * self[(2, 1)])
+            - self[(0, 1)] * (self[(1, 0)] * self[(2, 2)] - self[(1, 2)] * self[(2, 0)])
+            + self[(0, 2)] * (self[(1, 0)] * self[(2, 1)] - self[(1, 1)] * self[(2, 0)])
+    }
+
+    fn general_determinant(&self) -> f64 {
         if self.size() == 2 {
             self[(0, 0)] * self[(1, 1)] - self[(0, 1)] * self[(1, 0)]
         } else {
@@ -95,18 +126,34 @@ impl Matrix {
         }
     }
 
+    pub fn preserves_w<T: Tuple>(&self, t: &T) -> bool {
+        let w = self[(3, 0)] * t.x()
+            + self[(3, 1)] * t.y()
+            + self[(3, 2)] * t.z()
+            + self[(3, 3)] * t.w();
+        w.approx_eq(T::W)
+    }
+
     pub fn from(values: Vec<f64>, size: usize) -> Matrix {
-        let data = values
-            .chunks(size)
-            .map(|r| {
-                let mut row = Vec::new();
-                for val in r {
-                    row.push(*val);
-                }
-                row
-            })
-            .collect::<Vec<_>>();
-        Matrix::new(data)
+        assert_eq!(
+            values.len(),
+            size * size,
+            "Matrix needs {} values",
+            size * size
+        );
+        Matrix { size, data: values }
+    }
+
+    fn offset(&self, (row, col): (usize, usize)) -> usize {
+        assert!(
+            row < self.size && col < self.size,
+            "Index ({}, {}) out of bounds for a {}x{} matrix",
+            row,
+            col,
+            self.size,
+            self.size
+        );
+        row * self.size + col
     }
 }
 
@@ -114,30 +161,25 @@ impl Index<(usize, usize)> for Matrix {
     type Output = f64;
 
     fn index(&self, index: (usize, usize)) -> &Self::Output {
-        self.data.get(index.0).unwrap().get(index.1).unwrap()
+        &self.data[self.offset(index)]
     }
 }
 
 impl IndexMut<(usize, usize)> for Matrix {
     fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        self.data
-            .get_mut(index.0)
-            .unwrap()
-            .get_mut(index.1)
-            .unwrap()
+        let offset = self.offset(index);
+        &mut self.data[offset]
     }
 }
 
 impl PartialEq for Matrix {
     fn eq(&self, other: &Self) -> bool {
-        for row in 0..self.data.len() {
-            for col in 0..self.data.len() {
-                if !self[(row, col)].approx_eq(other[(row, col)]) {
-                    return false;
-                }
-            }
-        }
-        true
+        self.size == other.size
+            && self
+                .data
+                .iter()
+                .zip(&other.data)
+                .all(|(a, b)| a.approx_eq(*b))
     }
 }
 
@@ -145,15 +187,13 @@ impl Sub for &Matrix {
     type Output = Matrix;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        let mut m: Vec<Vec<f64>> = Vec::new();
-        let mut r: Vec<f64> = Vec::new();
-        for row in 0..self.size() {
-            for col in 0..self.size() {
-                r.push(self[(row, col)] - rhs[(row, col)]);
-            }
-            m.push(r.clone());
-        }
-        Matrix::new(m)
+        let data = self
+            .data
+            .iter()
+            .zip(&rhs.data)
+            .map(|(a, b)| a - b)
+            .collect();
+        Matrix::from(data, self.size())
     }
 }
 
@@ -201,7 +241,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::tuples::points::Point;
+    use std::f64::consts::PI;
+
+    use crate::{
+        transformations::Transformation,
+        tuples::{points::Point, vectors::Vector},
+    };
 
     use super::*;
 
@@ -310,6 +355,35 @@ mod tests {
         assert_eq!(&m * &p, Point::new(18.0, 24.0, 33.0));
     }
 
+    #[test]
+    fn a_point_keeps_w_through_affine_transforms() {
+        let t = Transformation::new_transform()
+            .rotation_x(PI / 3.0)
+            .shearing(1.0, 0.0, 0.5, 0.0, 0.0, 2.0)
+            .scaling(2.0, 3.0, 4.0)
+            .translation(5.0, -2.0, 1.0)
+            .matrix;
+        let p = Point::new(1.0, 2.0, 3.0);
+        let v = Vector::new(1.0, 2.0, 3.0);
+        assert!(t.preserves_w(&p));
+        assert!(t.preserves_w(&v));
+        let inverse = t.inverse().unwrap();
+        assert!(inverse.preserves_w(&p));
+        assert!(inverse.preserves_w(&v));
+    }
+
+    #[test]
+    fn a_projective_matrix_changing_w_is_detected() {
+        let m = Matrix::new(vec![
+            vec![1.0, 0.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 1.0, 0.0],
+            vec![0.0, 0.0, 1.0, 0.0],
+        ]);
+        assert!(!m.preserves_w(&Point::new(1.0, 2.0, 3.0)));
+        assert!(!m.preserves_w(&Vector::new(1.0, 2.0, 3.0)));
+    }
+
     #[test]
     fn multiplying_a_matrix_for_identity_matrix_gives_back_the_same() {
         let m = Matrix::new(vec![
@@ -559,4 +633,31 @@ mod tests {
         let i2 = i2.unwrap();
         assert_eq!(&m3 * &i2, m1);
     }
+
+    #[test]
+    fn affine_determinant_matches_the_general_one() {
+        let transforms = [
+            Transformation::new_transform(),
+            Transformation::new_transform().translation(1.0, -2.0, 3.0),
+            Transformation::new_transform()
+                .scaling(2.0, 3.0, 0.5)
+                .rotation_x(PI / 3.0),
+            Transformation::new_transform()
+                .rotation_y(PI / 5.0)
+                .shearing(1.0, 0.5, 0.0, 2.0, 0.25, 1.0)
+                .translation(5.0, 0.0, -1.0),
+            Transformation::new_transform()
+                .reflect_z()
+                .rotation_z(PI / 7.0)
+                .scaling(-1.5, 4.0, 2.0),
+            Transformation::new_transform().scaling(0.0, 1.0, 1.0),
+        ];
+        for t in transforms {
+            assert!(t.matrix.is_affine());
+            assert!(t
+                .matrix
+                .affine_determinant()
+                .approx_eq(t.matrix.general_determinant()));
+        }
+    }
 }
@@ -1,12 +1,16 @@
 use std::{
     collections::BTreeMap,
-    fs::File,
+    fmt::Write,
+    fs::{self, File},
     io::{BufReader, Read},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
+use colo_rs::colors::Color;
+
 use crate::{
-    shapes::{ObjectBuilder, WithGroup, WithShape},
+    materials::Material,
+    shapes::{Object, ObjectBuilder, WithGroup, WithShape},
     tuples::{points::Point, vectors::Vector, Tuple},
 };
 use anyhow::{anyhow, Result};
@@ -16,6 +20,9 @@ enum OBJElement {
     Face(Vec<FaceInfo>),
     Group(String),
     Normal(Vector),
+    TextureVertex((f64, f64)),
+    MaterialLibrary(String),
+    UseMaterial(String),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -28,6 +35,8 @@ pub struct FaceInfo {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Face {
     group: Option<String>,
+    material: Option<String>,
+    indices: Vec<usize>,
     vertices: Vec<Point>,
     normals: Vec<Vector>,
     textures: Vec<usize>,
@@ -37,6 +46,8 @@ impl Face {
     fn new() -> Self {
         Self {
             group: None,
+            material: None,
+            indices: Vec::new(),
             vertices: Vec::new(),
             normals: Vec::new(),
             textures: Vec::new(),
@@ -65,6 +76,12 @@ impl Face {
         }
     }
 
+    fn geometric_normal(&self) -> Vector {
+        let e1 = self.vertices[1] - self.vertices[0];
+        let e2 = self.vertices[2] - self.vertices[0];
+        (e2 * e1).normalize()
+    }
+
     fn push_vertex(&mut self, v: Point) {
         self.vertices.push(v)
     }
@@ -83,34 +100,149 @@ pub struct OBJParser {
     vertices: Vec<Point>,
     faces: Vec<Face>,
     normals: Vec<Vector>,
+    texture_vertices: Vec<(f64, f64)>,
+    material_libraries: Vec<String>,
+    materials: BTreeMap<String, Material>,
 }
 
 impl OBJParser {
     pub fn load_file(path: &PathBuf) -> Result<Self> {
-        let file = File::open(path).unwrap();
+        let file =
+            File::open(path).map_err(|e| anyhow!("Cannot open {}: {}", path.display(), e))?;
         let mut reader = BufReader::new(file);
         let mut input = String::new();
         reader.read_to_string(&mut input)?;
-        Ok(Self::parse(&input))
+        let mut parser = Self::parse(&input)?;
+        let dir = path.parent().map(PathBuf::from).unwrap_or_default();
+        for library in parser.material_libraries.clone() {
+            let library = dir.join(&library);
+            let mtl = fs::read_to_string(&library)
+                .map_err(|e| anyhow!("Cannot read {}: {}", library.display(), e))?;
+            parser = parser.with_material_library(&mtl);
+        }
+        Ok(parser)
     }
 
-    pub fn parse(input: &str) -> Self {
+    pub fn write_group(obj: &Object, path: &Path) -> Result<()> {
+        fs::write(path, Self::to_obj_string(obj))
+            .map_err(|e| anyhow!("Cannot write {}: {}", path.display(), e))
+    }
+
+    pub fn to_obj_string(obj: &Object) -> String {
+        let mut out = String::new();
+        let mut vertices = 0;
+        let mut normals = 0;
+        let mut groups = vec![obj];
+        let mut group_count = 0;
+        while let Some(group) = groups.pop() {
+            if !std::ptr::eq(group, obj) {
+                group_count += 1;
+                match group.group().and_then(|g| g.name()) {
+                    Some(name) => writeln!(out, "g {}", name).unwrap(),
+                    None => writeln!(out, "g Group{}", group_count).unwrap(),
+                }
+            }
+            for child in group.children() {
+                if child.group().is_some() {
+                    continue;
+                }
+                let (Some(p1), Some(p2), Some(p3)) = (child.p1(), child.p2(), child.p3()) else {
+                    continue;
+                };
+                for p in [p1, p2, p3] {
+                    let p = child.object_to_world(p);
+                    writeln!(out, "v {} {} {}", p.x(), p.y(), p.z()).unwrap();
+                }
+                let v = vertices;
+                vertices += 3;
+                if let (Some(n1), Some(n2), Some(n3)) = (child.n1(), child.n2(), child.n3()) {
+                    for n in [n1, n2, n3] {
+                        let n = child.normal_to_world(n);
+                        writeln!(out, "vn {} {} {}", n.x(), n.y(), n.z()).unwrap();
+                    }
+                    let n = normals;
+                    normals += 3;
+                    writeln!(
+                        out,
+                        "f {}//{} {}//{} {}//{}",
+                        v + 1,
+                        n + 1,
+                        v + 2,
+                        n + 2,
+                        v + 3,
+                        n + 3
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(out, "f {} {} {}", v + 1, v + 2, v + 3).unwrap();
+                }
+            }
+            groups.extend(
+                group
+                    .children()
+                    .iter()
+                    .rev()
+                    .filter(|child| child.group().is_some()),
+            );
+        }
+        out
+    }
+
+    pub fn with_material_library(mut self, input: &str) -> Self {
+        self.materials.extend(parse_mtl(input));
+        self
+    }
+
+    pub fn parse(input: &str) -> Result<Self> {
         let mut lines_skipped = 0;
         let mut vertices = Vec::new();
         let mut faces = Vec::new();
         let mut current_group = None;
+        let mut current_material = None;
         let mut normals = Vec::new();
+        let mut texture_vertices = Vec::new();
+        let mut material_libraries = Vec::new();
         for line in input.lines() {
             let line_vec: Vec<&str> = line.split_whitespace().collect();
             match parse_line(&line_vec) {
                 Ok(OBJElement::Vertex(p)) => vertices.push(p),
                 Ok(OBJElement::Face(fv)) => {
+                    for info in fv.iter() {
+                        if info.vertex_index == 0 || info.vertex_index > vertices.len() {
+                            return Err(anyhow!(OBJParserError::ObjectNotFound(
+                                "Vertex".into(),
+                                info.vertex_index
+                            )));
+                        }
+                        if let Some(vn) = info.vertex_normal_index {
+                            if vn == 0 || vn > normals.len() {
+                                return Err(anyhow!(OBJParserError::ObjectNotFound(
+                                    "Normal".into(),
+                                    vn
+                                )));
+                            }
+                        }
+                        // Files without any `vt` lines keep ignoring texture
+                        // indices, as the book's face-with-normals example expects.
+                        if let Some(vt) = info.texture_vertex_index {
+                            if !texture_vertices.is_empty()
+                                && (vt == 0 || vt > texture_vertices.len())
+                            {
+                                return Err(anyhow!(OBJParserError::ObjectNotFound(
+                                    "Texture vertex".into(),
+                                    vt
+                                )));
+                            }
+                        }
+                    }
                     if let Some((p1, rest)) = fv.split_first() {
                         for window in rest.windows(2) {
                             let p2 = window[0];
                             let p3 = window[1];
                             let mut f = Face::new();
                             f.group = current_group.clone();
+                            f.material = current_material.clone();
+                            f.indices = vec![p1.vertex_index, p2.vertex_index, p3.vertex_index];
                             f.push_vertex(vertices[p1.vertex_index - 1]);
                             f.push_vertex(vertices[p2.vertex_index - 1]);
                             f.push_vertex(vertices[p3.vertex_index - 1]);
@@ -138,15 +270,21 @@ impl OBJParser {
                 }
                 Ok(OBJElement::Group(name)) => current_group = Some(name),
                 Ok(OBJElement::Normal(vn)) => normals.push(vn),
+                Ok(OBJElement::TextureVertex(vt)) => texture_vertices.push(vt),
+                Ok(OBJElement::MaterialLibrary(name)) => material_libraries.push(name),
+                Ok(OBJElement::UseMaterial(name)) => current_material = Some(name),
                 Err(_) => lines_skipped += 1,
             }
         }
-        Self {
+        Ok(Self {
             lines_skipped,
             vertices,
             faces,
             normals,
-        }
+            texture_vertices,
+            material_libraries,
+            materials: BTreeMap::new(),
+        })
     }
 
     pub fn get_vertex(&self, idx: usize) -> Result<Point> {
@@ -179,7 +317,49 @@ impl OBJParser {
         }
     }
 
+    pub fn get_texture_vertex(&self, idx: usize) -> Result<(f64, f64)> {
+        if idx == 0 || idx > self.texture_vertices.len() {
+            Err(anyhow!(OBJParserError::ObjectNotFound(
+                "Texture vertex".into(),
+                idx
+            )))
+        } else {
+            Ok(self.texture_vertices[idx - 1])
+        }
+    }
+
     pub fn into_group(&self) -> ObjectBuilder<WithShape, WithGroup> {
+        self.build_group(None)
+    }
+
+    pub fn into_smoothed_group(&self) -> ObjectBuilder<WithShape, WithGroup> {
+        self.build_group(Some(&self.vertex_normals()))
+    }
+
+    fn vertex_normals(&self) -> Vec<Vector> {
+        let mut normals = vec![Vector::zero(); self.vertices.len()];
+        for face in self.faces.iter() {
+            let n = face.geometric_normal();
+            for idx in face.indices.iter() {
+                normals[idx - 1] = normals[idx - 1] + n;
+            }
+        }
+        normals
+            .into_iter()
+            .map(|n| {
+                if n == Vector::zero() {
+                    n
+                } else {
+                    n.normalize()
+                }
+            })
+            .collect()
+    }
+
+    fn build_group(
+        &self,
+        vertex_normals: Option<&[Vector]>,
+    ) -> ObjectBuilder<WithShape, WithGroup> {
         let mut groups: BTreeMap<Option<String>, Vec<Face>> = BTreeMap::new();
         for face in self.faces.iter() {
             groups
@@ -190,50 +370,80 @@ impl OBJParser {
         let mut g = ObjectBuilder::new_group();
         if let Some(default_group) = groups.get(&None) {
             for face in default_group {
-                let t = if face.normals.is_empty() {
-                    ObjectBuilder::new_triangle()
-                        .set_p1(face.get_vertex(1).unwrap())
-                        .set_p2(face.get_vertex(2).unwrap())
-                        .set_p3(face.get_vertex(3).unwrap())
-                        .build()
-                } else {
-                    ObjectBuilder::new_smooth_triangle()
-                        .set_p1(face.get_vertex(1).unwrap())
-                        .set_p2(face.get_vertex(2).unwrap())
-                        .set_p3(face.get_vertex(3).unwrap())
-                        .set_n1(face.get_normal(1).unwrap())
-                        .set_n2(face.get_normal(2).unwrap())
-                        .set_n3(face.get_normal(3).unwrap())
-                        .build()
-                };
-                g = g.add_child(t);
+                g = g.add_child(self.build_triangle(face, vertex_normals));
             }
         }
-        for (_, faces) in groups.iter().filter(|&(k, _)| *k != None) {
-            let mut new_group = ObjectBuilder::new_group();
+        for (name, faces) in groups.iter() {
+            let Some(name) = name else {
+                continue;
+            };
+            let mut new_group = ObjectBuilder::new_group().with_name(name);
             for face in faces {
-                let t = if face.normals.is_empty() {
-                    ObjectBuilder::new_triangle()
-                        .set_p1(face.get_vertex(1).unwrap())
-                        .set_p2(face.get_vertex(2).unwrap())
-                        .set_p3(face.get_vertex(3).unwrap())
-                        .build()
-                } else {
-                    ObjectBuilder::new_smooth_triangle()
-                        .set_p1(face.get_vertex(1).unwrap())
-                        .set_p2(face.get_vertex(2).unwrap())
-                        .set_p3(face.get_vertex(3).unwrap())
-                        .set_n1(face.get_normal(1).unwrap())
-                        .set_n2(face.get_normal(2).unwrap())
-                        .set_n3(face.get_normal(3).unwrap())
-                        .build()
-                };
-                new_group = new_group.add_child(t);
+                new_group = new_group.add_child(self.build_triangle(face, vertex_normals));
             }
             g = g.add_child(new_group.build());
         }
         g
     }
+
+    fn build_triangle(&self, face: &Face, vertex_normals: Option<&[Vector]>) -> Object {
+        let material = face
+            .material
+            .as_ref()
+            .and_then(|name| self.materials.get(name))
+            .cloned()
+            .unwrap_or_else(Material::new);
+        let uvs = match face.textures.as_slice() {
+            [t1, t2, t3] => match (
+                self.get_texture_vertex(*t1),
+                self.get_texture_vertex(*t2),
+                self.get_texture_vertex(*t3),
+            ) {
+                (Ok(uv1), Ok(uv2), Ok(uv3)) => Some([uv1, uv2, uv3]),
+                _ => None,
+            },
+            _ => None,
+        };
+        let normals = match (face.normals.is_empty(), vertex_normals) {
+            (false, _) => Some([
+                face.get_normal(1).unwrap(),
+                face.get_normal(2).unwrap(),
+                face.get_normal(3).unwrap(),
+            ]),
+            (true, Some(vn)) => Some([
+                vn[face.indices[0] - 1],
+                vn[face.indices[1] - 1],
+                vn[face.indices[2] - 1],
+            ]),
+            (true, None) if uvs.is_some() => {
+                let n = face.geometric_normal();
+                Some([n, n, n])
+            }
+            (true, None) => None,
+        };
+        match normals {
+            None => ObjectBuilder::new_triangle()
+                .set_p1(face.get_vertex(1).unwrap())
+                .set_p2(face.get_vertex(2).unwrap())
+                .set_p3(face.get_vertex(3).unwrap())
+                .with_material(material)
+                .build(),
+            Some([n1, n2, n3]) => {
+                let mut t = ObjectBuilder::new_smooth_triangle()
+                    .set_p1(face.get_vertex(1).unwrap())
+                    .set_p2(face.get_vertex(2).unwrap())
+                    .set_p3(face.get_vertex(3).unwrap())
+                    .set_n1(n1)
+                    .set_n2(n2)
+                    .set_n3(n3)
+                    .with_material(material);
+                if let Some(uvs) = uvs {
+                    t = t.set_uvs(uvs);
+                }
+                t.build()
+            }
+        }
+    }
 }
 
 fn parse_line(line: &[&str]) -> Result<OBJElement> {
@@ -248,6 +458,9 @@ fn parse_line(line: &[&str]) -> Result<OBJElement> {
         "f" => parse_face(&line[1..]),
         "g" => parse_group(&line[1..]),
         "vn" => parse_vertex_normal(&line[1..]),
+        "vt" => parse_texture_vertex(&line[1..]),
+        "mtllib" => parse_name("Material library", &line[1..]).map(OBJElement::MaterialLibrary),
+        "usemtl" => parse_name("Material", &line[1..]).map(OBJElement::UseMaterial),
         _ => Err(anyhow!("Unrecognized element: {}", line[0])),
     };
     e
@@ -325,6 +538,64 @@ fn parse_vertex_normal(line: &[&str]) -> Result<OBJElement> {
     Ok(OBJElement::Normal(Vector::new(x, y, z)))
 }
 
+fn parse_texture_vertex(line: &[&str]) -> Result<OBJElement> {
+    if line.len() != 2 && line.len() != 3 {
+        return Err(anyhow!(OBJParserError::ParseError(
+            "Texture vertex".into(),
+            "Wrong format".into()
+        )));
+    }
+    let u = line[0].parse::<f64>()?;
+    let v = line[1].parse::<f64>()?;
+    Ok(OBJElement::TextureVertex((u, v)))
+}
+
+fn parse_name(obj_type: &str, line: &[&str]) -> Result<String> {
+    if line.is_empty() {
+        return Err(anyhow!(OBJParserError::ParseError(
+            obj_type.into(),
+            "Missing name".into()
+        )));
+    }
+    Ok(line.join(" "))
+}
+
+fn parse_mtl(input: &str) -> BTreeMap<String, Material> {
+    let mut materials = BTreeMap::new();
+    let mut current: Option<(String, Material)> = None;
+    for line in input.lines() {
+        let line_vec: Vec<&str> = line.split_whitespace().collect();
+        let Some((keyword, args)) = line_vec.split_first() else {
+            continue;
+        };
+        if *keyword == "newmtl" {
+            if let Some((name, material)) = current.take() {
+                materials.insert(name, material);
+            }
+            current = Some((args.join(" "), Material::new()));
+            continue;
+        }
+        let Some((_, material)) = current.as_mut() else {
+            continue;
+        };
+        let values: Vec<f64> = args.iter().filter_map(|a| a.parse().ok()).collect();
+        let updated = material.clone();
+        *material = match (*keyword, values.as_slice()) {
+            ("Kd", [r, g, b]) => updated.with_color(Color::new(*r, *g, *b)),
+            ("Ks", [r, g, b]) => updated.with_specular((r + g + b) / 3.0),
+            ("Ns", [ns]) => updated.with_shininess(*ns),
+            ("d", [d]) => updated.with_transparency(1.0 - d),
+            ("Tr", [tr]) => updated.with_transparency(*tr),
+            ("Ni", [ni]) => updated.with_refractive_index(*ni),
+            _ => updated,
+        };
+    }
+    if let Some((name, material)) = current {
+        materials.insert(name, material);
+    }
+    materials
+}
+
 #[derive(Debug)]
 enum OBJParserError {
     ObjectNotFound(String, usize),
@@ -358,7 +629,7 @@ mod tests {
             She set out one day
             in a relative way,
             and came back the previous night."#;
-        let parser = OBJParser::parse(input);
+        let parser = OBJParser::parse(input).unwrap();
         assert_eq!(parser.lines_skipped, 5);
     }
 
@@ -368,7 +639,7 @@ mod tests {
             v -1.0000 0.5000 0.0000
             v 1 0 0
             v 1 1 0"#;
-        let parser = OBJParser::parse(input);
+        let parser = OBJParser::parse(input).unwrap();
         assert_eq!(parser.get_vertex(1).unwrap(), Point::new(-1.0, 1.0, 0.0));
         assert_eq!(parser.get_vertex(2).unwrap(), Point::new(-1.0, 0.5, 0.0));
         assert_eq!(parser.get_vertex(3).unwrap(), Point::new(1.0, 0.0, 0.0));
@@ -383,7 +654,7 @@ mod tests {
             v 1 1 0
             f 1 2 3
             f 1 3 4"#;
-        let parser = OBJParser::parse(input);
+        let parser = OBJParser::parse(input).unwrap();
         assert!(parser.get_face(1).unwrap().group.is_none());
         assert!(parser.get_face(2).unwrap().group.is_none());
         assert_eq!(
@@ -420,7 +691,7 @@ mod tests {
             v 1 1 0
             v 0 2 0
             f 1 2 3 4 5"#;
-        let parser = OBJParser::parse(input);
+        let parser = OBJParser::parse(input).unwrap();
         assert!(parser.get_face(1).unwrap().group.is_none());
         assert!(parser.get_face(2).unwrap().group.is_none());
         assert!(parser.get_face(3).unwrap().group.is_none());
@@ -472,7 +743,7 @@ mod tests {
             f 1 2 3
             g SecondGroup
             f 1 3 4"#;
-        let parser = OBJParser::parse(input);
+        let parser = OBJParser::parse(input).unwrap();
         assert_eq!(parser.get_face(1).unwrap().group, Some("FirstGroup".into()));
         assert_eq!(
             parser.get_face(2).unwrap().group,
@@ -514,7 +785,7 @@ mod tests {
             f 1 2 3
             g SecondGroup
             f 1 3 4"#;
-        let parser = OBJParser::parse(input);
+        let parser = OBJParser::parse(input).unwrap();
         let g = parser.into_group().build();
         let c = g.group().unwrap().children();
         assert_eq!(c.len(), 2);
@@ -522,6 +793,8 @@ mod tests {
         let g2 = &c[1];
         let c1 = g1.group().unwrap().children();
         let c2 = g2.group().unwrap().children();
+        assert_eq!(g1.group().unwrap().name(), Some("FirstGroup"));
+        assert_eq!(g2.group().unwrap().name(), Some("SecondGroup"));
         assert_eq!(c1.len(), 1);
         assert_eq!(c2.len(), 1);
         let v1 = &c1[0];
@@ -539,7 +812,7 @@ mod tests {
         let input = r#"vn 0 0 1
             vn 0.707 0 -0.707
             vn 1 2 3"#;
-        let parser = OBJParser::parse(input);
+        let parser = OBJParser::parse(input).unwrap();
         assert_eq!(parser.get_normal(1).unwrap(), Vector::new(0.0, 0.0, 1.0));
         assert_eq!(
             parser.get_normal(2).unwrap(),
@@ -558,7 +831,7 @@ mod tests {
             vn 0 1 0
             f 1//3 2//1 3//2
             f 1/0/3 2/102/1 3/14/2"#;
-        let parser = OBJParser::parse(input);
+        let parser = OBJParser::parse(input).unwrap();
         let g = parser.into_group().build();
         let c = g.group().unwrap().children();
         assert_eq!(c.len(), 2);
@@ -577,4 +850,183 @@ mod tests {
         assert_eq!(t2.n2().unwrap(), parser.get_normal(1).unwrap());
         assert_eq!(t2.n3().unwrap(), parser.get_normal(2).unwrap());
     }
+
+    #[test]
+    fn faces_use_materials_from_a_material_library() {
+        let obj = r#"mtllib scene.mtl
+            v -1 1 0
+            v -1 0 0
+            v 1 0 0
+            v 1 1 0
+            usemtl red
+            f 1 2 3
+            usemtl glass
+            f 1 3 4"#;
+        let mtl = r#"newmtl red
+            Kd 1 0 0
+            Ks 0.5 0.5 0.5
+            Ns 50
+
+            newmtl glass
+            Kd 0 0 1
+            d 0.25
+            Ni 1.5"#;
+        let parser = OBJParser::parse(obj).unwrap().with_material_library(mtl);
+        assert_eq!(parser.material_libraries, vec!["scene.mtl".to_string()]);
+        let g = parser.into_group().build();
+        let c = g.group().unwrap().children();
+        assert_eq!(c.len(), 2);
+        let red = Material::new()
+            .with_color(Color::new(1.0, 0.0, 0.0))
+            .with_specular(0.5)
+            .with_shininess(50.0);
+        let glass = Material::new()
+            .with_color(Color::new(0.0, 0.0, 1.0))
+            .with_transparency(0.75)
+            .with_refractive_index(1.5);
+        assert_eq!(c[0].material(), &red);
+        assert_eq!(c[1].material(), &glass);
+    }
+
+    #[test]
+    fn smoothing_a_mesh_without_normals_averages_shared_vertices() {
+        let input = r#"v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            v 0 0 -1
+            f 1 2 3
+            f 1 3 4"#;
+        let parser = OBJParser::parse(input).unwrap();
+        let flat = parser.into_group().build();
+        assert!(flat.group().unwrap().children()[0].n1().is_none());
+
+        let g = parser.into_smoothed_group().build();
+        let c = g.group().unwrap().children();
+        assert_eq!(c.len(), 2);
+        let shared = Vector::new(1.0, 0.0, -1.0).normalize();
+        assert_eq!(c[0].n1().unwrap(), shared);
+        assert_eq!(c[0].n2().unwrap(), Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(c[0].n3().unwrap(), shared);
+        assert_eq!(c[1].n1().unwrap(), shared);
+        assert_eq!(c[1].n2().unwrap(), shared);
+        assert_eq!(c[1].n3().unwrap(), Vector::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn loading_a_missing_file_is_an_error() {
+        let path = PathBuf::from("./does/not/exist.obj");
+        let result = OBJParser::load_file(&path);
+        assert!(result.is_err());
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("does/not/exist.obj"));
+    }
+
+    #[test]
+    fn loading_a_missing_material_library_names_it() {
+        let path = std::env::temp_dir().join(format!("{}-missing_mtl.obj", std::process::id()));
+        fs::write(
+            &path,
+            "mtllib missing.mtl\nv 0 1 0\nv -1 0 0\nv 1 0 0\nf 1 2 3\n",
+        )
+        .unwrap();
+        let result = OBJParser::load_file(&path);
+        fs::remove_file(&path).unwrap();
+        let Err(error) = result else {
+            panic!("a missing material library should fail the load");
+        };
+        assert!(error.to_string().contains("missing.mtl"));
+    }
+
+    #[test]
+    fn faces_referencing_missing_vertices_are_an_error() {
+        let input = r#"v -1 1 0
+            v -1 0 0
+            v 1 0 0
+            f 1 2 4"#;
+        assert!(OBJParser::parse(input).is_err());
+        let input = r#"v -1 1 0
+            v -1 0 0
+            v 1 0 0
+            vn 0 0 1
+            f 1//1 2//1 3//2"#;
+        assert!(OBJParser::parse(input).is_err());
+        let input = r#"v -1 1 0
+            v -1 0 0
+            v 1 0 0
+            vt 0 0
+            f 1/1 2/1 3/2"#;
+        let Err(error) = OBJParser::parse(input) else {
+            panic!("a missing texture vertex should fail the parse");
+        };
+        assert!(error.to_string().contains("Texture vertex with index 2"));
+    }
+
+    #[test]
+    fn faces_with_texture_coordinates() {
+        let input = r#"v 0 1 0
+            v -1 0 0
+            v 1 0 0
+            vt 0.5 1
+            vt 0 0 0
+            vt 1 0
+            f 1/1 2/2 3/3
+            f 1/3/ 2/2/ 3/1/"#;
+        let parser = OBJParser::parse(input).unwrap();
+        assert_eq!(parser.get_texture_vertex(1).unwrap(), (0.5, 1.0));
+        assert_eq!(parser.get_texture_vertex(2).unwrap(), (0.0, 0.0));
+        assert_eq!(parser.get_texture_vertex(3).unwrap(), (1.0, 0.0));
+        let g = parser.into_group().build();
+        let c = g.group().unwrap().children();
+        assert_eq!(c.len(), 2);
+        assert_eq!(c[0].uvs(), Some([(0.5, 1.0), (0.0, 0.0), (1.0, 0.0)]));
+        assert_eq!(c[1].uvs(), Some([(1.0, 0.0), (0.0, 0.0), (0.5, 1.0)]));
+        assert_eq!(c[0].n1().unwrap(), Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn writing_a_group_and_parsing_it_back() {
+        let t1 = ObjectBuilder::new_triangle()
+            .set_p1(Point::new(-1.0, 1.0, 0.0))
+            .set_p2(Point::new(-1.0, 0.0, 0.0))
+            .set_p3(Point::new(1.0, 0.0, 0.0))
+            .build();
+        let t2 = ObjectBuilder::new_triangle()
+            .set_p1(Point::new(-1.0, 1.0, 0.0))
+            .set_p2(Point::new(1.0, 0.0, 0.0))
+            .set_p3(Point::new(1.0, 1.0, 0.0))
+            .build();
+        let g = ObjectBuilder::new_group()
+            .add_child(t1.clone())
+            .add_child(
+                ObjectBuilder::new_group()
+                    .with_name("Roof")
+                    .add_child(t2.clone())
+                    .build(),
+            )
+            .build();
+        let path =
+            std::env::temp_dir().join(format!("{}-ray_tracer_round_trip.obj", std::process::id()));
+        OBJParser::write_group(&g, &path).unwrap();
+        let parser = OBJParser::load_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let expected = [
+            t1.p1().unwrap(),
+            t1.p2().unwrap(),
+            t1.p3().unwrap(),
+            t2.p1().unwrap(),
+            t2.p2().unwrap(),
+            t2.p3().unwrap(),
+        ];
+        for (i, p) in expected.into_iter().enumerate() {
+            assert_eq!(parser.get_vertex(i + 1).unwrap(), p);
+        }
+        let parsed = parser.into_group().build();
+        assert_eq!(parsed.children()[0], t1);
+        assert_eq!(parsed.children()[1].children()[0], t2);
+        assert_eq!(parsed.children()[1].group().unwrap().name(), Some("Roof"));
+    }
 }
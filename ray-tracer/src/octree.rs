@@ -1,8 +1,5 @@
-use std::f64::{INFINITY, NEG_INFINITY};
-
 use crate::{
     bounds::Bounds,
-    intersections::Intersections,
     rays::Ray,
     shapes::Object,
     tuples::{points::Point, Tuple},
@@ -11,21 +8,20 @@ use crate::{
 const MAX_SIZE: f64 = 1024.0;
 const MIN_SIZE: f64 = 0.001;
 
-#[derive(Debug)]
-pub struct Octree<'a> {
+#[derive(Debug, Clone)]
+pub struct Octree {
     region: Bounds,
-    objects: Vec<&'a Object>,
-    children: [Option<Box<Octree<'a>>>; 8],
+    objects: Vec<usize>,
+    children: [Option<Box<Octree>>; 8],
 }
 
-impl<'a> Octree<'a> {
-    pub fn with_region(mut self, region: Bounds) -> Self {
-        self.region = region;
-        self
+impl Octree {
+    pub fn new(objects: &[Object]) -> Self {
+        Self::default().build(objects, (0..objects.len()).collect())
     }
 
-    pub fn with_objects(mut self, objects: Vec<&'a Object>) -> Self {
-        self.objects = objects;
+    pub fn with_region(mut self, region: Bounds) -> Self {
+        self.region = region;
         self
     }
 
@@ -33,117 +29,91 @@ impl<'a> Octree<'a> {
         self.children.iter().any(|child| child.is_some())
     }
 
-    pub fn intersects(&self, r: &Ray) -> Intersections {
-        if self.objects.len() == 0 && !self.has_children() {
-            Intersections::new()
-        } else {
-            let mut intersections = Intersections::new();
-            for obj in self.objects.clone() {
-                match obj.shape() {
-                    crate::shapes::Shape::Group(_) => {}
-                    _ => {
-                        let mut xs = obj.intersects(r);
-                        for i in xs.iter_mut() {
-                            intersections.push(*i);
-                        }
-                    }
-                }
-            }
-            for idx in 0..=7 {
-                if let Some(child) = &self.children[idx] {
-                    if child.region.intersects(&r) {
-                        let mut xs = child.intersects(r);
-                        for i in xs.iter_mut() {
-                            intersections.push(*i);
-                        }
-                    }
-                }
-            }
-            intersections
-        }
+    pub fn candidates(&self, r: &Ray) -> Vec<usize> {
+        let mut candidates = Vec::new();
+        self.collect(r, &mut candidates);
+        candidates.sort_unstable();
+        candidates
     }
 
-    pub fn build(mut self) -> Option<Octree<'a>> {
-        if self.objects.len() == 1 {
-            return None;
-        }
-
-        let dimensions = *self.region.max() - *self.region.min();
-        if dimensions.x() <= MIN_SIZE && dimensions.y() <= MIN_SIZE && dimensions.z() <= MIN_SIZE {
-            return None;
-        }
+    fn collect(&self, r: &Ray, candidates: &mut Vec<usize>) {
+        candidates.extend(&self.objects);
+        self.children
+            .iter()
+            .flatten()
+            .filter(|child| child.region.intersects(r))
+            .for_each(|child| child.collect(r, candidates));
+    }
 
-        let dimensions = *self.region.max() - *self.region.min();
-        let half = dimensions / 2.0;
-        let center = *self.region.min() + half;
-        let octants = [
-            Bounds::new(*self.region.min(), center),
+    fn octants(&self) -> [Bounds; 8] {
+        let (min, max) = (*self.region.min(), *self.region.max());
+        let center = min + (max - min) / 2.0;
+        [
+            Bounds::new(min, center),
             Bounds::new(
-                Point::new(center.x(), self.region.min().y(), self.region.min().z()),
-                Point::new(self.region.max().x(), center.y(), center.z()),
+                Point::new(center.x(), min.y(), min.z()),
+                Point::new(max.x(), center.y(), center.z()),
             ),
             Bounds::new(
-                Point::new(center.x(), self.region.min().y(), center.z()),
-                Point::new(self.region.max().x(), center.y(), self.region.max().z()),
+                Point::new(center.x(), min.y(), center.z()),
+                Point::new(max.x(), center.y(), max.z()),
             ),
             Bounds::new(
-                Point::new(self.region.min().x(), self.region.min().y(), center.z()),
-                Point::new(center.x(), center.y(), self.region.max().z()),
+                Point::new(min.x(), min.y(), center.z()),
+                Point::new(center.x(), center.y(), max.z()),
             ),
             Bounds::new(
-                Point::new(self.region.min().x(), center.y(), self.region.min().z()),
-                Point::new(center.x(), self.region.max().y(), center.z()),
+                Point::new(min.x(), center.y(), min.z()),
+                Point::new(center.x(), max.y(), center.z()),
             ),
             Bounds::new(
-                Point::new(center.x(), center.y(), self.region.min().z()),
-                Point::new(self.region.max().x(), self.region.max().y(), center.z()),
+                Point::new(center.x(), center.y(), min.z()),
+                Point::new(max.x(), max.y(), center.z()),
             ),
-            Bounds::new(center, *self.region.max()),
+            Bounds::new(center, max),
             Bounds::new(
-                Point::new(self.region.min().x(), center.y(), center.z()),
-                Point::new(center.x(), self.region.max().y(), self.region.max().z()),
+                Point::new(min.x(), center.y(), center.z()),
+                Point::new(center.x(), max.y(), max.z()),
             ),
-        ];
-        let mut oct_vecs: [Vec<&Object>; 8] = Default::default();
-        let mut to_remove: Vec<&Object> = Vec::new();
-
-        self.objects.iter().for_each(|obj| match obj.shape() {
-            crate::shapes::Shape::Group(_) => {}
-            _ => {
-                if obj.bounds().min().x() > NEG_INFINITY
-                    && obj.bounds().min().y() > NEG_INFINITY
-                    && obj.bounds().min().z() > NEG_INFINITY
-                    && obj.bounds().max().x() < INFINITY
-                    && obj.bounds().max().y() < INFINITY
-                    && obj.bounds().max().z() < INFINITY
-                {
-                    for idx in 0..=7 {
-                        if octants[idx].contains(&obj.bounds()) {
-                            oct_vecs[idx].push(obj);
-                            to_remove.push(obj);
-                        }
-                    }
-                }
+        ]
+    }
+
+    fn build(mut self, objects: &[Object], indices: Vec<usize>) -> Self {
+        let dimensions = *self.region.max() - *self.region.min();
+        if indices.len() <= 1
+            || (dimensions.x() <= MIN_SIZE
+                && dimensions.y() <= MIN_SIZE
+                && dimensions.z() <= MIN_SIZE)
+        {
+            self.objects = indices;
+            return self;
+        }
+
+        let octants = self.octants();
+        let mut oct_vecs: [Vec<usize>; 8] = Default::default();
+        for idx in indices {
+            match octants
+                .iter()
+                .position(|octant| octant.contains(objects[idx].bounds()))
+            {
+                Some(octant) => oct_vecs[octant].push(idx),
+                None => self.objects.push(idx),
             }
-        });
-        self.objects.retain(|i| !to_remove.contains(i));
-        for idx in 0..=7 {
-            match oct_vecs[idx].len() {
-                0 => self.children[idx] = None,
-                _ => {
-                    self.children[idx] = Self::default()
-                        .with_region(octants[idx])
-                        .with_objects(oct_vecs[idx].clone())
-                        .build()
-                        .map(Box::new);
-                }
+        }
+        for (octant, oct_vec) in oct_vecs.into_iter().enumerate() {
+            if !oct_vec.is_empty() {
+                self.children[octant] = Some(Box::new(
+                    Self::default()
+                        .with_region(octants[octant])
+                        .build(objects, oct_vec),
+                ));
             }
         }
-        Some(self)
+        self
     }
 }
 
-impl<'a> Default for Octree<'a> {
+impl Default for Octree {
     fn default() -> Self {
         Self {
             region: Bounds::new(
@@ -151,7 +121,41 @@ impl<'a> Default for Octree<'a> {
                 Point::new(MAX_SIZE, MAX_SIZE, MAX_SIZE),
             ),
             objects: Vec::new(),
-            children: [None, None, None, None, None, None, None, None],
+            children: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{shapes::ObjectBuilder, transformations::Transformation, tuples::vectors::Vector};
+
+    use super::*;
+
+    fn spheres() -> Vec<Object> {
+        [(-5.0, -5.0), (5.0, -5.0), (-5.0, 5.0), (5.0, 5.0)]
+            .into_iter()
+            .map(|(x, z)| {
+                ObjectBuilder::new_sphere()
+                    .with_transform(Transformation::new_transform().translation(x, 5.0, z))
+                    .build()
+            })
+            .chain([ObjectBuilder::new_plane().build()])
+            .collect()
+    }
+
+    #[test]
+    fn objects_are_stored_in_the_octant_containing_them() {
+        let octree = Octree::new(&spheres());
+        assert!(octree.has_children());
+        assert_eq!(octree.objects, vec![4]);
+        assert_eq!(octree.children.iter().flatten().count(), 4);
+    }
+
+    #[test]
+    fn a_ray_only_collects_objects_in_octants_it_crosses() {
+        let octree = Octree::new(&spheres());
+        let r = Ray::new(Point::new(5.0, 5.0, -20.0), Vector::z_norm());
+        assert_eq!(octree.candidates(&r), vec![1, 3, 4]);
+    }
+}
@@ -8,20 +8,30 @@ use super::Pattern;
 pub struct BlendingPattern {
     a: Box<Pattern>,
     b: Box<Pattern>,
+    weight: f64,
 }
 
 impl BlendingPattern {
     pub fn new(a: Pattern, b: Pattern) -> Self {
+        Self::new_weighted(a, b, 0.5)
+    }
+
+    pub fn new_weighted(a: Pattern, b: Pattern, weight: f64) -> Self {
         Self {
             a: Box::new(a),
             b: Box::new(b),
+            weight,
         }
     }
 
     pub fn pattern_at(&self, p: Point) -> Color {
         let c1 = self.a.pattern_at(p);
         let c2 = self.b.pattern_at(p);
-        (&c1 + &c2) / 2.0
+        &(c1 * (1.0 - self.weight)) + &(c2 * self.weight)
+    }
+
+    pub fn opacity_at(&self, p: Point) -> f64 {
+        self.a.opacity_at(p) * (1.0 - self.weight) + self.b.opacity_at(p) * self.weight
     }
 }
 
@@ -63,4 +73,15 @@ mod tests {
             Color::black()
         );
     }
+
+    #[test]
+    fn weighted_blend_mixes_patterns_by_weight() {
+        let a = Pattern::new_solid_pattern(Color::white());
+        let b = Pattern::new_solid_pattern(Color::new(0.0, 0.5, 1.0));
+        let p = Point::new(0.3, 0.2, 0.1);
+        let weighted = BlendingPattern::new_weighted(a.clone(), b.clone(), 0.7);
+        assert_eq!(weighted.pattern_at(p), Color::new(0.3, 0.65, 1.0));
+        let half = BlendingPattern::new_weighted(a.clone(), b.clone(), 0.5);
+        assert_eq!(half.pattern_at(p), BlendingPattern::new(a, b).pattern_at(p));
+    }
 }
@@ -18,14 +18,22 @@ impl CheckerPattern {
         }
     }
 
-    pub fn pattern_at(&self, p: Point) -> Color {
+    fn pick(&self, p: Point) -> &Pattern {
         let sum = p.x().floor() + p.y().floor() + p.z().floor();
         if sum % 2.0 == 0.0 {
-            self.a.pattern_at(p)
+            &self.a
         } else {
-            self.b.pattern_at(p)
+            &self.b
         }
     }
+
+    pub fn pattern_at(&self, p: Point) -> Color {
+        self.pick(p).pattern_at(p)
+    }
+
+    pub fn opacity_at(&self, p: Point) -> f64 {
+        self.pick(p).opacity_at(p)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,121 @@
+use colo_rs::colors::Color;
+
+use crate::tuples::{points::Point, Tuple};
+
+use super::Pattern;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CubeFace {
+    Left,
+    Front,
+    Right,
+    Back,
+    Up,
+    Down,
+}
+
+impl CubeFace {
+    pub fn from_point(p: Point) -> Self {
+        let coord = p.x().abs().max(p.y().abs()).max(p.z().abs());
+        if coord == p.x() {
+            CubeFace::Right
+        } else if coord == -p.x() {
+            CubeFace::Left
+        } else if coord == p.y() {
+            CubeFace::Up
+        } else if coord == -p.y() {
+            CubeFace::Down
+        } else if coord == p.z() {
+            CubeFace::Front
+        } else {
+            CubeFace::Back
+        }
+    }
+
+    pub fn uv(&self, p: Point) -> (f64, f64) {
+        let wrap = |c: f64| c.rem_euclid(2.0) / 2.0;
+        match self {
+            CubeFace::Front => (wrap(p.x() + 1.0), wrap(p.y() + 1.0)),
+            CubeFace::Back => (wrap(1.0 - p.x()), wrap(p.y() + 1.0)),
+            CubeFace::Left => (wrap(p.z() + 1.0), wrap(p.y() + 1.0)),
+            CubeFace::Right => (wrap(1.0 - p.z()), wrap(p.y() + 1.0)),
+            CubeFace::Up => (wrap(p.x() + 1.0), wrap(1.0 - p.z())),
+            CubeFace::Down => (wrap(p.x() + 1.0), wrap(p.z() + 1.0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeMapPattern {
+    faces: Box<[Pattern; 6]>,
+}
+
+impl CubeMapPattern {
+    pub fn new(faces: [Pattern; 6]) -> Self {
+        Self {
+            faces: Box::new(faces),
+        }
+    }
+
+    fn pick(&self, p: Point) -> (&Pattern, Point) {
+        let face = CubeFace::from_point(p);
+        let (u, v) = face.uv(p);
+        (&self.faces[face as usize], Point::new(u, 0.0, v))
+    }
+
+    pub fn pattern_at(&self, p: Point) -> Color {
+        let (pattern, face_point) = self.pick(p);
+        pattern.pattern_at(face_point)
+    }
+
+    pub fn opacity_at(&self, p: Point) -> f64 {
+        let (pattern, face_point) = self.pick(p);
+        pattern.opacity_at(face_point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx_eq::ApproxEq;
+    use yare::parameterized;
+
+    use super::*;
+
+    fn faces() -> [Pattern; 6] {
+        [
+            Pattern::new_solid_pattern(Color::new(1.0, 0.0, 0.0)),
+            Pattern::new_solid_pattern(Color::new(0.0, 1.0, 0.0)),
+            Pattern::new_solid_pattern(Color::new(0.0, 0.0, 1.0)),
+            Pattern::new_solid_pattern(Color::new(1.0, 1.0, 0.0)),
+            Pattern::new_solid_pattern(Color::new(0.0, 1.0, 1.0)),
+            Pattern::new_solid_pattern(Color::new(1.0, 0.0, 1.0)),
+        ]
+    }
+
+    #[parameterized(
+        left = {Point::new(-1.0, 0.5, -0.25), CubeFace::Left, Color::new(1.0, 0.0, 0.0)},
+        front = {Point::new(-0.5, 0.25, 1.0), CubeFace::Front, Color::new(0.0, 1.0, 0.0)},
+        right = {Point::new(1.1, -0.75, 0.8), CubeFace::Right, Color::new(0.0, 0.0, 1.0)},
+        back = {Point::new(0.5, -0.25, -1.0), CubeFace::Back, Color::new(1.0, 1.0, 0.0)},
+        up = {Point::new(-0.5, 1.0, 0.9), CubeFace::Up, Color::new(0.0, 1.0, 1.0)},
+        down = {Point::new(-0.3, -1.0, -0.9), CubeFace::Down, Color::new(1.0, 0.0, 1.0)},
+    )]
+    fn a_cube_map_picks_the_pattern_of_the_face(p: Point, face: CubeFace, expected: Color) {
+        assert_eq!(CubeFace::from_point(p), face);
+        assert_eq!(CubeMapPattern::new(faces()).pattern_at(p), expected);
+    }
+
+    #[parameterized(
+        front = {CubeFace::Front, Point::new(-0.5, 0.5, 1.0), 0.25, 0.75},
+        back = {CubeFace::Back, Point::new(0.5, 0.5, -1.0), 0.25, 0.75},
+        left = {CubeFace::Left, Point::new(-1.0, 0.5, -0.5), 0.25, 0.75},
+        right = {CubeFace::Right, Point::new(1.0, 0.5, 0.5), 0.25, 0.75},
+        up = {CubeFace::Up, Point::new(-0.5, 1.0, -0.5), 0.25, 0.75},
+        down = {CubeFace::Down, Point::new(-0.5, -1.0, 0.5), 0.25, 0.75},
+    )]
+    fn uv_mapping_a_point_on_a_cube_face(face: CubeFace, p: Point, u: f64, v: f64) {
+        let (actual_u, actual_v) = face.uv(p);
+        assert!(actual_u.approx_eq(u));
+        assert!(actual_v.approx_eq(v));
+    }
+}
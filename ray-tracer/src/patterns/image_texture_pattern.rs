@@ -0,0 +1,103 @@
+use colo_rs::colors::Color;
+
+use crate::{canvas::Canvas, tuples::points::Point};
+
+use super::uv_checker_pattern::UvMapping;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageTexturePattern {
+    canvas: Canvas,
+    mapping: UvMapping,
+}
+
+impl ImageTexturePattern {
+    pub fn new(canvas: Canvas, mapping: UvMapping) -> Self {
+        Self { canvas, mapping }
+    }
+
+    pub fn with_mapping(&mut self, mapping: UvMapping) {
+        self.mapping = mapping;
+    }
+
+    pub fn mapping(&self) -> UvMapping {
+        self.mapping
+    }
+
+    pub(crate) fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        if self.canvas.width() == 0 || self.canvas.height() == 0 {
+            return Color::black();
+        }
+        let lerp = |a: Color, b: Color, t: f64| &a + &((&b - &a) * t);
+        let max_x = self.canvas.width() - 1;
+        let max_y = self.canvas.height() - 1;
+        let x = u.clamp(0.0, 1.0) * max_x as f64;
+        let y = (1.0 - v.clamp(0.0, 1.0)) * max_y as f64;
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(max_x), (y0 + 1).min(max_y));
+        let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+        let top = lerp(
+            self.canvas.pixel_at(x0, y0),
+            self.canvas.pixel_at(x1, y0),
+            fx,
+        );
+        let bottom = lerp(
+            self.canvas.pixel_at(x0, y1),
+            self.canvas.pixel_at(x1, y1),
+            fx,
+        );
+        lerp(top, bottom, fy)
+    }
+
+    pub fn pattern_at(&self, p: Point) -> Color {
+        let (u, v) = self.mapping.map(p);
+        self.uv_pattern_at(u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yare::parameterized;
+
+    use crate::tuples::Tuple;
+
+    use super::*;
+
+    fn two_by_two() -> ImageTexturePattern {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write_pixel(0, 0, Color::red());
+        canvas.write_pixel(1, 0, Color::green());
+        canvas.write_pixel(0, 1, Color::blue());
+        canvas.write_pixel(1, 1, Color::white());
+        ImageTexturePattern::new(canvas, UvMapping::Planar)
+    }
+
+    #[parameterized(
+        top_left = {0.0, 1.0, Color::red()},
+        top_right = {1.0, 1.0, Color::green()},
+        bottom_left = {0.0, 0.0, Color::blue()},
+        bottom_right = {1.0, 0.0, Color::white()},
+        clamped = {1.5, -0.5, Color::white()},
+    )]
+    fn corners_of_the_canvas_map_to_its_pixels(u: f64, v: f64, expected: Color) {
+        assert_eq!(two_by_two().uv_pattern_at(u, v), expected);
+    }
+
+    #[test]
+    fn sampling_between_pixels_is_bilinear() {
+        let pattern = two_by_two();
+        assert_eq!(pattern.uv_pattern_at(0.5, 1.0), Color::new(0.5, 0.5, 0.0));
+        assert_eq!(pattern.uv_pattern_at(0.5, 0.5), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn an_image_texture_uses_its_mapping() {
+        let pattern = two_by_two();
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), Color::blue());
+    }
+
+    #[test]
+    fn an_empty_image_samples_as_black() {
+        let pattern = ImageTexturePattern::new(Canvas::new(0, 0), UvMapping::Planar);
+        assert_eq!(pattern.uv_pattern_at(0.5, 0.5), Color::black());
+    }
+}
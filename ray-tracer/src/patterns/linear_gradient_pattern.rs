@@ -2,20 +2,54 @@ use colo_rs::colors::Color;
 
 use crate::tuples::{points::Point, Tuple};
 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Repeat {
+    Mirror,
+    #[default]
+    Wrap,
+    Clamp,
+}
+
+impl Repeat {
+    fn fraction(&self, x: f64) -> f64 {
+        match self {
+            Repeat::Mirror => {
+                let t = x.rem_euclid(2.0);
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+            Repeat::Wrap => x - x.floor(),
+            Repeat::Clamp => x.clamp(0.0, 1.0),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct LinearGradientPattern {
     a: Color,
     b: Color,
+    repeat: Repeat,
 }
 
 impl LinearGradientPattern {
     pub fn new(a: Color, b: Color) -> Self {
-        Self { a, b }
+        Self {
+            a,
+            b,
+            repeat: Repeat::default(),
+        }
+    }
+
+    pub fn with_repeat(&mut self, repeat: Repeat) {
+        self.repeat = repeat;
     }
 
     pub fn pattern_at(&self, p: Point) -> Color {
         let distance = &self.b - &self.a;
-        let fraction = p.x() - p.x().floor();
+        let fraction = self.repeat.fraction(p.x());
         &self.a + &(distance * fraction)
     }
 }
@@ -23,6 +57,7 @@ impl LinearGradientPattern {
 #[cfg(test)]
 mod tests {
     use colo_rs::colors::Color;
+    use yare::parameterized;
 
     use crate::tuples::{points::Point, Tuple};
 
@@ -45,4 +80,20 @@ mod tests {
             Color::new(0.25, 0.25, 0.25)
         );
     }
+
+    #[parameterized(
+        mirror = {Repeat::Mirror, [0.0, 0.5, 1.0, 0.5, 0.0]},
+        wrap = {Repeat::Wrap, [0.0, 0.5, 0.0, 0.5, 0.0]},
+        clamp = {Repeat::Clamp, [0.0, 0.5, 1.0, 1.0, 1.0]},
+    )]
+    fn a_gradient_repeats_according_to_its_mode(repeat: Repeat, fractions: [f64; 5]) {
+        let mut pattern = LinearGradientPattern::new(Color::black(), Color::white());
+        pattern.with_repeat(repeat);
+        for (x, fraction) in [0.0, 0.5, 1.0, 1.5, 2.0].into_iter().zip(fractions) {
+            assert_eq!(
+                pattern.pattern_at(Point::new(x, 0.0, 0.0)),
+                Color::new(fraction, fraction, fraction)
+            );
+        }
+    }
 }
@@ -1,23 +1,33 @@
 pub mod blending_pattern;
 pub mod checker_pattern;
+pub mod cube_map_pattern;
+pub mod image_texture_pattern;
 pub mod linear_gradient_pattern;
+pub mod noise_pattern;
 pub mod perturbed_pattern;
 pub mod ring_pattern;
 pub mod solid_pattern;
 pub mod striped_pattern;
 pub mod test_pattern;
+pub mod uv_checker_pattern;
 
 use blending_pattern::BlendingPattern;
 use checker_pattern::CheckerPattern;
 use colo_rs::colors::Color;
-use linear_gradient_pattern::LinearGradientPattern;
+use cube_map_pattern::CubeMapPattern;
+use image_texture_pattern::ImageTexturePattern;
+use linear_gradient_pattern::{LinearGradientPattern, Repeat};
+use noise_pattern::NoisePattern;
 use perturbed_pattern::PerturbedPattern;
 use ring_pattern::RingPattern;
 use solid_pattern::SolidPattern;
 use striped_pattern::StripePattern;
 use test_pattern::TestPattern;
+use uv_checker_pattern::{UvCheckerPattern, UvMapping};
 
 use crate::{
+    canvas::Canvas,
+    matrix::Matrix,
     shapes::{Object, Shape},
     transformations::Transformation,
     tuples::points::Point,
@@ -33,6 +43,10 @@ enum PatternType {
     Solid(SolidPattern),
     Blending(BlendingPattern),
     Perturbed(PerturbedPattern),
+    Noise(NoisePattern),
+    UvChecker(UvCheckerPattern),
+    ImageTexture(ImageTexturePattern),
+    CubeMap(CubeMapPattern),
 }
 
 impl PatternType {
@@ -46,6 +60,27 @@ impl PatternType {
             PatternType::Solid(pattern) => pattern.pattern_at(),
             PatternType::Blending(pattern) => pattern.pattern_at(p),
             PatternType::Perturbed(pattern) => pattern.pattern_at(p),
+            PatternType::Noise(pattern) => pattern.pattern_at(p),
+            PatternType::UvChecker(pattern) => pattern.pattern_at(p),
+            PatternType::ImageTexture(pattern) => pattern.pattern_at(p),
+            PatternType::CubeMap(pattern) => pattern.pattern_at(p),
+        }
+    }
+
+    fn opacity_at(&self, p: Point) -> f64 {
+        match self {
+            PatternType::Striped(pattern) => pattern.opacity_at(p),
+            PatternType::Ring(pattern) => pattern.opacity_at(p),
+            PatternType::Checker(pattern) => pattern.opacity_at(p),
+            PatternType::Blending(pattern) => pattern.opacity_at(p),
+            PatternType::Perturbed(pattern) => pattern.opacity_at(p),
+            PatternType::CubeMap(pattern) => pattern.opacity_at(p),
+            PatternType::Test(_)
+            | PatternType::LinearGradient(_)
+            | PatternType::Solid(_)
+            | PatternType::Noise(_)
+            | PatternType::UvChecker(_)
+            | PatternType::ImageTexture(_) => 1.0,
         }
     }
 }
@@ -54,6 +89,7 @@ impl PatternType {
 pub struct Pattern {
     pattern_type: PatternType,
     transform: Transformation,
+    opacity: f64,
 }
 
 impl Pattern {
@@ -61,6 +97,7 @@ impl Pattern {
         Self {
             pattern_type: PatternType::Striped(StripePattern::new(a, b)),
             transform: Transformation::new_transform(),
+            opacity: 1.0,
         }
     }
 
@@ -68,6 +105,7 @@ impl Pattern {
         Self {
             pattern_type: PatternType::Test(TestPattern {}),
             transform: Transformation::new_transform(),
+            opacity: 1.0,
         }
     }
 
@@ -75,6 +113,7 @@ impl Pattern {
         Self {
             pattern_type: PatternType::LinearGradient(LinearGradientPattern::new(a, b)),
             transform: Transformation::new_transform(),
+            opacity: 1.0,
         }
     }
 
@@ -82,6 +121,7 @@ impl Pattern {
         Self {
             pattern_type: PatternType::Ring(RingPattern::new(a, b)),
             transform: Transformation::new_transform(),
+            opacity: 1.0,
         }
     }
 
@@ -89,6 +129,7 @@ impl Pattern {
         Self {
             pattern_type: PatternType::Checker(CheckerPattern::new(a, b)),
             transform: Transformation::new_transform(),
+            opacity: 1.0,
         }
     }
 
@@ -96,6 +137,7 @@ impl Pattern {
         Self {
             pattern_type: PatternType::Solid(SolidPattern::new(c)),
             transform: Transformation::new_transform(),
+            opacity: 1.0,
         }
     }
 
@@ -103,6 +145,15 @@ impl Pattern {
         Self {
             pattern_type: PatternType::Blending(BlendingPattern::new(a, b)),
             transform: Transformation::new_transform(),
+            opacity: 1.0,
+        }
+    }
+
+    pub fn new_blending_pattern_weighted(a: Pattern, b: Pattern, weight: f64) -> Self {
+        Self {
+            pattern_type: PatternType::Blending(BlendingPattern::new_weighted(a, b, weight)),
+            transform: Transformation::new_transform(),
+            opacity: 1.0,
         }
     }
 
@@ -110,6 +161,44 @@ impl Pattern {
         Self {
             pattern_type: PatternType::Perturbed(PerturbedPattern::new(p)),
             transform: Transformation::new_transform(),
+            opacity: 1.0,
+        }
+    }
+
+    pub fn new_noise_pattern(a: Color, b: Color, scale: f64) -> Self {
+        Self {
+            pattern_type: PatternType::Noise(NoisePattern::new(a, b, scale)),
+            transform: Transformation::new_transform(),
+            opacity: 1.0,
+        }
+    }
+
+    pub fn new_uv_checker(width: f64, height: f64, a: Color, b: Color, mapping: UvMapping) -> Self {
+        Self {
+            pattern_type: PatternType::UvChecker(UvCheckerPattern::new(
+                width, height, a, b, mapping,
+            )),
+            transform: Transformation::new_transform(),
+            opacity: 1.0,
+        }
+    }
+
+    pub fn new_image_texture(canvas: Canvas) -> Self {
+        Self {
+            pattern_type: PatternType::ImageTexture(ImageTexturePattern::new(
+                canvas,
+                UvMapping::default(),
+            )),
+            transform: Transformation::new_transform(),
+            opacity: 1.0,
+        }
+    }
+
+    pub fn new_cube_map(faces: [Pattern; 6]) -> Self {
+        Self {
+            pattern_type: PatternType::CubeMap(CubeMapPattern::new(faces)),
+            transform: Transformation::new_transform(),
+            opacity: 1.0,
         }
     }
 
@@ -118,17 +207,88 @@ impl Pattern {
         self
     }
 
-    fn pattern_at(&self, p: Point) -> Color {
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        if let PatternType::LinearGradient(ref mut pattern) = self.pattern_type {
+            pattern.with_repeat(repeat);
+        }
+        self
+    }
+
+    pub fn with_mapping(mut self, mapping: UvMapping) -> Self {
+        if let PatternType::ImageTexture(ref mut pattern) = self.pattern_type {
+            pattern.with_mapping(mapping);
+        }
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn solid_color(&self) -> Option<Color> {
+        match &self.pattern_type {
+            PatternType::Solid(pattern) => Some(pattern.pattern_at()),
+            _ => None,
+        }
+    }
+
+    /// Samples the pattern at a point given in the pattern's parent space.
+    ///
+    /// ```
+    /// use colo_rs::colors::Color;
+    /// use ray_tracer::patterns::Pattern;
+    /// use ray_tracer::tuples::{points::Point, Tuple};
+    ///
+    /// let pattern = Pattern::new_striped_pattern(
+    ///     Pattern::new_solid_pattern(Color::white()),
+    ///     Pattern::new_solid_pattern(Color::black()),
+    /// );
+    /// assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), Color::white());
+    /// assert_eq!(pattern.pattern_at(Point::new(1.0, 0.0, 0.0)), Color::black());
+    /// ```
+    pub fn pattern_at(&self, p: Point) -> Color {
         let pattern_point = self.transform.inverse().unwrap() * &p;
         self.pattern_type.pattern_at(pattern_point)
     }
 
-    pub fn pattern_at_object(&self, obj: &Object, p: Point) -> Color {
-        let object_point = match obj.shape() {
+    fn opacity_at(&self, p: Point) -> f64 {
+        let pattern_point = self.transform.inverse().unwrap() * &p;
+        self.opacity * self.pattern_type.opacity_at(pattern_point)
+    }
+
+    fn object_point(obj: &Object, p: Point) -> Point {
+        match obj.shape() {
             Shape::Group(_) => obj.world_to_object(p),
             _ => obj.to_object_space(&p).unwrap(),
+        }
+    }
+
+    pub fn pattern_at_object(&self, obj: &Object, p: Point) -> Color {
+        self.pattern_at(Self::object_point(obj, p))
+    }
+
+    pub fn opacity_at_object(&self, obj: &Object, p: Point) -> f64 {
+        self.opacity_at(Self::object_point(obj, p))
+    }
+
+    // A sphere's own (u, v) only matches what the pattern would sample when
+    // the pattern maps spherically and is not moved by its transform.
+    pub fn maps_spherically(&self) -> bool {
+        let mapping = match &self.pattern_type {
+            PatternType::UvChecker(pattern) => pattern.mapping(),
+            PatternType::ImageTexture(pattern) => pattern.mapping(),
+            _ => return false,
         };
-        self.pattern_at(object_point)
+        mapping == UvMapping::Spherical && self.transform.matrix == Matrix::identity(4)
+    }
+
+    pub fn color_at_hit(&self, obj: &Object, p: Point, uv: Option<(f64, f64)>) -> Color {
+        match (&self.pattern_type, uv) {
+            (PatternType::UvChecker(pattern), Some((u, v))) => pattern.uv_pattern_at(u, v),
+            (PatternType::ImageTexture(pattern), Some((u, v))) => pattern.uv_pattern_at(u, v),
+            _ => self.pattern_at_object(obj, p),
+        }
     }
 }
 
@@ -157,6 +317,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn nested_pattern_transforms_compose_with_their_parent() {
+        let pattern = Pattern::new_checker_pattern(
+            Pattern::new_striped_pattern(
+                Pattern::new_solid_pattern(Color::white()),
+                Pattern::new_solid_pattern(Color::black()),
+            )
+            .with_transform(Transformation::new_transform().translation(0.5, 0.0, 0.0)),
+            Pattern::new_solid_pattern(Color::red()),
+        )
+        .with_transform(Transformation::new_transform().scaling(2.0, 2.0, 2.0));
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.8, 0.0, 0.0)),
+            Color::black()
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(1.2, 0.0, 0.0)),
+            Color::white()
+        );
+        assert_eq!(pattern.pattern_at(Point::new(2.2, 0.0, 0.0)), Color::red());
+    }
+
     #[test]
     fn a_pattern_with_an_object_transformation() {
         let object = ObjectBuilder::new_sphere()
@@ -186,4 +368,28 @@ mod tests {
         let c = pattern.pattern_at_object(&object, Point::new(2.5, 3.0, 3.5));
         assert_eq!(c, Color::new(0.75, 0.5, 0.25));
     }
+
+    #[test]
+    fn a_pattern_is_opaque_by_default() {
+        let object = ObjectBuilder::new_sphere().build();
+        let pattern = Pattern::new_solid_pattern(Color::white());
+        assert_eq!(pattern.opacity_at_object(&object, Point::zero()), 1.0);
+    }
+
+    #[test]
+    fn a_checker_pattern_can_cut_out_every_other_cell() {
+        let object = ObjectBuilder::new_sphere().build();
+        let pattern = Pattern::new_checker_pattern(
+            Pattern::new_solid_pattern(Color::white()),
+            Pattern::new_solid_pattern(Color::black()).with_opacity(0.0),
+        );
+        assert_eq!(
+            pattern.opacity_at_object(&object, Point::new(0.5, 0.5, 0.5)),
+            1.0
+        );
+        assert_eq!(
+            pattern.opacity_at_object(&object, Point::new(1.5, 0.5, 0.5)),
+            0.0
+        );
+    }
 }
@@ -0,0 +1,85 @@
+use colo_rs::colors::Color;
+
+use crate::tuples::{points::Point, Tuple};
+
+const NOISE_SEED: u64 = 0x5EED_CAFE_F00D_D00D;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoisePattern {
+    a: Color,
+    b: Color,
+    scale: f64,
+    seed: u64,
+}
+
+impl NoisePattern {
+    pub fn new(a: Color, b: Color, scale: f64) -> Self {
+        Self {
+            a,
+            b,
+            scale,
+            seed: NOISE_SEED,
+        }
+    }
+
+    fn lattice_value(&self, x: i64, y: i64, z: i64) -> f64 {
+        let mut h = self.seed
+            ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+        h ^= h >> 33;
+        (h >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn noise_at(&self, p: Point) -> f64 {
+        let (x, y, z) = (p.x() / self.scale, p.y() / self.scale, p.z() / self.scale);
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let fade = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (fx, fy, fz) = (fade(x - x0), fade(y - y0), fade(z - z0));
+        let (ix, iy, iz) = (x0 as i64, y0 as i64, z0 as i64);
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+        let corner = |dx: i64, dy: i64, dz: i64| self.lattice_value(ix + dx, iy + dy, iz + dz);
+        let y0_z0 = lerp(corner(0, 0, 0), corner(1, 0, 0), fx);
+        let y1_z0 = lerp(corner(0, 1, 0), corner(1, 1, 0), fx);
+        let y0_z1 = lerp(corner(0, 0, 1), corner(1, 0, 1), fx);
+        let y1_z1 = lerp(corner(0, 1, 1), corner(1, 1, 1), fx);
+        lerp(lerp(y0_z0, y1_z0, fy), lerp(y0_z1, y1_z1, fy), fz)
+    }
+
+    pub fn pattern_at(&self, p: Point) -> Color {
+        let distance = &self.b - &self.a;
+        &self.a + &(distance * self.noise_at(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_reproducible_and_varies_across_space() {
+        let pattern = NoisePattern::new(Color::white(), Color::black(), 1.0);
+        let p = Point::new(0.3, 1.7, -2.2);
+        assert_eq!(pattern.pattern_at(p), pattern.pattern_at(p));
+        assert_eq!(
+            pattern.pattern_at(p),
+            NoisePattern::new(Color::white(), Color::black(), 1.0).pattern_at(p)
+        );
+        let far = Point::new(40.5, -12.5, 7.5);
+        assert_ne!(pattern.pattern_at(p), pattern.pattern_at(far));
+    }
+
+    #[test]
+    fn noise_stays_between_the_two_colors() {
+        let pattern = NoisePattern::new(Color::white(), Color::black(), 0.5);
+        for i in 0..50 {
+            let t = i as f64 * 0.37;
+            let n = pattern.noise_at(Point::new(t, -t * 0.5, t * 1.3));
+            assert!((0.0..=1.0).contains(&n));
+        }
+    }
+}
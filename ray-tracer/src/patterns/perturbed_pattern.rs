@@ -17,16 +17,23 @@ impl PerturbedPattern {
         }
     }
 
-    pub fn pattern_at(&self, p: Point) -> Color {
+    fn perturb(&self, p: Point) -> Point {
         let perlin = PerlinNoise::new();
         let perturbed_x = perlin.get3d([p.x(), p.y(), p.z()]) * 0.5;
         let perturbed_y = perlin.get3d([p.x(), p.y(), p.z()]) * 0.5;
         let perturbed_z = perlin.get3d([p.x(), p.y(), p.z()]) * 0.5;
-        let new_p = Point::new(
+        Point::new(
             p.x() + perturbed_x,
             p.y() + perturbed_y,
             p.z() + perturbed_z,
-        );
-        self.pattern.pattern_at(new_p)
+        )
+    }
+
+    pub fn pattern_at(&self, p: Point) -> Color {
+        self.pattern.pattern_at(self.perturb(p))
+    }
+
+    pub fn opacity_at(&self, p: Point) -> f64 {
+        self.pattern.opacity_at(self.perturb(p))
     }
 }
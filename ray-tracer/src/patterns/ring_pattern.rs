@@ -18,16 +18,24 @@ impl RingPattern {
         }
     }
 
-    pub fn pattern_at(&self, p: Point) -> Color {
+    fn pick(&self, p: Point) -> &Pattern {
         let x_squared = p.x() * p.x();
         let z_squared = p.z() * p.z();
         let distance = (x_squared + z_squared).sqrt();
         if distance.floor() % 2.0 == 0.0 {
-            self.a.pattern_at(p)
+            &self.a
         } else {
-            self.b.pattern_at(p)
+            &self.b
         }
     }
+
+    pub fn pattern_at(&self, p: Point) -> Color {
+        self.pick(p).pattern_at(p)
+    }
+
+    pub fn opacity_at(&self, p: Point) -> f64 {
+        self.pick(p).opacity_at(p)
+    }
 }
 
 #[cfg(test)]
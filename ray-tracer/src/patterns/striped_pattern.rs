@@ -1,9 +1,6 @@
 use colo_rs::colors::Color;
 
-use crate::{
-    transformations::Transformation,
-    tuples::{points::Point, Tuple},
-};
+use crate::tuples::{points::Point, Tuple};
 
 use super::Pattern;
 
@@ -11,7 +8,6 @@ use super::Pattern;
 pub struct StripePattern {
     a: Box<Pattern>,
     b: Box<Pattern>,
-    transform: Transformation,
 }
 
 impl StripePattern {
@@ -19,22 +15,24 @@ impl StripePattern {
         Self {
             a: Box::new(a),
             b: Box::new(b),
-            transform: Transformation::new_transform(),
         }
     }
 
-    pub fn with_transform(mut self, t: Transformation) -> Self {
-        self.transform = t;
-        self
-    }
-
-    pub fn pattern_at(&self, p: Point) -> Color {
+    fn pick(&self, p: Point) -> &Pattern {
         if p.x().floor() % 2.0 == 0.0 {
-            self.a.pattern_at(p)
+            &self.a
         } else {
-            self.b.pattern_at(p)
+            &self.b
         }
     }
+
+    pub fn pattern_at(&self, p: Point) -> Color {
+        self.pick(p).pattern_at(p)
+    }
+
+    pub fn opacity_at(&self, p: Point) -> f64 {
+        self.pick(p).opacity_at(p)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,168 @@
+use std::f64::consts::PI;
+
+use colo_rs::colors::Color;
+
+use crate::tuples::{points::Point, Tuple};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum UvMapping {
+    #[default]
+    Spherical,
+    Planar,
+    Cylindrical,
+}
+
+impl UvMapping {
+    pub fn map(&self, p: Point) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => {
+                let theta = f64::atan2(p.x(), p.z());
+                let radius = (p.x() * p.x() + p.y() * p.y() + p.z() * p.z()).sqrt();
+                let phi = (p.y() / radius).clamp(-1.0, 1.0).acos();
+                let raw_u = theta / (2.0 * PI);
+                (1.0 - (raw_u + 0.5), 1.0 - phi / PI)
+            }
+            UvMapping::Planar => (p.x().rem_euclid(1.0), p.z().rem_euclid(1.0)),
+            UvMapping::Cylindrical => {
+                let theta = f64::atan2(p.x(), p.z());
+                let raw_u = theta / (2.0 * PI);
+                (1.0 - (raw_u + 0.5), p.y().rem_euclid(1.0))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvCheckerPattern {
+    width: f64,
+    height: f64,
+    a: Color,
+    b: Color,
+    mapping: UvMapping,
+}
+
+impl UvCheckerPattern {
+    pub fn new(width: f64, height: f64, a: Color, b: Color, mapping: UvMapping) -> Self {
+        Self {
+            width,
+            height,
+            a,
+            b,
+            mapping,
+        }
+    }
+
+    pub fn mapping(&self) -> UvMapping {
+        self.mapping
+    }
+
+    pub(crate) fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let u2 = (u * self.width).floor();
+        let v2 = (v * self.height).floor();
+        if (u2 + v2).rem_euclid(2.0) == 0.0 {
+            self.a
+        } else {
+            self.b
+        }
+    }
+
+    pub fn pattern_at(&self, p: Point) -> Color {
+        let (u, v) = self.mapping.map(p);
+        self.uv_pattern_at(u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use approx_eq::ApproxEq;
+    use yare::parameterized;
+
+    use super::*;
+
+    #[parameterized(
+        front = {Point::new(0.0, 0.0, -1.0), 0.0, 0.5},
+        right = {Point::new(1.0, 0.0, 0.0), 0.25, 0.5},
+        back = {Point::new(0.0, 0.0, 1.0), 0.5, 0.5},
+        left = {Point::new(-1.0, 0.0, 0.0), 0.75, 0.5},
+        top = {Point::new(0.0, 1.0, 0.0), 0.5, 1.0},
+        bottom = {Point::new(0.0, -1.0, 0.0), 0.5, 0.0},
+        diagonal = {Point::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0), 0.25, 0.75},
+    )]
+    fn using_a_spherical_mapping_on_a_3d_point(p: Point, u: f64, v: f64) {
+        let (actual_u, actual_v) = UvMapping::Spherical.map(p);
+        assert!(actual_u.approx_eq(u));
+        assert!(actual_v.approx_eq(v));
+    }
+
+    #[parameterized(
+        p1 = {Point::new(0.25, 0.0, 0.5), 0.25, 0.5},
+        p2 = {Point::new(0.25, 0.0, -0.25), 0.25, 0.75},
+        p3 = {Point::new(0.25, 0.5, -0.25), 0.25, 0.75},
+        p4 = {Point::new(1.25, 0.0, 0.5), 0.25, 0.5},
+        p5 = {Point::new(0.25, 0.0, -1.75), 0.25, 0.25},
+        p6 = {Point::new(1.0, 0.0, -1.0), 0.0, 0.0},
+        p7 = {Point::new(0.0, 0.0, 0.0), 0.0, 0.0},
+    )]
+    fn using_a_planar_mapping_on_a_3d_point(p: Point, u: f64, v: f64) {
+        let (actual_u, actual_v) = UvMapping::Planar.map(p);
+        assert!(actual_u.approx_eq(u));
+        assert!(actual_v.approx_eq(v));
+    }
+
+    #[parameterized(
+        p1 = {Point::new(0.0, 0.0, -1.0), 0.0, 0.0},
+        p2 = {Point::new(0.0, 0.5, -1.0), 0.0, 0.5},
+        p3 = {Point::new(0.0, 1.0, -1.0), 0.0, 0.0},
+        p4 = {Point::new(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), 0.125, 0.5},
+        p5 = {Point::new(1.0, 0.5, 0.0), 0.25, 0.5},
+        p6 = {Point::new(FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), 0.375, 0.5},
+        p7 = {Point::new(0.0, -0.25, 1.0), 0.5, 0.75},
+    )]
+    fn using_a_cylindrical_mapping_on_a_3d_point(p: Point, u: f64, v: f64) {
+        let (actual_u, actual_v) = UvMapping::Cylindrical.map(p);
+        assert!(actual_u.approx_eq(u));
+        assert!(actual_v.approx_eq(v));
+    }
+
+    #[parameterized(
+        p1 = {0.0, 0.0, Color::black()},
+        p2 = {0.5, 0.0, Color::white()},
+        p3 = {0.0, 0.5, Color::white()},
+        p4 = {0.5, 0.5, Color::black()},
+        p5 = {1.0, 1.0, Color::black()},
+    )]
+    fn checker_pattern_in_2d(u: f64, v: f64, expected: Color) {
+        let pattern =
+            UvCheckerPattern::new(2.0, 2.0, Color::black(), Color::white(), UvMapping::Planar);
+        assert_eq!(pattern.uv_pattern_at(u, v), expected);
+    }
+
+    #[test]
+    fn a_uv_checker_wraps_a_sphere() {
+        let pattern = UvCheckerPattern::new(
+            16.0,
+            8.0,
+            Color::black(),
+            Color::white(),
+            UvMapping::Spherical,
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.4315, 0.4670, 0.7719)),
+            Color::white()
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(-0.9654, 0.2552, -0.0534)),
+            Color::black()
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.1039, 0.7090, 0.6975)),
+            Color::white()
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(-0.4986, -0.7856, -0.3663)),
+            Color::black()
+        );
+    }
+}
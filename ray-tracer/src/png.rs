@@ -0,0 +1,55 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use anyhow::{anyhow, Result};
+
+use crate::canvas::Canvas;
+
+pub fn write_png(canvas: &Canvas, path: &Path) -> Result<()> {
+    let file = File::create(path).map_err(|e| anyhow!("Cannot write {}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        canvas.width() as u32,
+        canvas.height() as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&canvas.to_rgb8())?;
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use colo_rs::colors::Color;
+
+    use super::*;
+
+    #[test]
+    fn writing_and_reading_back_a_png() {
+        let channels = |x: usize, y: usize| [x as f64 / 3.0, y as f64 / 2.0, 1.5 - x as f64];
+        let canvas = Canvas::from_fn(4, 3, |x, y| {
+            let [r, g, b] = channels(x, y);
+            Color::new(r, g, b)
+        });
+        let path = std::env::temp_dir().join(format!("ray-tracer-{}.png", std::process::id()));
+        write_png(&canvas, &path).unwrap();
+
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!((info.width, info.height), (4, 3));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        for (i, rgb) in buf.chunks(3).enumerate() {
+            let expected = channels(i % 4, i / 4);
+            for (actual, expected) in rgb.iter().zip(expected) {
+                assert!((*actual as f64 / 255.0 - expected.clamp(0.0, 1.0)).abs() <= 0.5 / 255.0);
+            }
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
 use colo_rs::colors::Color;
 
 use crate::canvas::Canvas;
@@ -70,6 +73,27 @@ impl ToString for PPM {
     }
 }
 
+impl PPM {
+    pub fn from_canvas_gamma(canvas: Canvas, gamma: f64) -> Self {
+        let mut ppm = Self::from(canvas);
+        ppm.pixel_data
+            .data
+            .iter_mut()
+            .for_each(|c| *c = c.map(|channel| channel.max(0.0).powf(1.0 / gamma)));
+        ppm
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = Header {
+            version: String::from("P6"),
+            ..self.header
+        };
+        let mut bytes = header.to_string().into_bytes();
+        bytes.extend(self.pixel_data.data.iter().flat_map(|c| c.as_255_bytes()));
+        bytes
+    }
+}
+
 impl From<Canvas> for PPM {
     fn from(canvas: Canvas) -> Self {
         Self {
@@ -81,12 +105,146 @@ impl From<Canvas> for PPM {
             },
             pixel_data: PixelData {
                 width: canvas.width(),
-                data: canvas.pixels().clone(),
+                data: canvas.pixels().to_vec(),
             },
         }
     }
 }
 
+struct Tokens<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if b == b'#' {
+                while self.bytes.get(self.pos).is_some_and(|&b| b != b'\n') {
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Option<&'a str> {
+        self.skip_whitespace_and_comments();
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()
+            .filter(|t| !t.is_empty())
+    }
+
+    fn next_number(&mut self, what: &str) -> Result<usize> {
+        let token = self.next_token().ok_or(anyhow!("Missing PPM {}", what))?;
+        token
+            .parse::<usize>()
+            .map_err(|e| anyhow!("Invalid PPM {} {}: {}", what, token, e))
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn raster(&mut self, len: usize) -> Result<&'a [u8]> {
+        match self.bytes.get(self.pos) {
+            Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+            _ => return Err(anyhow!("Missing whitespace before PPM raster")),
+        }
+        if len > self.remaining() {
+            return Err(anyhow!(
+                "Truncated PPM raster: expected {} bytes, found {}",
+                len,
+                self.remaining()
+            ));
+        }
+        let raster = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(raster)
+    }
+}
+
+impl PPM {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut tokens = Tokens { bytes, pos: 0 };
+        let version = tokens.next_token().ok_or(anyhow!("Missing PPM version"))?;
+        if version != "P3" && version != "P6" {
+            return Err(anyhow!("Unsupported PPM version: {}", version));
+        }
+        let width = tokens.next_number("width")?;
+        let height = tokens.next_number("height")?;
+        let max_color = tokens.next_number("max color")?;
+        let max_color = u8::try_from(max_color)
+            .ok()
+            .filter(|max_color| *max_color > 0)
+            .ok_or(anyhow!("Unsupported PPM max color: {}", max_color))?;
+        let scale = |c: usize| c as f64 / max_color as f64;
+        let too_large = || anyhow!("PPM dimensions {}x{} are too large", width, height);
+        let pixels = width.checked_mul(height).ok_or_else(too_large)?;
+
+        let data = if version == "P6" {
+            tokens
+                .raster(pixels.checked_mul(3).ok_or_else(too_large)?)?
+                .chunks(3)
+                .map(|c| {
+                    Color::new(
+                        scale(c[0] as usize),
+                        scale(c[1] as usize),
+                        scale(c[2] as usize),
+                    )
+                })
+                .collect()
+        } else {
+            // Every ASCII pixel takes at least six bytes ("0 0 0 "), so never
+            // reserve more than the remaining input could hold.
+            let mut data = Vec::with_capacity(pixels.min(tokens.remaining() / 6));
+            for _ in 0..pixels {
+                let r = scale(tokens.next_number("red")?);
+                let g = scale(tokens.next_number("green")?);
+                let b = scale(tokens.next_number("blue")?);
+                data.push(Color::new(r, g, b));
+            }
+            data
+        };
+        Ok(Self {
+            header: Header {
+                version: version.to_string(),
+                width,
+                height,
+                max_color,
+            },
+            pixel_data: PixelData { width, data },
+        })
+    }
+}
+
+impl FromStr for PPM {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s.as_bytes())
+    }
+}
+
+impl From<PPM> for Canvas {
+    fn from(ppm: PPM) -> Self {
+        let mut canvas = Canvas::new(ppm.header.width, ppm.header.height);
+        *canvas.pixels_mut() = ppm.pixel_data.data;
+        canvas
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,4 +293,133 @@ mod tests {
 153 255 204 153 255 204 153 255 204 153 255 204 153"#
         );
     }
+
+    #[test]
+    fn gamma_correcting_ppm_pixel_data() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write_pixel(0, 0, Color::new(0.5, 0.5, 0.5));
+        canvas.write_pixel(1, 0, Color::new(-0.5, 0.0, 1.5));
+        assert_eq!(
+            PPM::from(canvas.clone()).pixel_data.to_string(),
+            "128 128 128 0 0 255"
+        );
+        assert_eq!(
+            PPM::from_canvas_gamma(canvas.clone(), 1.0)
+                .pixel_data
+                .to_string(),
+            "128 128 128 0 0 255"
+        );
+        assert_eq!(
+            PPM::from_canvas_gamma(canvas, 2.2).pixel_data.to_string(),
+            "186 186 186 0 0 255"
+        );
+    }
+
+    #[test]
+    fn writing_a_binary_ppm() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.write_pixel(2, 1, Color::new(0.0, 0.5, 0.0));
+        canvas.write_pixel(4, 2, Color::new(-0.5, 0.0, 1.0));
+        let ppm = PPM::from(canvas);
+        let bytes = ppm.to_bytes();
+        let header = b"P6\n5 3\n255\n";
+        assert_eq!(&bytes[..header.len()], header);
+
+        let pixels = &bytes[header.len()..];
+        let ascii = ppm
+            .pixel_data
+            .to_string()
+            .split_whitespace()
+            .map(|v| v.parse::<u8>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pixels.len(), 5 * 3 * 3);
+        assert_eq!(pixels, ascii.as_slice());
+    }
+
+    #[test]
+    fn reading_a_canvas_from_ppm() {
+        let ppm: PPM = r#"P3
+# a comment
+2 2
+255
+255 0 0  0 255 0
+0 0 255  51 102 153
+"#
+        .parse()
+        .unwrap();
+        let canvas = Canvas::from(ppm);
+        assert_eq!(canvas.width(), 2);
+        assert_eq!(canvas.height(), 2);
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(canvas.pixel_at(0, 1), Color::new(0.0, 0.0, 1.0));
+        assert_eq!(canvas.pixel_at(1, 1), Color::new(0.2, 0.4, 0.6));
+    }
+
+    #[test]
+    fn reading_a_truncated_ppm_fails() {
+        assert!("P3\n2 2\n255\n255 0 0".parse::<PPM>().is_err());
+        assert!(PPM::parse(b"P6\n2 2\n255\n\0\0\0").is_err());
+    }
+
+    #[test]
+    fn reading_a_ppm_with_huge_dimensions_fails_without_allocating() {
+        assert!(PPM::parse(b"P3\n100000 100000\n255\n0 0 0").is_err());
+        assert!(PPM::parse(b"P6\n100000 100000\n255\n\0\0\0").is_err());
+        let overflow = format!("P6\n{} 2\n255\n\0\0\0", usize::MAX);
+        let Err(error) = PPM::parse(overflow.as_bytes()) else {
+            panic!("overflowing dimensions were accepted");
+        };
+        assert!(error.to_string().contains("too large"));
+    }
+
+    #[test]
+    fn reading_a_malformed_ppm_header_fails() {
+        assert!(PPM::parse(b"P5\n1 1\n255\n0").is_err());
+        assert!(PPM::parse(b"P3\n1 x\n255\n0 0 0").is_err());
+        assert!(PPM::parse(b"P3\n1 1\n0\n0 0 0").is_err());
+        assert!(PPM::parse(b"P3\n1 1\n65535\n0 0 0").is_err());
+        assert!(PPM::parse(b"P6\n1 1").is_err());
+    }
+
+    #[test]
+    fn reading_a_binary_ppm() {
+        let bytes = b"P6\n# a comment\n2 1\n255\n\xff\x00\x00\x33\x66\x99";
+        let canvas = Canvas::from_ppm(bytes).unwrap();
+        assert_eq!(canvas.width(), 2);
+        assert_eq!(canvas.height(), 1);
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(0.2, 0.4, 0.6));
+    }
+
+    fn gradient() -> Canvas {
+        Canvas::from_fn(7, 5, |x, y| {
+            Color::new(x as f64 / 6.0, y as f64 / 4.0, 1.0 - x as f64 / 6.0)
+        })
+    }
+
+    fn assert_within_rounding(expected: &Canvas, actual: &Canvas) {
+        assert_eq!(expected.width(), actual.width());
+        assert_eq!(expected.height(), actual.height());
+        for (e, a) in expected.pixels().iter().zip(actual.pixels()) {
+            assert_eq!(e.as_255_bytes(), a.as_255_bytes());
+        }
+    }
+
+    #[test]
+    fn a_canvas_round_trips_through_ascii_ppm() {
+        let canvas = gradient();
+        let ascii = PPM::from(canvas.clone()).to_string();
+        let parsed = Canvas::from_ppm(ascii.as_bytes()).unwrap();
+        assert_within_rounding(&canvas, &parsed);
+    }
+
+    #[test]
+    fn a_canvas_round_trips_through_binary_ppm() {
+        let canvas = gradient();
+        let binary = PPM::from(canvas.clone()).to_bytes();
+        let parsed = Canvas::from_ppm(&binary).unwrap();
+        assert_within_rounding(&canvas, &parsed);
+    }
 }
@@ -5,23 +5,58 @@ use crate::{
 
 #[derive(Debug, Clone, Copy)]
 pub struct Ray {
+    /// Prefer `Ray::origin()`; this field is slated to become private.
     pub origin: Point,
+    /// Prefer `Ray::direction()`; this field is slated to become private.
     pub direction: Vector,
+    pub wavelength: Option<f64>,
 }
 
 impl Ray {
-    pub fn new(origin: Point, direction: Vector) -> Self {
-        Self { origin, direction }
+    pub const fn new(origin: Point, direction: Vector) -> Self {
+        Self {
+            origin,
+            direction,
+            wavelength: None,
+        }
+    }
+
+    pub fn with_wavelength(mut self, wavelength: f64) -> Self {
+        self.wavelength = Some(wavelength);
+        self
+    }
+
+    pub const fn origin(&self) -> Point {
+        self.origin
+    }
+
+    pub const fn direction(&self) -> Vector {
+        self.direction
     }
 
     pub fn position(&self, distance: f64) -> Point {
         self.origin + self.direction * distance
     }
 
+    /// Alias for `position`: the point `t` units along the ray.
+    ///
+    /// ```
+    /// use ray_tracer::rays::Ray;
+    /// use ray_tracer::tuples::{points::Point, vectors::Vector, Tuple};
+    ///
+    /// let r = Ray::new(Point::new(2.0, 3.0, 4.0), Vector::new(1.0, 0.0, 0.0));
+    /// assert_eq!(r.at(2.0), Point::new(4.0, 3.0, 4.0));
+    /// ```
+    pub fn at(&self, t: f64) -> Point {
+        self.position(t)
+    }
+
     pub fn transform(&self, t: &Matrix) -> Self {
+        debug_assert!(t.preserves_w(&self.origin) && t.preserves_w(&self.direction));
         Self {
             origin: t * &self.origin,
             direction: t * &self.direction,
+            wavelength: self.wavelength,
         }
     }
 }
@@ -44,6 +79,8 @@ mod tests {
         let r = Ray::new(origin, direction);
         assert_eq!(r.origin, origin);
         assert_eq!(r.direction, direction);
+        assert_eq!(r.origin(), origin);
+        assert_eq!(r.direction(), direction);
     }
 
     #[test]
@@ -55,6 +92,12 @@ mod tests {
         assert_eq!(r.position(2.5), Point::new(4.5, 3.0, 4.0));
     }
 
+    #[test]
+    fn at_is_an_alias_for_position() {
+        let r = Ray::new(Point::new(2.0, 3.0, 4.0), Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(r.at(2.5), r.position(2.5));
+    }
+
     #[test]
     fn a_ray_intersects_a_sphere_in_two_points() {
         let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
@@ -0,0 +1,92 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::{canvas::Canvas, png::write_png, ppm::PPM, yaml_loader::YamlLoader};
+
+#[derive(Debug, Clone, Default)]
+pub struct RenderSettings {
+    size: Option<(usize, usize)>,
+    resources: Option<PathBuf>,
+}
+
+impl RenderSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_size(mut self, width: usize, height: usize) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn with_resources(mut self, resources: PathBuf) -> Self {
+        self.resources = Some(resources);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderStats {
+    pub width: usize,
+    pub height: usize,
+    pub objects: usize,
+    pub lights: usize,
+    pub elapsed: Duration,
+}
+
+enum OutputFormat {
+    Ppm,
+    Png,
+}
+
+impl OutputFormat {
+    fn from_path(out: &Path) -> Result<Self> {
+        match out.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("ppm") => Ok(OutputFormat::Ppm),
+            Some(ext) if ext.eq_ignore_ascii_case("png") => Ok(OutputFormat::Png),
+            _ => Err(anyhow!("Unsupported output format: {}", out.display())),
+        }
+    }
+
+    fn write(&self, canvas: Canvas, out: &Path) -> Result<()> {
+        match self {
+            OutputFormat::Ppm => fs::write(out, PPM::from(canvas).to_string())
+                .map_err(|e| anyhow!("Cannot write {}: {}", out.display(), e)),
+            OutputFormat::Png => write_png(&canvas, out),
+        }
+    }
+}
+
+pub fn render_scene(path: &Path, out: &Path, settings: RenderSettings) -> Result<RenderStats> {
+    let format = OutputFormat::from_path(out)?;
+    let loader = match &settings.resources {
+        Some(resources) => YamlLoader::load_with_resources(path, resources)?,
+        None => YamlLoader::load(path)?,
+    };
+    let camera = match settings.size {
+        Some((width, height)) => loader.camera().clone().with_size(width, height),
+        None => loader.camera().clone(),
+    };
+    let world = loader.world();
+    let objects = world.objects().len();
+    let lights = world.lights().len();
+
+    let now = Instant::now();
+    let canvas = camera.render(world);
+    let elapsed = now.elapsed();
+    let (width, height) = (canvas.width(), canvas.height());
+    format.write(canvas, out)?;
+
+    Ok(RenderStats {
+        width,
+        height,
+        objects,
+        lights,
+        elapsed,
+    })
+}
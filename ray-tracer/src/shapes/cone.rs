@@ -1,3 +1,5 @@
+use std::f64::consts::FRAC_PI_2;
+
 use approx_eq::{ApproxEq, EPSILON};
 
 use crate::{
@@ -14,6 +16,7 @@ pub struct Cone {
     min: f64,
     max: f64,
     cap: Cap,
+    slope: f64,
 }
 
 impl Default for Cone {
@@ -22,6 +25,7 @@ impl Default for Cone {
             min: f64::NEG_INFINITY,
             max: f64::INFINITY,
             cap: Cap::Uncapped,
+            slope: 1.0,
         }
     }
 }
@@ -39,19 +43,42 @@ impl Cone {
         self.cap = cap;
     }
 
+    /// Sets the angle between the cone's side and its axis, in radians. It is
+    /// clamped into the open range (0, π/2): a flat or inverted cone, or one
+    /// opened into a plane, has no finite slope.
+    pub fn with_half_angle(&mut self, angle: f64) {
+        self.slope = angle.clamp(EPSILON, FRAC_PI_2 - EPSILON).tan();
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    pub fn cap(&self) -> &Cap {
+        &self.cap
+    }
+
+    pub fn half_angle(&self) -> f64 {
+        self.slope.atan()
+    }
+
     fn intersects_caps<'a>(&self, object: &'a Object, r: &Ray, xs: &mut Intersections<'a>) {
         if self.cap == Cap::Uncapped || r.direction.y().approx_eq(0.0) {
             return;
         }
         if self.cap == Cap::Both || self.cap == Cap::BottomCap {
             let t = (self.min - r.origin.y()) / r.direction.y();
-            if Cone::check_cap(r, t, self.min) {
+            if Cone::check_cap(r, t, self.slope * self.min) {
                 xs.push(Intersection::new(t, object));
             }
         }
         if self.cap == Cap::Both || self.cap == Cap::TopCap {
             let t = (self.max - r.origin.y()) / r.direction.y();
-            if Cone::check_cap(r, t, self.max) {
+            if Cone::check_cap(r, t, self.slope * self.max) {
                 xs.push(Intersection::new(t, object));
             }
         }
@@ -65,7 +92,7 @@ impl Cone {
 
     pub fn normal_at(&self, object_point: Point) -> Vector {
         let x2 = object_point.x().powi(2);
-        let y2 = object_point.y().powi(2);
+        let y2 = (self.slope * object_point.y()).powi(2);
         let z2 = object_point.z().powi(2);
 
         let dist = x2 + z2;
@@ -82,9 +109,9 @@ impl Cone {
             Vector::y_norm() * -1.0
         } else {
             let y = if object_point.y() > 0.0 {
-                -f64::sqrt(dist)
+                -self.slope * f64::sqrt(dist)
             } else {
-                f64::sqrt(dist)
+                self.slope * f64::sqrt(dist)
             };
 
             Vector::new(object_point.x(), y, object_point.z())
@@ -92,10 +119,11 @@ impl Cone {
     }
 
     pub fn intersects<'a>(&self, object: &'a Object, r: &Ray) -> Intersections<'a> {
-        let a = r.direction.x().powi(2) - r.direction.y().powi(2) + r.direction.z().powi(2);
-        let b = 2.0 * r.origin.x() * r.direction.x() - 2.0 * r.origin.y() * r.direction.y()
+        let k2 = self.slope.powi(2);
+        let a = r.direction.x().powi(2) - k2 * r.direction.y().powi(2) + r.direction.z().powi(2);
+        let b = 2.0 * r.origin.x() * r.direction.x() - 2.0 * k2 * r.origin.y() * r.direction.y()
             + 2.0 * r.origin.z() * r.direction.z();
-        let c = r.origin.x().powi(2) - r.origin.y().powi(2) + r.origin.z().powi(2);
+        let c = r.origin.x().powi(2) - k2 * r.origin.y().powi(2) + r.origin.z().powi(2);
         let mut intersections = if a.approx_eq(0.0) {
             if b.approx_eq(0.0) {
                 Intersections::new()
@@ -127,13 +155,14 @@ impl Cone {
             }
         };
         self.intersects_caps(object, r, &mut intersections);
+        intersections.sort_by(|i1, i2| i1.t.total_cmp(&i2.t));
         intersections
     }
 
     pub fn bounds(&self) -> Bounds {
         let abs_min = self.min.abs();
         let abs_max = self.max.abs();
-        let limit = abs_min.max(abs_max);
+        let limit = self.slope * abs_min.max(abs_max);
         Bounds::new(
             Point::new(-limit, self.min, -limit),
             Point::new(limit, self.max, limit),
@@ -143,7 +172,7 @@ impl Cone {
 
 #[cfg(test)]
 mod tests {
-    use std::f64::{INFINITY, NEG_INFINITY};
+    use std::f64::{consts::FRAC_PI_3, INFINITY, NEG_INFINITY};
 
     use crate::shapes::ObjectBuilder;
 
@@ -303,6 +332,47 @@ mod tests {
         assert_eq!(n, normal);
     }
 
+    #[test]
+    fn a_shallower_cone_is_struck_at_different_distances() {
+        let r = Ray::new(Point::new(0.0, 0.5, -5.0), Vector::z_norm());
+        let default = ObjectBuilder::new_cone().build();
+        let shallow = ObjectBuilder::new_cone().with_half_angle(FRAC_PI_3).build();
+        let xs = default.intersects(&r);
+        assert!(xs[0].t.approx_eq(4.5));
+        assert!(xs[1].t.approx_eq(5.5));
+        let xs = shallow.intersects(&r);
+        assert!(xs[0].t.approx_eq(5.0 - 0.5 * 3.0_f64.sqrt()));
+        assert!(xs[1].t.approx_eq(5.0 + 0.5 * 3.0_f64.sqrt()));
+    }
+
+    #[test]
+    fn the_normal_on_a_shallower_cone_is_perpendicular_to_its_side() {
+        let mut cone = Cone::default();
+        cone.with_half_angle(FRAC_PI_3);
+        let p = Point::new(3.0_f64.sqrt(), 1.0, 0.0);
+        let n = cone.normal_at(p).normalize();
+        let side = Vector::new(3.0_f64.sqrt(), 1.0, 0.0).normalize();
+        assert!(n.dot(side).approx_eq(0.0));
+        assert!(n.y() < 0.0);
+    }
+
+    #[parameterized(
+        zero = {0.0},
+        negative = {-0.5},
+        right_angle = {FRAC_PI_2},
+        obtuse = {2.0},
+    )]
+    fn an_out_of_range_half_angle_is_clamped(angle: f64) {
+        let mut cone = Cone::default();
+        cone.with_min(-1.0);
+        cone.with_max(1.0);
+        cone.with_half_angle(angle);
+        assert!(cone.half_angle() > 0.0 && cone.half_angle() < FRAC_PI_2);
+        let b = cone.bounds();
+        assert!(b.is_finite());
+        assert!(b.max().x() > 0.0);
+    }
+
     #[test]
     fn an_unbounded_cone_has_a_bounding_box() {
         let s = Cone::default();
@@ -330,4 +400,16 @@ mod tests {
         assert_eq!(b.min(), &Point::new(-5.0, -5.0, -5.0));
         assert_eq!(b.max(), &Point::new(5.0, 3.0, 5.0));
     }
+
+    #[test]
+    fn the_half_angle_widens_the_bounding_box() {
+        let mut s = Cone::default();
+        s.with_min(-1.0);
+        s.with_max(2.0);
+        s.with_half_angle(FRAC_PI_3);
+        let b = s.bounds();
+        assert!(b.max().x().approx_eq(2.0 * 3.0_f64.sqrt()));
+        assert!(b.min().z().approx_eq(-2.0 * 3.0_f64.sqrt()));
+        assert!(b.max().y().approx_eq(2.0));
+    }
 }
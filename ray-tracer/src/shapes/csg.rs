@@ -25,6 +25,10 @@ impl CSG {
         }
     }
 
+    pub fn kind(&self) -> &CSGKind {
+        &self.kind
+    }
+
     pub fn left(&self) -> &Object {
         self.left.as_ref()
     }
@@ -59,15 +63,12 @@ impl CSG {
         }
     }
 
+    // CSG filtering happens here, before any caller looks for a hit, so
+    // Intersections::hit never sees surfaces the operation culled.
     pub fn intersects(&self, _object: &Object, ray: &Ray) -> Intersections {
         let left_xs = self.left().intersects(ray);
         let right_xs = self.right().intersects(ray);
-        let mut xs: Intersections = left_xs
-            .clone()
-            .into_iter()
-            .chain(right_xs.clone().into_iter())
-            .collect();
-        xs.sort_unstable_by(|i1, i2| i1.t.total_cmp(&i2.t));
+        let xs = left_xs.merge_sorted(right_xs);
         self.filter_intersections(&xs)
     }
 
@@ -75,7 +76,7 @@ impl CSG {
         let (_, _, result) = xs.iter().fold(
             (false, false, Intersections::new()),
             |(mut inside_left, mut inside_right, mut xs), i| {
-                let left_hit = self.left.includes(i.object);
+                let left_hit = self.left.csg_includes(i.object);
                 if self.intersection_allowed(left_hit, inside_left, inside_right) {
                     xs.push(*i);
                 }
@@ -99,7 +100,8 @@ mod tests {
     use crate::{
         intersections::{Intersection, Intersections},
         shapes::ObjectBuilder,
-        tuples::vectors::Vector,
+        transformations::Transformation,
+        tuples::{points::Point, vectors::Vector, Tuple},
     };
 
     use super::*;
@@ -160,15 +162,47 @@ mod tests {
     fn filtering_a_list_of_intersections(operation: CSGKind, x0: f64, x1: f64) {
         let s1 = ObjectBuilder::new_sphere().build();
         let s2 = ObjectBuilder::new_cube().build();
-        let c = CSG::new(operation, s1.clone(), s2.clone());
+        let c = CSG::new(operation, s1, s2);
         let mut xs = Intersections::new();
-        xs.push(Intersection::new(0.0, &s1));
-        xs.push(Intersection::new(1.0, &s2));
-        xs.push(Intersection::new(2.0, &s1));
-        xs.push(Intersection::new(3.0, &s2));
+        xs.push(Intersection::new(0.0, c.left()));
+        xs.push(Intersection::new(1.0, c.right()));
+        xs.push(Intersection::new(2.0, c.left()));
+        xs.push(Intersection::new(3.0, c.right()));
         let result = c.filter_intersections(&xs);
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].t, x0);
         assert_eq!(result[1].t, x1);
     }
+
+    #[test]
+    fn filtering_tells_equal_operands_apart() {
+        let s = ObjectBuilder::new_sphere().build();
+        let c = CSG::new(CSGKind::Difference, s.clone(), s);
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(0.0, c.left()));
+        xs.push(Intersection::new(1.0, c.right()));
+        xs.push(Intersection::new(2.0, c.left()));
+        xs.push(Intersection::new(3.0, c.right()));
+        let result = c.filter_intersections(&xs);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].t, 0.0);
+        assert_eq!(result[1].t, 1.0);
+    }
+
+    #[test]
+    fn the_hit_of_a_difference_skips_culled_surfaces() {
+        let sphere = ObjectBuilder::new_sphere().build();
+        let cube = ObjectBuilder::new_cube()
+            .with_transform(Transformation::new_transform().translation(0.0, 0.0, -1.0))
+            .build();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        let naive = sphere.intersects(&r).merge_sorted(cube.intersects(&r));
+        assert_eq!(naive.hit().unwrap().t, 3.0);
+
+        let c = ObjectBuilder::new_csg(CSGKind::Difference, sphere, cube).build();
+        let xs = c.intersects(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs.hit().unwrap().t, 5.0);
+        assert_eq!(xs[1].t, 6.0);
+    }
 }
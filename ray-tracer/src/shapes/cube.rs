@@ -3,6 +3,7 @@ use approx_eq::ApproxEq;
 use crate::{
     bounds::Bounds,
     intersections::{Intersection, Intersections},
+    patterns::cube_map_pattern::CubeFace,
     rays::Ray,
     tuples::{points::Point, vectors::Vector, Tuple},
 };
@@ -66,13 +67,19 @@ impl Cube {
     pub fn bounds(&self) -> Bounds {
         Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
     }
+
+    pub fn uv_at(&self, object_point: Point) -> (CubeFace, f64, f64) {
+        let face = CubeFace::from_point(object_point);
+        let (u, v) = face.uv(object_point);
+        (face, u, v)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use yare::parameterized;
 
-    use crate::shapes::ObjectBuilder;
+    use crate::{shapes::ObjectBuilder, transformations::Transformation};
 
     use super::*;
 
@@ -131,4 +138,40 @@ mod tests {
         assert_eq!(b.min(), &Point::new(-1.0, -1.0, -1.0));
         assert_eq!(b.max(), &Point::new(1.0, 1.0, 1.0));
     }
+
+    #[parameterized(
+        front_upper_left = {Point::new(-0.5, 0.5, 1.0), CubeFace::Front, 0.25, 0.75},
+        front_lower_right = {Point::new(0.5, -0.5, 1.0), CubeFace::Front, 0.75, 0.25},
+        back_upper_left = {Point::new(0.5, 0.5, -1.0), CubeFace::Back, 0.25, 0.75},
+        back_lower_right = {Point::new(-0.5, -0.5, -1.0), CubeFace::Back, 0.75, 0.25},
+        left_upper_left = {Point::new(-1.0, 0.5, -0.5), CubeFace::Left, 0.25, 0.75},
+        left_lower_right = {Point::new(-1.0, -0.5, 0.5), CubeFace::Left, 0.75, 0.25},
+        right_upper_left = {Point::new(1.0, 0.5, 0.5), CubeFace::Right, 0.25, 0.75},
+        right_lower_right = {Point::new(1.0, -0.5, -0.5), CubeFace::Right, 0.75, 0.25},
+        up_upper_left = {Point::new(-0.5, 1.0, -0.5), CubeFace::Up, 0.25, 0.75},
+        up_lower_right = {Point::new(0.5, 1.0, 0.5), CubeFace::Up, 0.75, 0.25},
+        down_upper_left = {Point::new(-0.5, -1.0, 0.5), CubeFace::Down, 0.25, 0.75},
+        down_lower_right = {Point::new(0.5, -1.0, -0.5), CubeFace::Down, 0.75, 0.25},
+    )]
+    fn uv_mapping_a_point_on_a_cube(p: Point, face: CubeFace, u: f64, v: f64) {
+        let (actual_face, actual_u, actual_v) = Cube::default().uv_at(p);
+        assert_eq!(actual_face, face);
+        assert!(actual_u.approx_eq(u));
+        assert!(actual_v.approx_eq(v));
+    }
+
+    #[test]
+    fn face_uv_on_a_transformed_cube_object() {
+        let cube = ObjectBuilder::new_cube()
+            .with_transform(Transformation::new_transform().scaling(2.0, 2.0, 2.0))
+            .build();
+        let (face, u, v) = cube.face_uv_at(Point::new(-1.0, 1.0, 2.0)).unwrap();
+        assert_eq!(face, CubeFace::Front);
+        assert!(u.approx_eq(0.25));
+        assert!(v.approx_eq(0.75));
+        assert!(ObjectBuilder::new_sphere()
+            .build()
+            .face_uv_at(Point::new(0.0, 0.0, -1.0))
+            .is_none());
+    }
 }
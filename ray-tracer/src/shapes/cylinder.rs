@@ -5,6 +5,7 @@ use approx_eq::{ApproxEq, EPSILON};
 use crate::{
     bounds::Bounds,
     intersections::{Intersection, Intersections},
+    patterns::uv_checker_pattern::UvMapping,
     rays::Ray,
     tuples::{points::Point, vectors::Vector, Tuple},
 };
@@ -41,6 +42,18 @@ impl Cylinder {
         self.cap = cap;
     }
 
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    pub fn cap(&self) -> &Cap {
+        &self.cap
+    }
+
     fn check_cap(r: &Ray, t: f64) -> bool {
         let x = r.origin.x() + t * r.direction.x();
         let z = r.origin.z() + t * r.direction.z();
@@ -65,6 +78,10 @@ impl Cylinder {
         }
     }
 
+    pub fn uv_at(&self, object_point: Point) -> (f64, f64) {
+        UvMapping::Cylindrical.map(object_point)
+    }
+
     pub fn normal_at(&self, object_point: Point) -> Vector {
         let dist = object_point.x().powi(2) + object_point.z().powi(2);
 
@@ -111,6 +128,7 @@ impl Cylinder {
             }
         };
         self.intersects_caps(object, r, &mut intersections);
+        intersections.sort_by(|i1, i2| i1.t.total_cmp(&i2.t));
         intersections
     }
 
@@ -0,0 +1,109 @@
+use approx_eq::EPSILON;
+
+use crate::{
+    bounds::Bounds,
+    intersections::{Intersection, Intersections},
+    rays::Ray,
+    tuples::{points::Point, vectors::Vector, Tuple},
+};
+
+use super::Object;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disk {
+    inner_radius: f64,
+    outer_radius: f64,
+}
+
+impl Default for Disk {
+    fn default() -> Self {
+        Self {
+            inner_radius: 0.0,
+            outer_radius: 1.0,
+        }
+    }
+}
+
+impl Disk {
+    pub fn with_radii(&mut self, inner_radius: f64, outer_radius: f64) {
+        self.inner_radius = inner_radius;
+        self.outer_radius = outer_radius;
+    }
+
+    pub fn normal_at(&self, _object_point: Point) -> Vector {
+        Vector::y_norm()
+    }
+
+    pub fn intersects<'a>(&self, object: &'a Object, ray: &Ray) -> Intersections<'a> {
+        let mut intersections = Intersections::new();
+        if ray.direction.y().abs() > EPSILON {
+            let t = -ray.origin.y() / ray.direction.y();
+            let x = ray.origin.x() + t * ray.direction.x();
+            let z = ray.origin.z() + t * ray.direction.z();
+            let distance = x.powi(2) + z.powi(2);
+            if self.inner_radius.powi(2) <= distance && distance <= self.outer_radius.powi(2) {
+                intersections.push(Intersection::new(t, object));
+            }
+        }
+        intersections
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point::new(-self.outer_radius, 0.0, -self.outer_radius),
+            Point::new(self.outer_radius, 0.0, self.outer_radius),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shapes::ObjectBuilder;
+
+    use super::*;
+
+    #[test]
+    fn the_normal_of_a_disk_is_constant_everywhere() {
+        let disk = Disk::default();
+        assert_eq!(disk.normal_at(Point::zero()), Vector::y_norm());
+        assert_eq!(disk.normal_at(Point::new(0.5, 0.0, -0.5)), Vector::y_norm());
+    }
+
+    #[test]
+    fn a_ray_hits_the_ring_of_a_disk() {
+        let disk = ObjectBuilder::new_disk().with_radii(0.5, 1.0).build();
+        let r = Ray::new(Point::new(0.75, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let xs = disk.intersects(&r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 1.0);
+    }
+
+    #[test]
+    fn a_ray_passes_through_the_hole_of_a_disk() {
+        let disk = ObjectBuilder::new_disk().with_radii(0.5, 1.0).build();
+        let r = Ray::new(Point::new(0.25, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        assert!(disk.intersects(&r).is_empty());
+    }
+
+    #[test]
+    fn a_ray_misses_the_outside_of_a_disk() {
+        let disk = ObjectBuilder::new_disk().build();
+        let r = Ray::new(Point::new(1.5, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        assert!(disk.intersects(&r).is_empty());
+    }
+
+    #[test]
+    fn a_ray_parallel_to_a_disk_misses() {
+        let disk = ObjectBuilder::new_disk().build();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        assert!(disk.intersects(&r).is_empty());
+    }
+
+    #[test]
+    fn a_disk_has_a_bounding_box() {
+        let disk = Disk::default();
+        let b = disk.bounds();
+        assert_eq!(b.min(), &Point::new(-1.0, 0.0, -1.0));
+        assert_eq!(b.max(), &Point::new(1.0, 0.0, 1.0));
+    }
+}
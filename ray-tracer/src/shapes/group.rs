@@ -9,8 +9,26 @@ use crate::{
 
 use super::{Object, ObjectBuilder};
 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DivideStats {
+    pub depth: usize,
+    pub groups_created: usize,
+    pub leaves: usize,
+}
+
+impl DivideStats {
+    pub fn leaf() -> Self {
+        Self {
+            depth: 0,
+            groups_created: 0,
+            leaves: 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Group {
+    name: Option<String>,
     children: Vec<Object>,
     bounds: Bounds,
 }
@@ -18,6 +36,7 @@ pub struct Group {
 impl Default for Group {
     fn default() -> Self {
         Self {
+            name: None,
             children: Vec::new(),
             bounds: Bounds::default(),
         }
@@ -25,6 +44,14 @@ impl Default for Group {
 }
 
 impl Group {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = Some(name);
+    }
+
     pub fn add_child(&mut self, object: Object) {
         self.children.push(object);
     }
@@ -45,10 +72,9 @@ impl Group {
         let mut xs = Intersections::new();
         if self.bounds().intersects(&ray) {
             for child in self.children.iter() {
-                let child_xs = child.intersects(&ray);
-                child_xs.iter().for_each(|i| xs.push(*i));
+                xs.push_all(child.intersects(&ray));
             }
-            xs.sort_unstable_by(|a, b| a.t.total_cmp(&b.t));
+            xs.sort_by(|i1, i2| i1.t.total_cmp(&i2.t));
         }
         xs
     }
@@ -99,18 +125,30 @@ impl Group {
     }
 
     pub fn divide(&mut self, threshold: usize) {
+        self.divide_with_stats(threshold);
+    }
+
+    pub fn divide_with_stats(&mut self, threshold: usize) -> DivideStats {
+        let mut stats = DivideStats::default();
         if threshold <= self.children.len() {
             let (left, right) = self.partition_children();
             if !left.is_empty() {
                 self.make_subgroup(left);
+                stats.groups_created += 1;
             }
             if !right.is_empty() {
                 self.make_subgroup(right);
+                stats.groups_created += 1;
             }
         }
         for child in &mut self.children {
-            child.divide(threshold);
+            let child_stats = child.divide_with_stats(threshold);
+            stats.depth = stats.depth.max(child_stats.depth);
+            stats.groups_created += child_stats.groups_created;
+            stats.leaves += child_stats.leaves;
         }
+        stats.depth += 1;
+        stats
     }
 }
 
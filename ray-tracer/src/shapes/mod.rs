@@ -2,37 +2,50 @@ mod cone;
 mod csg;
 pub mod cube;
 pub mod cylinder;
+mod disk;
 mod group;
+mod paraboloid;
 mod plane;
+mod rectangle;
 mod smooth_triangle;
 mod sphere;
 mod test_shape;
+mod torus;
 mod triangle;
 
 use std::marker::PhantomData;
 
+use anyhow::{anyhow, Result};
+
 pub use cone::Cone;
 pub use csg::CSGKind;
 use csg::CSG;
 use cube::Cube;
 pub use cylinder::Cylinder;
+use disk::Disk;
+pub use group::DivideStats;
 use group::Group;
+use paraboloid::Paraboloid;
 use plane::Plane;
+use rectangle::Rectangle;
 use smooth_triangle::SmoothTriangle;
 use sphere::Sphere;
 use state::{
-    InnerMarker, ShapeMarker, WithCSG, WithCone, WithCube, WithCylinder, WithPlane,
-    WithSmoothTriangle, WithSphere, WithTestShape, WithTriangle,
+    InnerMarker, ShapeMarker, WithCSG, WithCone, WithCube, WithCylinder, WithDisk, WithParaboloid,
+    WithPlane, WithRectangle, WithSmoothTriangle, WithSphere, WithTestShape, WithTorus,
+    WithTriangle,
 };
 pub use state::{WithGroup, WithShape};
 use test_shape::TestShape;
+use torus::Torus;
 use triangle::Triangle;
 
 use crate::bounds::Bounds;
 use crate::intersections::Intersection;
 use crate::tuples::{points::Point, vectors::Vector};
 use crate::{
-    intersections::Intersections, materials::Material, rays::Ray, transformations::Transformation,
+    intersections::Intersections, materials::Material, patterns::cube_map_pattern::CubeFace,
+    rays::Ray, transformations::Transformation,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -47,6 +60,10 @@ pub enum Shape {
     Triangle(Triangle),
     SmoothTriangle(SmoothTriangle),
     CSG(CSG),
+    Torus(Torus),
+    Disk(Disk),
+    Rectangle(Rectangle),
+    Paraboloid(Paraboloid),
 }
 
 impl Shape {
@@ -62,6 +79,10 @@ impl Shape {
             Shape::Triangle(s) => s.bounds(),
             Shape::SmoothTriangle(s) => s.bounds(),
             Shape::CSG(s) => s.bounds(),
+            Shape::Torus(s) => s.bounds(),
+            Shape::Disk(s) => s.bounds(),
+            Shape::Rectangle(s) => s.bounds(),
+            Shape::Paraboloid(s) => s.bounds(),
         }
     }
 
@@ -77,6 +98,10 @@ impl Shape {
             Shape::Triangle(s) => s.normal_at(local_point),
             Shape::SmoothTriangle(s) => s.normal_at(local_point, hit),
             Shape::CSG(s) => s.normal_at(local_point),
+            Shape::Torus(s) => s.normal_at(local_point),
+            Shape::Disk(s) => s.normal_at(local_point),
+            Shape::Rectangle(s) => s.normal_at(local_point),
+            Shape::Paraboloid(s) => s.normal_at(local_point),
         }
     }
 
@@ -92,6 +117,10 @@ impl Shape {
             Shape::Triangle(s) => s.intersects(object, ray),
             Shape::SmoothTriangle(s) => s.intersects(object, ray),
             Shape::CSG(s) => s.intersects(object, ray),
+            Shape::Torus(s) => s.intersects(object, ray),
+            Shape::Disk(s) => s.intersects(object, ray),
+            Shape::Rectangle(s) => s.intersects(object, ray),
+            Shape::Paraboloid(s) => s.intersects(object, ray),
         }
     }
 
@@ -123,10 +152,19 @@ impl Shape {
         }
     }
 
-    fn divide(&mut self, threshold: usize) {
+    fn divide_with_stats(&mut self, threshold: usize) -> DivideStats {
         match self {
-            Shape::Group(g) => g.divide(threshold),
-            _ => (),
+            Shape::Group(g) => g.divide_with_stats(threshold),
+            _ => DivideStats::leaf(),
+        }
+    }
+
+    fn uv_at(&self, local_point: Point) -> Option<(f64, f64)> {
+        match self {
+            Shape::Sphere(s) => Some(s.uv_at(local_point)),
+            Shape::Plane(s) => Some(s.uv_at(local_point)),
+            Shape::Cylinder(s) => Some(s.uv_at(local_point)),
+            _ => None,
         }
     }
 }
@@ -196,6 +234,7 @@ trait WithVertexNormals {
     fn n1(&self) -> Option<Vector>;
     fn n2(&self) -> Option<Vector>;
     fn n3(&self) -> Option<Vector>;
+    fn uvs(&self) -> Option<[(f64, f64); 3]>;
 }
 
 impl WithVertexNormals for Shape {
@@ -219,6 +258,13 @@ impl WithVertexNormals for Shape {
             _ => None,
         }
     }
+
+    fn uvs(&self) -> Option<[(f64, f64); 3]> {
+        match self {
+            Shape::SmoothTriangle(s) => s.uvs(),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -242,6 +288,10 @@ mod state {
     pub enum WithTriangle {}
     pub enum WithSmoothTriangle {}
     pub enum WithCSG {}
+    pub enum WithTorus {}
+    pub enum WithDisk {}
+    pub enum WithRectangle {}
+    pub enum WithParaboloid {}
 
     pub trait ShapeMarker {}
     impl ShapeMarker for () {}
@@ -259,11 +309,16 @@ mod state {
     impl InnerMarker for WithTriangle {}
     impl InnerMarker for WithSmoothTriangle {}
     impl InnerMarker for WithCSG {}
+    impl InnerMarker for WithTorus {}
+    impl InnerMarker for WithDisk {}
+    impl InnerMarker for WithRectangle {}
+    impl InnerMarker for WithParaboloid {}
 }
 
 pub enum Cappable {
     WithCone(WithCone),
     WithCylinder(WithCylinder),
+    WithParaboloid(WithParaboloid),
 }
 
 impl From<WithCone> for Cappable {
@@ -278,6 +333,12 @@ impl From<WithCylinder> for Cappable {
     }
 }
 
+impl From<WithParaboloid> for Cappable {
+    fn from(with_paraboloid: WithParaboloid) -> Self {
+        Self::WithParaboloid(with_paraboloid)
+    }
+}
+
 pub enum CanSetVertices {
     WithTriangle(WithTriangle),
     WithSmoothTriangle(WithSmoothTriangle),
@@ -436,6 +497,42 @@ impl ObjectBuilder<(), ()> {
         }
     }
 
+    pub fn new_torus() -> ObjectBuilder<WithShape, WithTorus> {
+        ObjectBuilder {
+            shape: Some(Shape::Torus(Torus::default())),
+            _shape: PhantomData,
+            _inner: PhantomData,
+            ..Default::default()
+        }
+    }
+
+    pub fn new_disk() -> ObjectBuilder<WithShape, WithDisk> {
+        ObjectBuilder {
+            shape: Some(Shape::Disk(Disk::default())),
+            _shape: PhantomData,
+            _inner: PhantomData,
+            ..Default::default()
+        }
+    }
+
+    pub fn new_rectangle() -> ObjectBuilder<WithShape, WithRectangle> {
+        ObjectBuilder {
+            shape: Some(Shape::Rectangle(Rectangle::default())),
+            _shape: PhantomData,
+            _inner: PhantomData,
+            ..Default::default()
+        }
+    }
+
+    pub fn new_paraboloid() -> ObjectBuilder<WithShape, WithParaboloid> {
+        ObjectBuilder {
+            shape: Some(Shape::Paraboloid(Paraboloid::default())),
+            _shape: PhantomData,
+            _inner: PhantomData,
+            ..Default::default()
+        }
+    }
+
     pub fn new_group() -> ObjectBuilder<WithShape, WithGroup> {
         ObjectBuilder {
             shape: Some(Shape::Group(Group::default())),
@@ -475,6 +572,25 @@ impl ObjectBuilder<(), ()> {
             ..Default::default()
         }
     }
+
+    pub fn new_csg_many(
+        kind: csg::CSGKind,
+        children: Vec<Object>,
+    ) -> Result<ObjectBuilder<WithShape, WithCSG>> {
+        if children.len() < 2 {
+            return Err(anyhow!(
+                "A CSG needs at least two children, got {}",
+                children.len()
+            ));
+        }
+        let mut children = children.into_iter();
+        let first = children.next().unwrap();
+        let second = children.next().unwrap();
+        let builder = Self::new_csg(kind.clone(), first, second);
+        Ok(children.fold(builder, |builder, child| {
+            Self::new_csg(kind.clone(), builder.build(), child)
+        }))
+    }
 }
 
 impl ObjectBuilder<WithShape, WithSphere> {
@@ -492,6 +608,7 @@ impl<C: InnerMarker + Into<Cappable>> ObjectBuilder<WithShape, C> {
         match shape {
             Shape::Cylinder(ref mut s) => s.with_min(min),
             Shape::Cone(ref mut s) => s.with_min(min),
+            Shape::Paraboloid(ref mut s) => s.with_min(min),
             _ => unreachable!(),
         };
         self.shape = Some(shape);
@@ -503,6 +620,7 @@ impl<C: InnerMarker + Into<Cappable>> ObjectBuilder<WithShape, C> {
         match shape {
             Shape::Cylinder(ref mut s) => s.with_max(max),
             Shape::Cone(ref mut s) => s.with_max(max),
+            Shape::Paraboloid(ref mut s) => s.with_max(max),
             _ => unreachable!(),
         };
         self.shape = Some(shape);
@@ -514,6 +632,59 @@ impl<C: InnerMarker + Into<Cappable>> ObjectBuilder<WithShape, C> {
         match shape {
             Shape::Cylinder(ref mut s) => s.with_cap(cap),
             Shape::Cone(ref mut s) => s.with_cap(cap),
+            Shape::Paraboloid(ref mut s) => s.with_cap(cap),
+            _ => unreachable!(),
+        };
+        self.shape = Some(shape);
+        self
+    }
+
+    pub fn with_extent(self, min: f64, max: f64, cap: Cap) -> Self {
+        self.with_min(min).with_max(max).with_cap(cap)
+    }
+}
+
+impl ObjectBuilder<WithShape, WithCone> {
+    pub fn with_half_angle(mut self, angle: f64) -> Self {
+        let mut shape = self.shape.unwrap();
+        match shape {
+            Shape::Cone(ref mut s) => s.with_half_angle(angle),
+            _ => unreachable!(),
+        };
+        self.shape = Some(shape);
+        self
+    }
+}
+
+impl ObjectBuilder<WithShape, WithPlane> {
+    pub fn with_extent(mut self, half_x: f64, half_z: f64) -> Self {
+        let mut shape = self.shape.unwrap();
+        match shape {
+            Shape::Plane(ref mut s) => s.with_extent(half_x, half_z),
+            _ => unreachable!(),
+        };
+        self.shape = Some(shape);
+        self
+    }
+}
+
+impl ObjectBuilder<WithShape, WithTorus> {
+    pub fn with_radii(mut self, major_radius: f64, minor_radius: f64) -> Self {
+        let mut shape = self.shape.unwrap();
+        match shape {
+            Shape::Torus(ref mut s) => s.with_radii(major_radius, minor_radius),
+            _ => unreachable!(),
+        };
+        self.shape = Some(shape);
+        self
+    }
+}
+
+impl ObjectBuilder<WithShape, WithDisk> {
+    pub fn with_radii(mut self, inner_radius: f64, outer_radius: f64) -> Self {
+        let mut shape = self.shape.unwrap();
+        match shape {
+            Shape::Disk(ref mut s) => s.with_radii(inner_radius, outer_radius),
             _ => unreachable!(),
         };
         self.shape = Some(shape);
@@ -522,6 +693,16 @@ impl<C: InnerMarker + Into<Cappable>> ObjectBuilder<WithShape, C> {
 }
 
 impl ObjectBuilder<WithShape, WithGroup> {
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        let mut shape = self.shape.unwrap();
+        match shape {
+            Shape::Group(ref mut s) => s.set_name(name.into()),
+            _ => unreachable!(),
+        };
+        self.shape = Some(shape);
+        self
+    }
+
     pub fn add_child(mut self, child: Object) -> Self {
         let mut shape = self.shape.unwrap();
         match shape {
@@ -531,6 +712,20 @@ impl ObjectBuilder<WithShape, WithGroup> {
         self.shape = Some(shape);
         self
     }
+
+    pub fn with_inherited_material(mut self, material: Material, force: bool) -> Self {
+        let mut shape = self.shape.unwrap();
+        match shape {
+            Shape::Group(ref mut g) => g
+                .children_mut()
+                .iter_mut()
+                .for_each(|child| child.inherit_material(&material, force)),
+            _ => unreachable!(),
+        };
+        self.material = material;
+        self.shape = Some(shape);
+        self
+    }
 }
 
 impl<I: InnerMarker + Into<CanSetVertices>> ObjectBuilder<WithShape, I> {
@@ -598,6 +793,16 @@ impl<I: InnerMarker + Into<CanSetVertexNormals>> ObjectBuilder<WithShape, I> {
         self.shape = Some(shape);
         self
     }
+
+    pub fn set_uvs(mut self, uvs: [(f64, f64); 3]) -> Self {
+        let mut shape = self.shape.unwrap();
+        match shape {
+            Shape::SmoothTriangle(ref mut s) => s.set_uvs(uvs),
+            _ => unreachable!(),
+        };
+        self.shape = Some(shape);
+        self
+    }
 }
 
 impl Object {
@@ -623,14 +828,13 @@ impl Object {
     }
 
     pub fn intersects(&self, r: &Ray) -> Intersections {
-        let r = if !matches!(self.shape, Shape::Group(_)) {
-            &r.transform(self.transform.inverse().unwrap())
-        } else if !matches!(self.shape, Shape::CSG(_)) {
-            &r.transform(self.transform.inverse().unwrap())
-        } else {
-            r
-        };
-        self.shape.intersects(self, r)
+        match self.shape {
+            // Group transforms are baked into the children, so the ray is already local.
+            Shape::Group(_) => self.shape.intersects(self, r),
+            _ => self
+                .shape
+                .intersects(self, &r.transform(self.transform.inverse().unwrap())),
+        }
     }
 
     pub fn to_object_space(&self, world_point: &Point) -> Option<Point> {
@@ -657,10 +861,30 @@ impl Object {
         };
     }
 
+    pub fn inherit_material(&mut self, material: &Material, force: bool) {
+        match self.shape {
+            Shape::Group(ref mut g) => {
+                self.material = material.clone();
+                g.children_mut()
+                    .iter_mut()
+                    .for_each(|child| child.inherit_material(material, force));
+            }
+            _ => {
+                if force || self.material == Material::new() {
+                    self.material = material.clone();
+                }
+            }
+        };
+    }
+
     pub fn material_mut(&mut self) -> &mut Material {
         &mut self.material
     }
 
+    pub fn object_to_world(&self, point: Point) -> Point {
+        &self.transform.matrix * &point
+    }
+
     pub fn world_to_object(&self, point: Point) -> Point {
         self.transform.inverse().unwrap() * &point
     }
@@ -674,10 +898,52 @@ impl Object {
         &self.shape
     }
 
+    pub fn transform(&self) -> &Transformation {
+        &self.transform
+    }
+
+    pub fn uv_at(&self, world_point: Point) -> Option<(f64, f64)> {
+        self.shape.uv_at(self.world_to_object(world_point))
+    }
+
+    pub fn face_uv_at(&self, world_point: Point) -> Option<(CubeFace, f64, f64)> {
+        match &self.shape {
+            Shape::Cube(c) => Some(c.uv_at(self.world_to_object(world_point))),
+            _ => None,
+        }
+    }
+
     pub fn bounds(&self) -> &Bounds {
         &self.bounds
     }
 
+    pub fn world_bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    pub fn total_bounds(&self) -> Bounds {
+        match &self.shape {
+            Shape::Group(g) => g
+                .children()
+                .iter()
+                .fold(Bounds::default(), |bounds, child| {
+                    bounds + child.total_bounds()
+                }),
+            _ => self.world_bounds(),
+        }
+    }
+
+    /// The centre and radius of a sphere enclosing the object's bounds, or
+    /// `None` for unbounded shapes such as planes and open cylinders.
+    pub fn bounding_sphere(&self) -> Option<(Point, f64)> {
+        if !self.bounds.is_finite() {
+            return None;
+        }
+        let (min, max) = (*self.bounds.min(), *self.bounds.max());
+        let half_diagonal = (max - min) / 2.0;
+        Some((min + half_diagonal, half_diagonal.magnitude()))
+    }
+
     pub fn normal(&self) -> Option<Vector> {
         self.shape.normal()
     }
@@ -714,6 +980,20 @@ impl Object {
         self.shape.n3()
     }
 
+    pub fn uvs(&self) -> Option<[(f64, f64); 3]> {
+        self.shape.uvs()
+    }
+
+    /// Turns the (u, v) carried by an intersection into texture coordinates.
+    /// Smooth triangles carry barycentrics, which only become texture
+    /// coordinates through their vertex UVs.
+    pub fn texture_uv(&self, u: f64, v: f64) -> Option<(f64, f64)> {
+        match &self.shape {
+            Shape::SmoothTriangle(s) => s.texture_uv(u, v),
+            _ => Some((u, v)),
+        }
+    }
+
     pub fn group_mut(&mut self) -> Option<&mut Group> {
         self.shape.group_mut()
     }
@@ -722,19 +1002,46 @@ impl Object {
         self.shape.group()
     }
 
+    pub fn child_count(&self) -> usize {
+        self.children().len()
+    }
+
+    pub fn children(&self) -> &[Object] {
+        match self.shape.group() {
+            Some(g) => g.children(),
+            None => &[],
+        }
+    }
+
+    pub fn iter_leaves(&self) -> Box<dyn Iterator<Item = &Object> + '_> {
+        match self.shape {
+            Shape::Group(ref g) => Box::new(g.children().iter().flat_map(|c| c.iter_leaves())),
+            _ => Box::new(std::iter::once(self)),
+        }
+    }
+
     pub fn remove_child(&mut self, child: &Object) {
         self.shape.remove_child(child);
     }
 
     pub fn divide(&mut self, threshold: usize) {
-        self.shape.divide(threshold);
+        self.divide_with_stats(threshold);
+    }
+
+    pub fn divide_with_stats(&mut self, threshold: usize) -> DivideStats {
+        self.shape.divide_with_stats(threshold)
     }
 
-    fn includes(&self, object: &Object) -> bool {
+    // Matches by address: an equal copy of a child is not part of this tree,
+    // and equal operands of a CSG must still be told apart.
+    pub fn csg_includes(&self, other: &Object) -> bool {
+        if std::ptr::eq(self, other) {
+            return true;
+        }
         match self.shape() {
-            Shape::Group(g) => g.children().iter().any(|child| child.includes(object)),
-            Shape::CSG(csg) => csg.left() == object || csg.right() == object,
-            _ => self == object,
+            Shape::Group(g) => g.children().iter().any(|child| child.csg_includes(other)),
+            Shape::CSG(csg) => csg.left().csg_includes(other) || csg.right().csg_includes(other),
+            _ => false,
         }
     }
 }
@@ -744,6 +1051,7 @@ mod tests {
 
     use std::f64::consts::PI;
 
+    use approx_eq::ApproxEq;
     use csg::CSGKind;
 
     use crate::{matrix::Matrix, tuples::Tuple};
@@ -765,6 +1073,28 @@ mod tests {
         assert_eq!(s.transform, t);
     }
 
+    #[test]
+    fn setting_the_extent_of_cappable_shapes_at_once() {
+        let cylinder = ObjectBuilder::new_cylinder()
+            .with_extent(-1.0, 2.0, Cap::Both)
+            .build();
+        let expected = ObjectBuilder::new_cylinder()
+            .with_min(-1.0)
+            .with_max(2.0)
+            .with_cap(Cap::Both)
+            .build();
+        assert_eq!(cylinder, expected);
+        let cone = ObjectBuilder::new_cone()
+            .with_extent(-1.0, 2.0, Cap::Both)
+            .build();
+        let expected = ObjectBuilder::new_cone()
+            .with_min(-1.0)
+            .with_max(2.0)
+            .with_cap(Cap::Both)
+            .build();
+        assert_eq!(cone, expected);
+    }
+
     #[test]
     fn default_material() {
         let s = ObjectBuilder::new_test_shape().build();
@@ -775,8 +1105,7 @@ mod tests {
     #[test]
     fn assigning_a_material() {
         let mut s = ObjectBuilder::new_test_shape().build();
-        let mut m = Material::new();
-        m.ambient = 1.0;
+        let m = Material::new().with_ambient(1.0);
         s.material = m.clone();
         assert_eq!(s.material, m);
     }
@@ -824,6 +1153,174 @@ mod tests {
         assert!(g.group().unwrap().contains(&s));
     }
 
+    #[test]
+    fn children_of_a_group_inherit_its_material() {
+        let m = Material::new().with_ambient(1.0);
+        let g = ObjectBuilder::new_group()
+            .add_child(ObjectBuilder::new_sphere().build())
+            .add_child(ObjectBuilder::new_sphere().build())
+            .with_inherited_material(m.clone(), false)
+            .build();
+        assert!(g
+            .group()
+            .unwrap()
+            .children()
+            .iter()
+            .all(|child| child.material() == &m));
+    }
+
+    #[test]
+    fn inherited_materials_only_override_custom_ones_when_forced() {
+        let custom = Material::new().with_reflective(0.5);
+        let m = Material::new().with_ambient(1.0);
+        let build = |force| {
+            ObjectBuilder::new_group()
+                .add_child(ObjectBuilder::new_sphere().build())
+                .add_child(
+                    ObjectBuilder::new_group()
+                        .add_child(
+                            ObjectBuilder::new_sphere()
+                                .with_material(custom.clone())
+                                .build(),
+                        )
+                        .build(),
+                )
+                .with_inherited_material(m.clone(), force)
+                .build()
+        };
+        let nested_material = |g: &Object| {
+            g.group().unwrap().children()[1].group().unwrap().children()[0]
+                .material()
+                .clone()
+        };
+
+        let g = build(false);
+        assert_eq!(g.group().unwrap().children()[0].material(), &m);
+        assert_eq!(nested_material(&g), custom);
+
+        let g = build(true);
+        assert_eq!(g.group().unwrap().children()[0].material(), &m);
+        assert_eq!(nested_material(&g), m);
+    }
+
+    fn hexagon() -> Object {
+        let side = |n: usize| {
+            ObjectBuilder::new_group()
+                .add_child(
+                    ObjectBuilder::new_sphere()
+                        .with_transform(
+                            Transformation::new_transform()
+                                .scaling(0.25, 0.25, 0.25)
+                                .translation(0.0, 0.0, 1.0),
+                        )
+                        .build(),
+                )
+                .add_child(
+                    ObjectBuilder::new_cylinder()
+                        .with_min(0.0)
+                        .with_max(1.0)
+                        .with_transform(
+                            Transformation::new_transform()
+                                .scaling(0.25, 1.0, 0.25)
+                                .rotation_z(-PI / 2.0)
+                                .rotation_y(-PI / 6.0)
+                                .translation(0.0, 0.0, -1.0),
+                        )
+                        .build(),
+                )
+                .with_transform(Transformation::new_transform().rotation_y(n as f64 * PI / 3.0))
+                .build()
+        };
+        (0..6)
+            .fold(ObjectBuilder::new_group(), |hex, n| hex.add_child(side(n)))
+            .build()
+    }
+
+    #[test]
+    fn counting_the_children_and_leaves_of_a_nested_group() {
+        let hex = hexagon();
+        assert_eq!(hex.child_count(), 6);
+        assert_eq!(hex.children()[0].child_count(), 2);
+        assert_eq!(hex.iter_leaves().count(), 12);
+        assert!(hex
+            .iter_leaves()
+            .all(|leaf| !matches!(leaf.shape(), Shape::Group(_))));
+    }
+
+    #[test]
+    fn a_non_group_object_has_no_children_and_is_its_own_leaf() {
+        let s = ObjectBuilder::new_sphere().build();
+        assert_eq!(s.child_count(), 0);
+        assert!(s.children().is_empty());
+        assert_eq!(s.iter_leaves().collect::<Vec<_>>(), vec![&s]);
+    }
+
+    #[test]
+    fn an_object_includes_itself_and_its_descendants() {
+        let s1 = ObjectBuilder::new_sphere().build();
+        let s2 = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().translation(0.0, 2.0, 0.0))
+            .build();
+        let unrelated = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().translation(5.0, 0.0, 0.0))
+            .build();
+        let g = ObjectBuilder::new_group().add_child(s2.clone()).build();
+        let c = ObjectBuilder::new_csg(CSGKind::Difference, s1.clone(), g).build();
+        let Shape::CSG(csg) = c.shape() else {
+            unreachable!()
+        };
+        let (left, right) = (csg.left(), csg.right());
+        assert!(c.csg_includes(&c));
+        assert!(left.csg_includes(left));
+        assert!(right.csg_includes(&right.children()[0]));
+        assert!(c.csg_includes(left));
+        assert!(c.csg_includes(&right.children()[0]));
+        assert!(!left.csg_includes(right));
+        assert!(!c.csg_includes(&unrelated));
+    }
+
+    #[test]
+    fn an_equal_copy_is_not_included() {
+        let s1 = ObjectBuilder::new_sphere().build();
+        let g = ObjectBuilder::new_group().add_child(s1.clone()).build();
+        assert!(!g.csg_includes(&s1));
+        assert!(g.csg_includes(&g.children()[0]));
+    }
+
+    #[test]
+    fn a_csg_of_many_children_intersects_like_the_nested_form() {
+        let children: Vec<Object> = [-1.0, 0.0, 1.0]
+            .into_iter()
+            .map(|x| {
+                ObjectBuilder::new_sphere()
+                    .with_transform(Transformation::new_transform().translation(x, 0.0, 0.0))
+                    .build()
+            })
+            .collect();
+        let nested = ObjectBuilder::new_csg(
+            CSGKind::Union,
+            ObjectBuilder::new_csg(CSGKind::Union, children[0].clone(), children[1].clone())
+                .build(),
+            children[2].clone(),
+        )
+        .build();
+        let many = ObjectBuilder::new_csg_many(CSGKind::Union, children)
+            .unwrap()
+            .build();
+        assert_eq!(many, nested);
+
+        let r = Ray::new(Point::new(-5.0, 0.0, 0.0), Vector::x_norm());
+        let ts = |o: &Object| o.intersects(&r).iter().map(|i| i.t).collect::<Vec<_>>();
+        assert_eq!(ts(&many), ts(&nested));
+        assert_eq!(ts(&many), vec![3.0, 7.0]);
+    }
+
+    #[test]
+    fn a_csg_of_many_children_needs_at_least_two() {
+        let s = ObjectBuilder::new_sphere().build();
+        assert!(ObjectBuilder::new_csg_many(CSGKind::Union, vec![s]).is_err());
+    }
+
     #[test]
     fn intersecting_a_ray_with_an_empty_group() {
         let g = ObjectBuilder::new_group().build();
@@ -900,6 +1397,19 @@ mod tests {
         assert_eq!(xs.len(), 2);
     }
 
+    #[test]
+    fn a_group_bakes_its_transform_into_its_children() {
+        let g = ObjectBuilder::new_group()
+            .with_transform(Transformation::new_transform().scaling(2.0, 2.0, 2.0))
+            .add_child(ObjectBuilder::new_sphere().build())
+            .build();
+        assert_eq!(g.transform(), &Transformation::new_transform());
+        assert_eq!(
+            g.children()[0].transform(),
+            &Transformation::new_transform().scaling(2.0, 2.0, 2.0)
+        );
+    }
+
     #[test]
     fn converting_a_point_from_world_to_object_space() {
         let s = ObjectBuilder::new_sphere()
@@ -1027,6 +1537,75 @@ mod tests {
         assert_eq!(b.max(), &Point::new(1.5, -1.0, 9.0));
     }
 
+    #[test]
+    fn the_total_bounds_of_a_group_span_all_its_children() {
+        let sphere_at = |x: f64| {
+            ObjectBuilder::new_sphere()
+                .with_transform(Transformation::new_transform().translation(x, 0.0, 0.0))
+                .build()
+        };
+        let g = ObjectBuilder::new_group()
+            .add_child(sphere_at(-2.0))
+            .add_child(sphere_at(2.0))
+            .build();
+        let b = g.total_bounds();
+        assert_eq!(b.min(), &Point::new(-3.0, -1.0, -1.0));
+        assert_eq!(b.max(), &Point::new(3.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn the_total_bounds_of_a_nested_group_include_the_group_transforms() {
+        let inner = ObjectBuilder::new_group()
+            .add_child(ObjectBuilder::new_sphere().build())
+            .with_transform(Transformation::new_transform().translation(0.0, 5.0, 0.0))
+            .build();
+        let outer = ObjectBuilder::new_group()
+            .add_child(inner)
+            .add_child(ObjectBuilder::new_sphere().build())
+            .with_transform(Transformation::new_transform().scaling(2.0, 2.0, 2.0))
+            .build();
+        let b = outer.total_bounds();
+        assert_eq!(b.min(), &Point::new(-2.0, -2.0, -2.0));
+        assert_eq!(b.max(), &Point::new(2.0, 12.0, 2.0));
+        assert_eq!(
+            outer.children()[0].world_bounds(),
+            outer.children()[0].total_bounds()
+        );
+    }
+
+    #[test]
+    fn a_cube_is_enclosed_by_a_sphere_through_its_corners() {
+        let (center, radius) = ObjectBuilder::new_cube().build().bounding_sphere().unwrap();
+        assert_eq!(center, Point::zero());
+        assert!(radius.approx_eq(3.0_f64.sqrt()));
+    }
+
+    #[test]
+    fn an_unbounded_object_has_no_bounding_sphere() {
+        assert!(ObjectBuilder::new_plane()
+            .build()
+            .bounding_sphere()
+            .is_none());
+        assert!(ObjectBuilder::new_cylinder()
+            .build()
+            .bounding_sphere()
+            .is_none());
+    }
+
+    #[test]
+    fn the_bounding_sphere_follows_the_object_transform() {
+        let s = ObjectBuilder::new_sphere()
+            .with_transform(
+                Transformation::new_transform()
+                    .scaling(2.0, 2.0, 2.0)
+                    .translation(1.0, -3.0, 5.0),
+            )
+            .build();
+        let (center, radius) = s.bounding_sphere().unwrap();
+        assert_eq!(center, Point::new(1.0, -3.0, 5.0));
+        assert!(radius.approx_eq(2.0 * 3.0_f64.sqrt()));
+    }
+
     #[test]
     fn subdividing_a_primitive_does_nothing() {
         let mut s = ObjectBuilder::new_sphere().build();
@@ -1072,6 +1651,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn subdividing_reports_the_resulting_structure() {
+        let mut s = ObjectBuilder::new_sphere().build();
+        assert_eq!(s.divide_with_stats(1), DivideStats::leaf());
+
+        let s1 = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().translation(-2.0, -2.0, 0.0))
+            .build();
+        let s2 = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().translation(-2.0, 2.0, 0.0))
+            .build();
+        let s3 = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().scaling(4.0, 4.0, 4.0))
+            .build();
+        let mut g = ObjectBuilder::new_group()
+            .add_child(s1)
+            .add_child(s2)
+            .add_child(s3)
+            .build();
+        assert_eq!(
+            g.divide_with_stats(1),
+            DivideStats {
+                depth: 3,
+                groups_created: 3,
+                leaves: 3,
+            }
+        );
+    }
+
     #[test]
     fn subdividing_a_group_with_too_few_children() {
         let s1 = ObjectBuilder::new_sphere()
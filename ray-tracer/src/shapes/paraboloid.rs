@@ -0,0 +1,189 @@
+use approx_eq::{ApproxEq, EPSILON};
+
+use crate::{
+    bounds::Bounds,
+    intersections::{Intersection, Intersections},
+    rays::Ray,
+    tuples::{points::Point, vectors::Vector, Tuple},
+};
+
+use super::{Cap, Object};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paraboloid {
+    min: f64,
+    max: f64,
+    cap: Cap,
+}
+
+impl Default for Paraboloid {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: f64::INFINITY,
+            cap: Cap::Uncapped,
+        }
+    }
+}
+
+impl Paraboloid {
+    pub fn with_min(&mut self, min: f64) {
+        self.min = min;
+    }
+
+    pub fn with_max(&mut self, max: f64) {
+        self.max = max;
+    }
+
+    pub fn with_cap(&mut self, cap: Cap) {
+        self.cap = cap;
+    }
+
+    fn intersects_caps<'a>(&self, object: &'a Object, r: &Ray, xs: &mut Intersections<'a>) {
+        if self.cap == Cap::Uncapped || r.direction.y().approx_eq(0.0) {
+            return;
+        }
+        if self.cap == Cap::Both || self.cap == Cap::BottomCap {
+            let t = (self.min - r.origin.y()) / r.direction.y();
+            if Paraboloid::check_cap(r, t, self.min) {
+                xs.push(Intersection::new(t, object));
+            }
+        }
+        if self.cap == Cap::Both || self.cap == Cap::TopCap {
+            let t = (self.max - r.origin.y()) / r.direction.y();
+            if Paraboloid::check_cap(r, t, self.max) {
+                xs.push(Intersection::new(t, object));
+            }
+        }
+    }
+
+    fn check_cap(r: &Ray, t: f64, y: f64) -> bool {
+        let x = r.origin.x() + t * r.direction.x();
+        let z = r.origin.z() + t * r.direction.z();
+        x.powi(2) + z.powi(2) <= y
+    }
+
+    pub fn normal_at(&self, object_point: Point) -> Vector {
+        let dist = object_point.x().powi(2) + object_point.z().powi(2);
+
+        if (self.cap == Cap::Both || self.cap == Cap::TopCap)
+            && dist <= object_point.y()
+            && object_point.y() >= self.max - EPSILON
+        {
+            Vector::y_norm()
+        } else if (self.cap == Cap::Both || self.cap == Cap::BottomCap)
+            && dist <= object_point.y()
+            && object_point.y() <= self.min + EPSILON
+        {
+            Vector::y_norm() * -1.0
+        } else {
+            Vector::new(2.0 * object_point.x(), -1.0, 2.0 * object_point.z())
+        }
+    }
+
+    pub fn intersects<'a>(&self, object: &'a Object, r: &Ray) -> Intersections<'a> {
+        let a = r.direction.x().powi(2) + r.direction.z().powi(2);
+        let b = 2.0 * r.origin.x() * r.direction.x() + 2.0 * r.origin.z() * r.direction.z()
+            - r.direction.y();
+        let c = r.origin.x().powi(2) + r.origin.z().powi(2) - r.origin.y();
+        let candidates = if a.approx_eq(0.0) {
+            if b.approx_eq(0.0) {
+                vec![]
+            } else {
+                vec![-c / b]
+            }
+        } else {
+            let discriminant = b.powi(2) - 4.0 * a * c;
+            if discriminant < 0.0 {
+                vec![]
+            } else {
+                let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+                let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+                vec![t0, t1]
+            }
+        };
+        let mut intersections = Intersections::new();
+        for t in candidates {
+            let y = r.origin.y() + t * r.direction.y();
+            if self.min < y && y < self.max {
+                intersections.push(Intersection::new(t, object));
+            }
+        }
+        self.intersects_caps(object, r, &mut intersections);
+        intersections.sort_by(|i1, i2| i1.t.total_cmp(&i2.t));
+        intersections
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let limit = self.max.sqrt();
+        Bounds::new(
+            Point::new(-limit, self.min.max(0.0), -limit),
+            Point::new(limit, self.max, limit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shapes::ObjectBuilder;
+
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        across_the_wall = {Point::new(-5.0, 1.0, 0.0), Vector::x_norm(), vec![4.0, 6.0]},
+        up_the_axis = {Point::new(0.5, -5.0, 0.0), Vector::y_norm(), vec![5.25]},
+        oblique = {Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 1.0), vec![(11.0 - 21.0_f64.sqrt()) / 2.0, (11.0 + 21.0_f64.sqrt()) / 2.0]},
+        below_the_vertex = {Point::new(-5.0, -1.0, 0.0), Vector::x_norm(), vec![]},
+    )]
+    fn a_ray_strikes_a_paraboloid(origin: Point, direction: Vector, ts: Vec<f64>) {
+        let paraboloid = ObjectBuilder::new_paraboloid().build();
+        let r = Ray::new(origin, direction);
+        let xs = paraboloid.intersects(&r);
+        assert_eq!(xs.len(), ts.len());
+        for (i, t) in ts.into_iter().enumerate() {
+            assert!(xs[i].t.approx_eq(t));
+        }
+    }
+
+    #[parameterized(
+        through_both_caps = {Point::new(0.0, 5.0, 0.0), -Vector::y_norm(), vec![4.0, 4.75]},
+        through_top_cap_and_wall = {Point::new(0.75, 5.0, 0.0), -Vector::y_norm(), vec![4.0, 4.4375]},
+        across_the_wall = {Point::new(-5.0, 0.5, 0.0), Vector::x_norm(), vec![5.0 - 0.5_f64.sqrt(), 5.0 + 0.5_f64.sqrt()]},
+        outside_the_rim = {Point::new(1.5, 5.0, 0.0), -Vector::y_norm(), vec![]},
+    )]
+    fn intersecting_a_closed_paraboloid(origin: Point, direction: Vector, ts: Vec<f64>) {
+        let paraboloid = ObjectBuilder::new_paraboloid()
+            .with_extent(0.25, 1.0, Cap::Both)
+            .build();
+        let r = Ray::new(origin, direction);
+        let xs = paraboloid.intersects(&r);
+        assert_eq!(xs.len(), ts.len());
+        for (i, t) in ts.into_iter().enumerate() {
+            assert!(xs[i].t.approx_eq(t));
+        }
+    }
+
+    #[parameterized(
+        on_the_wall = {Point::new(0.8, 0.64, 0.0), Vector::new(1.6, -1.0, 0.0)},
+        at_the_vertex = {Point::new(0.0, 0.0, 0.0), Vector::new(0.0, -1.0, 0.0)},
+        on_the_top_cap = {Point::new(0.5, 1.0, 0.0), Vector::y_norm()},
+        on_the_bottom_cap = {Point::new(0.25, 0.25, 0.0), Vector::y_norm() * -1.0},
+    )]
+    fn normal_vector_on_a_paraboloid(point: Point, normal: Vector) {
+        let mut paraboloid = Paraboloid::default();
+        paraboloid.with_min(0.25);
+        paraboloid.with_max(1.0);
+        paraboloid.with_cap(Cap::Both);
+        assert_eq!(paraboloid.normal_at(point), normal);
+    }
+
+    #[test]
+    fn a_bounded_paraboloid_has_a_bounding_box() {
+        let mut s = Paraboloid::default();
+        s.with_max(4.0);
+        let b = s.bounds();
+        assert_eq!(b.min(), &Point::new(-2.0, 0.0, -2.0));
+        assert_eq!(b.max(), &Point::new(2.0, 4.0, 2.0));
+    }
+}
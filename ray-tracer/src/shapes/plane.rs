@@ -3,6 +3,7 @@ use approx_eq::EPSILON;
 use crate::{
     bounds::Bounds,
     intersections::{Intersection, Intersections},
+    patterns::uv_checker_pattern::UvMapping,
     rays::Ray,
     tuples::{points::Point, vectors::Vector, Tuple},
 };
@@ -10,32 +11,56 @@ use crate::{
 use super::Object;
 
 #[derive(Debug, Clone, PartialEq, Default)]
-pub struct Plane {}
+pub struct Plane {
+    extent: Option<(f64, f64)>,
+}
 
 impl Plane {
+    pub fn with_extent(&mut self, half_x: f64, half_z: f64) {
+        self.extent = Some((half_x, half_z));
+    }
+
+    pub fn extent(&self) -> Option<(f64, f64)> {
+        self.extent
+    }
+
     pub fn normal_at(&self, _object_point: Point) -> Vector {
         return Vector::y_norm();
     }
 
+    pub fn uv_at(&self, object_point: Point) -> (f64, f64) {
+        UvMapping::Planar.map(object_point)
+    }
+
     pub fn intersects<'a>(&self, object: &'a Object, ray: &Ray) -> Intersections<'a> {
         let mut intersections = Intersections::new();
         if ray.direction.y().abs() > EPSILON {
             let t = -ray.origin.y() / ray.direction.y();
-            intersections.push(Intersection::new(t, object));
+            let p = ray.position(t);
+            let inside = match self.extent {
+                Some((half_x, half_z)) => p.x().abs() <= half_x && p.z().abs() <= half_z,
+                None => true,
+            };
+            if inside {
+                intersections.push(Intersection::new(t, object));
+            }
         }
         intersections
     }
 
     pub fn bounds(&self) -> Bounds {
+        let (half_x, half_z) = self.extent.unwrap_or((f64::INFINITY, f64::INFINITY));
         Bounds::new(
-            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
-            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+            Point::new(-half_x, 0.0, -half_z),
+            Point::new(half_x, 0.0, half_z),
         )
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use yare::parameterized;
+
     use crate::{shapes::ObjectBuilder, tuples::Tuple};
 
     use super::*;
@@ -84,6 +109,34 @@ mod tests {
         assert_eq!(xs[0].t, 1.0);
     }
 
+    #[parameterized(
+        inside = {Point::new(1.5, 1.0, -0.5), 1},
+        on_the_edge = {Point::new(2.0, 1.0, 1.0), 1},
+        past_x = {Point::new(2.5, 1.0, 0.0), 0},
+        past_z = {Point::new(0.0, 1.0, -1.5), 0},
+    )]
+    fn a_finite_plane_only_intersects_within_its_extent(origin: Point, count: usize) {
+        let p = ObjectBuilder::new_plane().with_extent(2.0, 1.0).build();
+        let r = Ray::new(origin, -Vector::y_norm());
+        assert_eq!(p.intersects(&r).len(), count);
+    }
+
+    #[test]
+    fn an_infinite_plane_is_hit_everywhere() {
+        let p = ObjectBuilder::new_plane().build();
+        let r = Ray::new(Point::new(1e6, 1.0, -1e6), -Vector::y_norm());
+        assert_eq!(p.intersects(&r).len(), 1);
+    }
+
+    #[test]
+    fn a_finite_plane_has_a_finite_bounding_box() {
+        let mut plane = Plane::default();
+        plane.with_extent(2.0, 1.0);
+        let b = plane.bounds();
+        assert_eq!(b.min(), &Point::new(-2.0, 0.0, -1.0));
+        assert_eq!(b.max(), &Point::new(2.0, 0.0, 1.0));
+    }
+
     #[test]
     fn a_plane_has_a_bounding_box() {
         let s = Plane::default();
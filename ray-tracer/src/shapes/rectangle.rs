@@ -0,0 +1,83 @@
+use approx_eq::EPSILON;
+
+use crate::{
+    bounds::Bounds,
+    intersections::{Intersection, Intersections},
+    rays::Ray,
+    tuples::{points::Point, vectors::Vector, Tuple},
+};
+
+use super::Object;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Rectangle {}
+
+impl Rectangle {
+    pub fn normal_at(&self, _object_point: Point) -> Vector {
+        Vector::y_norm()
+    }
+
+    pub fn intersects<'a>(&self, object: &'a Object, ray: &Ray) -> Intersections<'a> {
+        let mut intersections = Intersections::new();
+        if ray.direction.y().abs() > EPSILON {
+            let t = -ray.origin.y() / ray.direction.y();
+            let x = ray.origin.x() + t * ray.direction.x();
+            let z = ray.origin.z() + t * ray.direction.z();
+            if x.abs() <= 1.0 && z.abs() <= 1.0 {
+                intersections.push(Intersection::new(t, object));
+            }
+        }
+        intersections
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(Point::new(-1.0, 0.0, -1.0), Point::new(1.0, 0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yare::parameterized;
+
+    use crate::shapes::ObjectBuilder;
+
+    use super::*;
+
+    #[test]
+    fn the_normal_of_a_rectangle_is_constant_everywhere() {
+        let rect = Rectangle::default();
+        assert_eq!(rect.normal_at(Point::zero()), Vector::y_norm());
+        assert_eq!(rect.normal_at(Point::new(1.0, 0.0, -1.0)), Vector::y_norm());
+    }
+
+    #[parameterized(
+        center = {Point::new(0.0, 1.0, 0.0), 1},
+        near_corner = {Point::new(0.99, 1.0, -0.99), 1},
+        on_edge = {Point::new(1.0, 1.0, 0.0), 1},
+        outside_x = {Point::new(1.5, 1.0, 0.0), 0},
+        outside_z = {Point::new(0.0, 1.0, -1.5), 0},
+    )]
+    fn a_ray_intersects_a_rectangle(origin: Point, count: usize) {
+        let rect = ObjectBuilder::new_rectangle().build();
+        let r = Ray::new(origin, Vector::new(0.0, -1.0, 0.0));
+        let xs = rect.intersects(&r);
+        assert_eq!(xs.len(), count);
+        if count > 0 {
+            assert_eq!(xs[0].t, 1.0);
+        }
+    }
+
+    #[test]
+    fn a_ray_parallel_to_a_rectangle_misses() {
+        let rect = ObjectBuilder::new_rectangle().build();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        assert!(rect.intersects(&r).is_empty());
+    }
+
+    #[test]
+    fn a_rectangle_has_a_bounding_box() {
+        let b = Rectangle::default().bounds();
+        assert_eq!(b.min(), &Point::new(-1.0, 0.0, -1.0));
+        assert_eq!(b.max(), &Point::new(1.0, 0.0, 1.0));
+    }
+}
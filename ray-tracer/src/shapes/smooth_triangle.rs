@@ -17,6 +17,7 @@ pub struct SmoothTriangle {
     n1: Vector,
     n2: Vector,
     n3: Vector,
+    uvs: Option<[(f64, f64); 3]>,
 }
 
 impl Default for SmoothTriangle {
@@ -34,15 +35,18 @@ impl Default for SmoothTriangle {
             n1,
             n2,
             n3,
+            uvs: None,
         }
     }
 }
 
 impl SmoothTriangle {
     pub fn normal_at(&self, _object_point: Point, hit: Intersection) -> Vector {
-        self.n2 * hit.u.unwrap()
-            + self.n3 * hit.v.unwrap()
-            + self.n1 * (1.0 - hit.u.unwrap() - hit.v.unwrap())
+        self.interpolated_normal(hit.u.unwrap(), hit.v.unwrap())
+    }
+
+    pub fn interpolated_normal(&self, u: f64, v: f64) -> Vector {
+        (self.n2 * u + self.n3 * v + self.n1 * (1.0 - u - v)).normalize()
     }
 
     pub fn intersects<'a>(&self, object: &'a Object, r: &Ray) -> Intersections<'a> {
@@ -128,6 +132,25 @@ impl SmoothTriangle {
         self.n3 = n3;
     }
 
+    pub fn uvs(&self) -> Option<[(f64, f64); 3]> {
+        self.uvs
+    }
+
+    /// Interpolates the vertex texture coordinates at the barycentric (u, v)
+    /// of a hit, or returns `None` when the triangle has none.
+    pub fn texture_uv(&self, u: f64, v: f64) -> Option<(f64, f64)> {
+        let [uv1, uv2, uv3] = self.uvs?;
+        let w = 1.0 - u - v;
+        Some((
+            uv1.0 * w + uv2.0 * u + uv3.0 * v,
+            uv1.1 * w + uv2.1 * u + uv3.1 * v,
+        ))
+    }
+
+    pub fn set_uvs(&mut self, uvs: [(f64, f64); 3]) {
+        self.uvs = Some(uvs);
+    }
+
     pub fn e1(&self) -> Vector {
         self.p2 - self.p1
     }
@@ -203,6 +226,12 @@ mod tests {
         }
     }
 
+    #[test]
+    fn interpolating_the_normal_without_an_intersection() {
+        let n = SmoothTriangle::default().interpolated_normal(0.45, 0.25);
+        assert_eq!(n, Vector::new(-0.5547, 0.83205, 0.0));
+    }
+
     #[test]
     fn preparing_the_normal_on_a_smooth_triangle() {
         let tri = ObjectBuilder::new_smooth_triangle().build();
@@ -213,6 +242,21 @@ mod tests {
         assert_eq!(comps.normal_v, Vector::new(-0.5547, 0.83205, 0.0));
     }
 
+    #[test]
+    fn preparing_the_texture_uv_on_a_smooth_triangle() {
+        let r = Ray::new(Point::new(-0.2, 0.3, -2.0), Vector::z_norm());
+        let tri = ObjectBuilder::new_smooth_triangle().build();
+        let xs = tri.intersects(&r);
+        assert_eq!(xs[0].prepare_computations(r, &xs).uv, None);
+        let tri = ObjectBuilder::new_smooth_triangle()
+            .set_uvs([(0.0, 0.0), (1.0, 0.0), (0.0, 0.5)])
+            .build();
+        let xs = tri.intersects(&r);
+        let (u, v) = xs[0].prepare_computations(r, &xs).uv.unwrap();
+        assert!(u.approx_eq(0.45));
+        assert!(v.approx_eq(0.125));
+    }
+
     #[test]
     fn a_smooth_triangle_has_a_bounding_box() {
         let mut s = SmoothTriangle::default();
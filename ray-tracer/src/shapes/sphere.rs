@@ -1,6 +1,7 @@
 use crate::{
     bounds::Bounds,
     intersections::{Intersection, Intersections},
+    patterns::uv_checker_pattern::UvMapping,
     rays::Ray,
     tuples::{points::Point, vectors::Vector, Tuple},
 };
@@ -23,8 +24,16 @@ impl Sphere {
             let mut xs = Intersections::new();
             let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
             let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
-            xs.push(Intersection::new(t1, object));
-            xs.push(Intersection::new(t2, object));
+            let textured = object.material().pattern().maps_spherically();
+            for t in [t1, t2] {
+                let i = Intersection::new(t, object);
+                xs.push(if textured {
+                    let (u, v) = self.uv_at(ray.position(t));
+                    i.with_uv(u, v)
+                } else {
+                    i
+                });
+            }
             xs
         }
     }
@@ -36,10 +45,25 @@ impl Sphere {
     pub fn normal_at(&self, object_point: Point) -> Vector {
         object_point - Point::zero()
     }
+
+    pub fn uv_at(&self, object_point: Point) -> (f64, f64) {
+        UvMapping::Spherical.map(object_point)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use approx_eq::ApproxEq;
+    use colo_rs::colors::Color;
+    use yare::parameterized;
+
+    use crate::{
+        materials::Material, patterns::Pattern, shapes::ObjectBuilder,
+        transformations::Transformation,
+    };
+
     use super::*;
 
     #[test]
@@ -87,6 +111,80 @@ mod tests {
         assert_eq!(n, n.normalize());
     }
 
+    #[parameterized(
+        front = {Point::new(0.0, 0.0, -1.0), 0.0, 0.5},
+        right = {Point::new(1.0, 0.0, 0.0), 0.25, 0.5},
+        back = {Point::new(0.0, 0.0, 1.0), 0.5, 0.5},
+        left = {Point::new(-1.0, 0.0, 0.0), 0.75, 0.5},
+        top = {Point::new(0.0, 1.0, 0.0), 0.5, 1.0},
+        bottom = {Point::new(0.0, -1.0, 0.0), 0.5, 0.0},
+        diagonal = {Point::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0), 0.25, 0.75},
+    )]
+    fn uv_coordinates_on_a_sphere(p: Point, u: f64, v: f64) {
+        let (actual_u, actual_v) = Sphere::default().uv_at(p);
+        assert!(actual_u.approx_eq(u));
+        assert!(actual_v.approx_eq(v));
+    }
+
+    #[test]
+    fn uv_coordinates_on_a_transformed_sphere_object() {
+        let object = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().scaling(2.0, 2.0, 2.0))
+            .build();
+        let (u, v) = object.uv_at(Point::new(2.0, 0.0, 0.0)).unwrap();
+        assert!(u.approx_eq(0.25));
+        assert!(v.approx_eq(0.5));
+        assert!(ObjectBuilder::new_cube()
+            .build()
+            .uv_at(Point::new(1.0, 0.0, 0.0))
+            .is_none());
+    }
+
+    #[test]
+    fn a_textured_sphere_reports_uv_on_its_intersections() {
+        let pattern = Pattern::new_uv_checker(
+            2.0,
+            2.0,
+            Color::black(),
+            Color::white(),
+            UvMapping::Spherical,
+        );
+        let sphere = ObjectBuilder::new_sphere()
+            .with_material(Material::new().with_pattern(pattern))
+            .build();
+        let r = Ray::new(Point::new(0.0, 0.0, 5.0), -Vector::z_norm());
+        let xs = sphere.intersects(&r);
+        assert!(xs[0].u.unwrap().approx_eq(0.5));
+        assert!(xs[0].v.unwrap().approx_eq(0.5));
+    }
+
+    #[parameterized(
+        planar = {Pattern::new_uv_checker(2.0, 2.0, Color::black(), Color::white(), UvMapping::Planar)},
+        rotated = {Pattern::new_uv_checker(2.0, 2.0, Color::black(), Color::white(), UvMapping::Spherical)
+            .with_transform(Transformation::new_transform().rotation_y(0.5))},
+    )]
+    fn a_sphere_leaves_other_uv_mappings_to_the_pattern(pattern: Pattern) {
+        let sphere = ObjectBuilder::new_sphere()
+            .with_material(Material::new().with_pattern(pattern.clone()))
+            .build();
+        let r = Ray::new(Point::new(0.3, 0.4, 5.0), -Vector::z_norm());
+        let xs = sphere.intersects(&r);
+        assert!(xs.iter().all(|i| i.u.is_none() && i.v.is_none()));
+        let p = r.position(xs[0].t);
+        assert_eq!(
+            pattern.color_at_hit(&sphere, p, xs[0].u.zip(xs[0].v)),
+            pattern.pattern_at_object(&sphere, p)
+        );
+    }
+
+    #[test]
+    fn an_untextured_sphere_reports_no_uv() {
+        let sphere = ObjectBuilder::new_sphere().build();
+        let r = Ray::new(Point::new(0.0, 0.0, 5.0), -Vector::z_norm());
+        let xs = sphere.intersects(&r);
+        assert!(xs.iter().all(|i| i.u.is_none() && i.v.is_none()));
+    }
+
     #[test]
     fn a_sphere_has_a_bounding_box() {
         let s = Sphere::default();
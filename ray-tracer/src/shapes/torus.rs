@@ -0,0 +1,247 @@
+use std::f64::consts::PI;
+
+use crate::{
+    bounds::Bounds,
+    intersections::{Intersection, Intersections},
+    rays::Ray,
+    tuples::{points::Point, vectors::Vector, Tuple},
+};
+
+use super::Object;
+
+const ROOT_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Torus {
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Self {
+            major_radius: 1.0,
+            minor_radius: 0.25,
+        }
+    }
+}
+
+impl Torus {
+    pub fn with_radii(&mut self, major_radius: f64, minor_radius: f64) {
+        self.major_radius = major_radius;
+        self.minor_radius = minor_radius;
+    }
+
+    pub fn intersects<'a>(&self, object: &'a Object, r: &Ray) -> Intersections<'a> {
+        let origin = r.origin - Point::zero();
+        let r2 = self.major_radius.powi(2);
+        let m = r.direction.dot(r.direction);
+        let n = origin.dot(r.direction);
+        let k = origin.dot(origin) + r2 - self.minor_radius.powi(2);
+        let dxz = r.direction.x().powi(2) + r.direction.z().powi(2);
+        let odxz = r.origin.x() * r.direction.x() + r.origin.z() * r.direction.z();
+        let oxz = r.origin.x().powi(2) + r.origin.z().powi(2);
+        let coefficients = [
+            k * k - 4.0 * r2 * oxz,
+            4.0 * n * k - 8.0 * r2 * odxz,
+            2.0 * m * k + 4.0 * n * n - 4.0 * r2 * dxz,
+            4.0 * m * n,
+            m * m,
+        ];
+
+        let mut xs = Intersections::new();
+        solve_quartic(coefficients)
+            .into_iter()
+            .map(|t| polish_root(coefficients, t))
+            .for_each(|t| xs.push(Intersection::new(t, object)));
+        xs.sort_by(|i1, i2| i1.t.total_cmp(&i2.t));
+        xs
+    }
+
+    pub fn normal_at(&self, object_point: Point) -> Vector {
+        let (x, y, z) = (object_point.x(), object_point.y(), object_point.z());
+        let param = x * x + y * y + z * z + self.major_radius.powi(2) - self.minor_radius.powi(2);
+        let ring = 2.0 * self.major_radius.powi(2);
+        Vector::new(x * (param - ring), y * param, z * (param - ring))
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let outer = self.major_radius + self.minor_radius;
+        Bounds::new(
+            Point::new(-outer, -self.minor_radius, -outer),
+            Point::new(outer, self.minor_radius, outer),
+        )
+    }
+}
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < ROOT_EPSILON
+}
+
+fn polish_root(c: [f64; 5], t: f64) -> f64 {
+    let f = |t: f64| (((c[4] * t + c[3]) * t + c[2]) * t + c[1]) * t + c[0];
+    let df = |t: f64| ((4.0 * c[4] * t + 3.0 * c[3]) * t + 2.0 * c[2]) * t + c[1];
+    (0..2).fold(t, |t, _| {
+        let slope = df(t);
+        if is_zero(slope) {
+            t
+        } else {
+            t - f(t) / slope
+        }
+    })
+}
+
+// Repeated roots are returned once per multiplicity, so a tangent ray still
+// reports an even number of hits, as the quadric shapes do.
+fn solve_quadratic(c: [f64; 3]) -> Vec<f64> {
+    let p = c[1] / (2.0 * c[2]);
+    let q = c[0] / c[2];
+    let discriminant = p * p - q;
+    if is_zero(discriminant) {
+        vec![-p, -p]
+    } else if discriminant < 0.0 {
+        vec![]
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![sqrt_d - p, -sqrt_d - p]
+    }
+}
+
+fn solve_cubic(c: [f64; 4]) -> Vec<f64> {
+    let a = c[2] / c[3];
+    let b = c[1] / c[3];
+    let c = c[0] / c[3];
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0, 0.0, 0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u, -u]
+        }
+    } else if discriminant < 0.0 {
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![(sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()]
+    };
+    roots.into_iter().map(|s| s - a / 3.0).collect()
+}
+
+fn solve_quartic(c: [f64; 5]) -> Vec<f64> {
+    let a = c[3] / c[4];
+    let b = c[2] / c[4];
+    let c_ = c[1] / c[4];
+    let d = c[0] / c[4];
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + c_;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * c_ / 4.0 + d;
+
+    let roots = if is_zero(r) {
+        let mut roots = solve_cubic([q, p, 0.0, 1.0]);
+        roots.push(0.0);
+        roots
+    } else {
+        let z = solve_cubic([r * p / 2.0 - q * q / 8.0, -r, -p / 2.0, 1.0])[0];
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        if (u < 0.0 && !is_zero(u)) || (v < 0.0 && !is_zero(v)) {
+            return vec![];
+        }
+        let u = if is_zero(u) { 0.0 } else { u.sqrt() };
+        let v = if is_zero(v) { 0.0 } else { v.sqrt() };
+        let (v1, v2) = if q < 0.0 { (-v, v) } else { (v, -v) };
+        let mut roots = solve_quadratic([z - u, v1, 1.0]);
+        roots.extend(solve_quadratic([z + u, v2, 1.0]));
+        roots
+    };
+    roots.into_iter().map(|s| s - a / 4.0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use approx_eq::ApproxEq;
+    use yare::parameterized;
+
+    use crate::shapes::ObjectBuilder;
+
+    use super::*;
+
+    #[parameterized(
+        through_the_ring_along_z = {Point::new(0.0, 0.0, -5.0), Vector::z_norm(), vec![3.75, 4.25, 5.75, 6.25]},
+        through_the_ring_along_x = {Point::new(-5.0, 0.0, 0.0), Vector::x_norm(), vec![3.75, 4.25, 5.75, 6.25]},
+        through_the_tube = {Point::new(1.0, 5.0, 0.0), -Vector::y_norm(), vec![4.75, 5.25]},
+        through_the_hole = {Point::new(0.0, 5.0, 0.0), -Vector::y_norm(), vec![]},
+        above_the_torus = {Point::new(0.0, 0.5, -5.0), Vector::z_norm(), vec![]},
+        outside_the_torus = {Point::new(2.0, 0.0, -5.0), Vector::z_norm(), vec![]},
+    )]
+    fn a_ray_intersects_a_torus(origin: Point, direction: Vector, ts: Vec<f64>) {
+        let torus = ObjectBuilder::new_torus().build();
+        let r = Ray::new(origin, direction);
+        let xs = torus.intersects(&r);
+        assert_eq!(xs.len(), ts.len());
+        for (i, t) in ts.into_iter().enumerate() {
+            assert!(xs[i].t.approx_eq(t));
+        }
+    }
+
+    #[parameterized(
+        outer_edge = {Point::new(1.25, 0.0, -5.0), Vector::z_norm(), vec![5.0, 5.0]},
+        top_of_the_tube = {Point::new(0.0, 0.25, -5.0), Vector::z_norm(), vec![4.0, 4.0, 6.0, 6.0]},
+    )]
+    fn a_tangent_ray_reports_each_touching_point_twice(
+        origin: Point,
+        direction: Vector,
+        ts: Vec<f64>,
+    ) {
+        let torus = ObjectBuilder::new_torus().build();
+        let xs = torus.intersects(&Ray::new(origin, direction));
+        assert_eq!(xs.len(), ts.len());
+        for (i, t) in ts.into_iter().enumerate() {
+            assert!(xs[i].t.approx_eq_within(t, 1e-3));
+        }
+    }
+
+    #[test]
+    fn intersecting_a_torus_with_custom_radii() {
+        let torus = ObjectBuilder::new_torus().with_radii(2.0, 0.5).build();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        let xs = torus.intersects(&r);
+        assert_eq!(xs.len(), 4);
+        assert!(xs[0].t.approx_eq(2.5));
+        assert!(xs[1].t.approx_eq(3.5));
+        assert!(xs[2].t.approx_eq(6.5));
+        assert!(xs[3].t.approx_eq(7.5));
+    }
+
+    #[parameterized(
+        outer_edge = {Point::new(1.25, 0.0, 0.0), Vector::x_norm()},
+        inner_edge = {Point::new(0.75, 0.0, 0.0), -Vector::x_norm()},
+        top = {Point::new(0.0, 0.25, 1.0), Vector::y_norm()},
+        bottom = {Point::new(0.0, -0.25, -1.0), -Vector::y_norm()},
+    )]
+    fn the_normal_on_a_torus(p: Point, normal: Vector) {
+        let torus = Torus::default();
+        assert_eq!(torus.normal_at(p).normalize(), normal);
+    }
+
+    #[test]
+    fn a_torus_has_a_bounding_box() {
+        let torus = Torus::default();
+        let b = torus.bounds();
+        assert_eq!(b.min(), &Point::new(-1.25, -0.25, -1.25));
+        assert_eq!(b.max(), &Point::new(1.25, 0.25, 1.25));
+    }
+}
@@ -62,6 +62,17 @@ impl Triangle {
         self.p3 - self.p1
     }
 
+    pub fn barycentric(&self, p: Point) -> (f64, f64, f64) {
+        let (e1, e2) = (self.e1(), self.e2());
+        let p1_to_p = p - self.p1;
+        let (d11, d12, d22) = (e1.dot(e1), e1.dot(e2), e2.dot(e2));
+        let (dp1, dp2) = (p1_to_p.dot(e1), p1_to_p.dot(e2));
+        let denominator = d11 * d22 - d12 * d12;
+        let beta = (d22 * dp1 - d12 * dp2) / denominator;
+        let gamma = (d11 * dp2 - d12 * dp1) / denominator;
+        (1.0 - beta - gamma, beta, gamma)
+    }
+
     pub fn normal_at(&self, _object_point: Point) -> Vector {
         (self.e2() * self.e1()).normalize()
     }
@@ -104,6 +115,7 @@ impl Triangle {
 
 #[cfg(test)]
 mod tests {
+    use yare::parameterized;
 
     use crate::shapes::ObjectBuilder;
 
@@ -203,6 +215,20 @@ mod tests {
         assert_eq!(xs[0].t, 2.0);
     }
 
+    #[parameterized(
+        p1 = {Point::new(0.0, 1.0, 0.0), (1.0, 0.0, 0.0)},
+        p2 = {Point::new(-1.0, 0.0, 0.0), (0.0, 1.0, 0.0)},
+        p3 = {Point::new(1.0, 0.0, 0.0), (0.0, 0.0, 1.0)},
+        centroid = {Point::new(0.0, 1.0 / 3.0, 0.0), (1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0)},
+        outside = {Point::new(0.0, -1.0, 0.0), (-1.0, 1.0, 1.0)},
+    )]
+    fn barycentric_coordinates_of_a_point(p: Point, expected: (f64, f64, f64)) {
+        let (alpha, beta, gamma) = Triangle::default().barycentric(p);
+        assert!(alpha.approx_eq(expected.0));
+        assert!(beta.approx_eq(expected.1));
+        assert!(gamma.approx_eq(expected.2));
+    }
+
     #[test]
     fn a_triangle_has_a_bounding_box() {
         let mut s = Triangle::default();
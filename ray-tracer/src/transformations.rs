@@ -1,8 +1,83 @@
+use std::{cell::RefCell, collections::HashMap};
+
 use crate::{
     matrix::Matrix,
     tuples::{points::Point, vectors::Vector, Tuple},
 };
 
+const INVERSE_CACHE_CAPACITY: usize = 256;
+
+thread_local! {
+    static INVERSE_CACHE: RefCell<InverseCache> =
+        RefCell::new(InverseCache::new(INVERSE_CACHE_CAPACITY));
+}
+
+type InverseKey = [u64; 16];
+
+// A small per-thread cache, so identical transforms built in a row share one
+// inverse without a global lock or unbounded growth. It keeps two generations
+// of half the capacity each: an entry used since the last rotation survives
+// the next one, which approximates LRU without ever scanning the entries.
+struct InverseCache {
+    generation_size: usize,
+    current: HashMap<InverseKey, Option<Matrix>>,
+    previous: HashMap<InverseKey, Option<Matrix>>,
+    #[cfg(test)]
+    computed: usize,
+}
+
+impl InverseCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            generation_size: (capacity / 2).max(1),
+            current: HashMap::new(),
+            previous: HashMap::new(),
+            #[cfg(test)]
+            computed: 0,
+        }
+    }
+
+    fn key(m: &Matrix) -> Option<InverseKey> {
+        if m.size() != 4 {
+            return None;
+        }
+        let mut key = [0; 16];
+        for (idx, bits) in key.iter_mut().enumerate() {
+            *bits = m[(idx / 4, idx % 4)].to_bits();
+        }
+        Some(key)
+    }
+
+    fn inverse(&mut self, m: &Matrix) -> Option<Matrix> {
+        let Some(key) = Self::key(m) else {
+            return m.inverse();
+        };
+        if let Some(inverse) = self.current.get(&key) {
+            return inverse.clone();
+        }
+        let inverse = match self.previous.remove(&key) {
+            Some(inverse) => inverse,
+            None => {
+                #[cfg(test)]
+                {
+                    self.computed += 1;
+                }
+                m.inverse()
+            }
+        };
+        if self.current.len() >= self.generation_size {
+            self.previous = std::mem::take(&mut self.current);
+        }
+        self.current.insert(key, inverse.clone());
+        inverse
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Transformation {
     pub matrix: Matrix,
@@ -22,7 +97,7 @@ impl Transformation {
     }
 
     fn prepare_transform(t: &Matrix) -> (Option<Matrix>, Option<Matrix>) {
-        let transform_inverse = t.inverse();
+        let transform_inverse = INVERSE_CACHE.with(|cache| cache.borrow_mut().inverse(t));
         let transform_inverse_transpose = if let Some(ti) = &transform_inverse {
             Some(ti.transpose())
         } else {
@@ -41,8 +116,8 @@ impl Transformation {
     pub fn view_transform(from: Point, to: Point, up: Vector) -> Self {
         let forward = (to - from).normalize();
         let up_normalized = up.normalize();
-        let left = forward * up_normalized;
-        let true_up = left * forward;
+        let left = forward.cross(up_normalized);
+        let true_up = left.cross(forward);
         let orientation = Matrix::new(vec![
             vec![left.x(), left.y(), left.z(), 0.0],
             vec![true_up.x(), true_up.y(), true_up.z(), 0.0],
@@ -146,6 +221,30 @@ impl Transformation {
         }
     }
 
+    pub fn rotation_axis(&self, axis: Vector, rad: f64) -> Self {
+        let k = axis.normalize();
+        let (x, y, z) = (k.x(), k.y(), k.z());
+        let (sin, cos) = rad.sin_cos();
+        let t = 1.0 - cos;
+        let mut m = Matrix::identity(4);
+        m[(0, 0)] = cos + x * x * t;
+        m[(0, 1)] = x * y * t - z * sin;
+        m[(0, 2)] = x * z * t + y * sin;
+        m[(1, 0)] = y * x * t + z * sin;
+        m[(1, 1)] = cos + y * y * t;
+        m[(1, 2)] = y * z * t - x * sin;
+        m[(2, 0)] = z * x * t - y * sin;
+        m[(2, 1)] = z * y * t + x * sin;
+        m[(2, 2)] = cos + z * z * t;
+        let t = &m * &self.matrix;
+        let (inverse, inverse_transposed) = Self::prepare_transform(&t);
+        Self {
+            matrix: t,
+            inverse,
+            inverse_transposed,
+        }
+    }
+
     pub fn shearing(&self, xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Self {
         let mut m = Matrix::identity(4);
         m[(0, 1)] = xy;
@@ -163,6 +262,10 @@ impl Transformation {
         }
     }
 
+    pub fn then(&self, other: &Transformation) -> Transformation {
+        Transformation::from(&other.matrix * &self.matrix)
+    }
+
     pub fn inverse(&self) -> Option<&Matrix> {
         self.inverse.as_ref()
     }
@@ -289,6 +392,33 @@ mod tests {
         assert_eq!(&full_quarter.matrix * &p, Point::new(-1.0, 0.0, 0.0));
     }
 
+    #[test]
+    fn rotating_around_an_arbitrary_axis() {
+        let p = Point::new(1.0, 0.0, 0.0);
+        let around_z = Transformation::new_transform().rotation_axis(Vector::z_norm(), PI / 2.0);
+        let rotation_z = Transformation::new_transform().rotation_z(PI / 2.0);
+        assert_eq!(around_z, rotation_z);
+        assert_eq!(&around_z.matrix * &p, Point::new(0.0, 1.0, 0.0));
+        assert_eq!(
+            Transformation::new_transform().rotation_axis(Vector::new(0.0, -2.0, 0.0), PI / 3.0),
+            Transformation::new_transform().rotation_y(-PI / 3.0)
+        );
+    }
+
+    #[test]
+    fn rotating_around_a_diagonal_axis_cycles_the_axes() {
+        let t = Transformation::new_transform()
+            .rotation_axis(Vector::new(1.0, 1.0, 1.0), 2.0 * PI / 3.0);
+        assert_eq!(
+            &t.matrix * &Point::new(1.0, 0.0, 0.0),
+            Point::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            &t.matrix * &Vector::new(0.0, 0.0, 1.0),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+    }
+
     #[test]
     fn shearing_moves_x_in_proportion_to_y() {
         let transform = Transformation::new_transform().shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
@@ -341,6 +471,29 @@ mod tests {
         assert_eq!(&transform.matrix * &p, Point::new(15.0, 0.0, 7.0));
     }
 
+    #[test]
+    fn composing_transformations_in_application_order() {
+        let rotate = Transformation::new_transform().rotation_x(PI / 2.0);
+        let scale = Transformation::new_transform().scaling(5.0, 5.0, 5.0);
+        let translate = Transformation::new_transform().translation(10.0, 5.0, 7.0);
+        let transform = rotate.then(&scale).then(&translate);
+        assert_eq!(
+            transform.matrix,
+            &(&translate.matrix * &scale.matrix) * &rotate.matrix
+        );
+        assert_eq!(
+            transform,
+            Transformation::new_transform()
+                .rotation_x(PI / 2.0)
+                .scaling(5.0, 5.0, 5.0)
+                .translation(10.0, 5.0, 7.0)
+        );
+        assert_eq!(
+            &transform.matrix * &Point::new(1.0, 0.0, 1.0),
+            Point::new(15.0, 0.0, 7.0)
+        );
+    }
+
     #[test]
     fn transformation_matrix_for_default_orientation() {
         let from = Point::zero();
@@ -387,4 +540,46 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn identical_transforms_compute_their_inverse_once() {
+        let build = || {
+            Transformation::new_transform()
+                .scaling(0.31, 0.47, 0.29)
+                .translation(7.0, -3.0, 11.0)
+        };
+        let computed = || INVERSE_CACHE.with(|cache| cache.borrow().computed);
+        let before = computed();
+        let first = build();
+        let after_first = computed();
+        assert!(after_first > before);
+        let rest: Vec<Transformation> = (1..100).map(|_| build()).collect();
+        assert_eq!(computed(), after_first);
+        assert!(rest.iter().all(|t| t.inverse() == first.inverse()));
+        assert_eq!(first.inverse(), first.matrix.inverse().as_ref());
+    }
+
+    #[test]
+    fn the_inverse_cache_evicts_the_least_recently_used_matrix() {
+        let mut cache = InverseCache::new(2);
+        let a = Transformation::new_transform()
+            .translation(1.0, 0.0, 0.0)
+            .matrix;
+        let b = Transformation::new_transform()
+            .translation(2.0, 0.0, 0.0)
+            .matrix;
+        let c = Transformation::new_transform()
+            .translation(3.0, 0.0, 0.0)
+            .matrix;
+        cache.inverse(&a);
+        cache.inverse(&b);
+        cache.inverse(&a);
+        cache.inverse(&c);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.computed, 3);
+        cache.inverse(&a);
+        assert_eq!(cache.computed, 3);
+        cache.inverse(&b);
+        assert_eq!(cache.computed, 4);
+    }
 }
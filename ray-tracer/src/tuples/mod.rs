@@ -1,14 +1,38 @@
+use approx_eq::ApproxEq;
+
 pub mod points;
 pub mod vectors;
 
+// Infinite components (as in unbounded bounding boxes) only match exactly.
+fn components_approx_eq(a: f64, b: f64, eps: f64) -> bool {
+    a == b || a.approx_eq_within(b, eps)
+}
+
 pub trait Tuple {
+    /// The homogeneous w coordinate every tuple of this type has.
+    const W: f64;
+
     fn new(x: f64, y: f64, z: f64) -> Self;
     fn x(&self) -> f64;
     fn y(&self) -> f64;
     fn z(&self) -> f64;
-    fn w(&self) -> f64;
+
+    fn w(&self) -> f64 {
+        Self::W
+    }
 
     fn zero() -> Self;
+
+    fn lerp(&self, other: &Self, t: f64) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(
+            self.x() + (other.x() - self.x()) * t,
+            self.y() + (other.y() - self.y()) * t,
+            self.z() + (other.z() - self.z()) * t,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -76,4 +100,30 @@ mod tests {
         let vector = Vector::new(1.0, -2.0, 3.0);
         assert_eq!(vector / 2.0, Vector::new(0.5, -1.0, 1.5));
     }
+
+    #[test]
+    fn interpolating_between_two_points() {
+        let a = Point::new(0.0, 2.0, -4.0);
+        let b = Point::new(4.0, -2.0, 4.0);
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 1.0), b);
+        assert_eq!(a.lerp(&b, 0.5), Point::new(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn interpolating_between_two_vectors() {
+        let a = Vector::x_norm();
+        let b = Vector::new(0.0, 3.0, 1.0);
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 1.0), b);
+        assert_eq!(a.lerp(&b, 0.5), Vector::new(0.5, 1.5, 0.5));
+    }
+
+    #[test]
+    fn points_closer_than_epsilon_are_approximately_equal() {
+        let p = Point::new(1.0, -2.0, 3.0);
+        assert!(p.approx_eq(&Point::new(1.000001, -2.0, 2.999999)));
+        assert!(!p.approx_eq(&Point::new(1.0, -2.001, 3.0)));
+        assert!(p.approx_eq_within(&Point::new(1.0, -2.001, 3.0), 0.01));
+    }
 }
@@ -1,8 +1,8 @@
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
-use approx_eq::ApproxEq;
+use approx_eq::EPSILON;
 
-use super::{points::Point, Tuple};
+use super::{components_approx_eq, points::Point, Tuple};
 
 #[derive(Debug, Copy, Clone)]
 pub struct Vector {
@@ -36,14 +36,49 @@ impl Vector {
         self.x() * other.x() + self.y() * other.y() + self.z() * other.z()
     }
 
+    pub fn approx_eq(&self, other: &Vector) -> bool {
+        self.approx_eq_within(other, EPSILON)
+    }
+
+    pub fn approx_eq_within(&self, other: &Vector, eps: f64) -> bool {
+        components_approx_eq(self.x, other.x, eps)
+            && components_approx_eq(self.y, other.y, eps)
+            && components_approx_eq(self.z, other.z, eps)
+    }
+
+    pub fn cross(&self, other: Vector) -> Vector {
+        Vector::new(
+            self.y() * other.z() - self.z() * other.y(),
+            self.z() * other.x() - self.x() * other.z(),
+            self.x() * other.y() - self.y() * other.x(),
+        )
+    }
+
+    pub fn angle_between(&self, other: Vector) -> f64 {
+        let cos = self.dot(other) / (self.magnitude() * other.magnitude());
+        cos.clamp(-1.0, 1.0).acos()
+    }
+
     pub fn reflect(&self, n: Vector) -> Vector {
         let dp = self.dot(n);
         let double_n = n * 2.0;
         *self - (double_n * dp)
     }
+
+    pub fn refract(&self, n: Vector, n_ratio: f64) -> Option<Vector> {
+        let cos_i = -self.dot(n);
+        let sin2_t = n_ratio * n_ratio * (1.0 - cos_i * cos_i);
+        if sin2_t > 1.0 {
+            return None;
+        }
+        let cos_t = f64::sqrt(1.0 - sin2_t);
+        Some(n * (n_ratio * cos_i - cos_t) + *self * n_ratio)
+    }
 }
 
 impl Tuple for Vector {
+    const W: f64 = 0.0;
+
     fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
     }
@@ -60,10 +95,6 @@ impl Tuple for Vector {
         self.z
     }
 
-    fn w(&self) -> f64 {
-        0.0
-    }
-
     fn zero() -> Self {
         Vector::new(0.0, 0.0, 0.0)
     }
@@ -71,7 +102,7 @@ impl Tuple for Vector {
 
 impl PartialEq for Vector {
     fn eq(&self, other: &Self) -> bool {
-        self.x.approx_eq(other.x) && self.y.approx_eq(other.y) && self.z.approx_eq(other.z)
+        self.approx_eq(other)
     }
 }
 
@@ -139,15 +170,12 @@ impl Mul<f64> for Vector {
     }
 }
 
+/// Multiplying two vectors is their cross product, the same as [`Vector::cross`].
 impl Mul<Vector> for Vector {
     type Output = Vector;
 
     fn mul(self, rhs: Vector) -> Self::Output {
-        Vector::new(
-            self.y() * rhs.z() - self.z() * rhs.y(),
-            self.z() * rhs.x() - self.x() * rhs.z(),
-            self.x() * rhs.y() - self.y() * rhs.x(),
-        )
+        self.cross(rhs)
     }
 }
 
@@ -171,6 +199,11 @@ impl From<[f64; 3]> for Vector {
 
 #[cfg(test)]
 mod tests {
+    use std::f64::consts::PI;
+
+    use approx_eq::ApproxEq;
+    use yare::parameterized;
+
     use super::*;
 
     #[test]
@@ -252,4 +285,55 @@ mod tests {
         let r = v.reflect(n);
         assert_eq!(r, Vector::new(1.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn refracting_a_vector_head_on_passes_straight_through() {
+        let v = -Vector::y_norm();
+        let n = Vector::y_norm();
+        assert_eq!(v.refract(n, 1.0 / 1.5), Some(-Vector::y_norm()));
+    }
+
+    #[test]
+    fn refracting_a_vector_bends_it_towards_the_normal() {
+        let v = Vector::new(0.0, -f64::sqrt(2.0) / 2.0, f64::sqrt(2.0) / 2.0);
+        let n = Vector::y_norm();
+        let r = v.refract(n, 1.0 / 1.5).unwrap();
+        assert_eq!(r, Vector::new(0.0, -0.88192, 0.47140));
+    }
+
+    #[test]
+    fn refracting_a_vector_under_total_internal_reflection() {
+        let v = Vector::new(0.0, -f64::sqrt(2.0) / 2.0, f64::sqrt(2.0) / 2.0);
+        let n = Vector::y_norm();
+        assert_eq!(v.refract(n, 1.5), None);
+    }
+
+    #[parameterized(
+        x_cross_y = {Vector::x_norm(), Vector::y_norm(), Vector::z_norm()},
+        y_cross_z = {Vector::y_norm(), Vector::z_norm(), Vector::x_norm()},
+        z_cross_x = {Vector::z_norm(), Vector::x_norm(), Vector::y_norm()},
+        y_cross_x = {Vector::y_norm(), Vector::x_norm(), -Vector::z_norm()},
+    )]
+    fn the_cross_product_of_basis_vectors(a: Vector, b: Vector, expected: Vector) {
+        assert_eq!(a.cross(b), expected);
+        assert_eq!(a * b, expected);
+    }
+
+    #[parameterized(
+        orthogonal = {Vector::x_norm(), Vector::z_norm(), PI / 2.0},
+        parallel = {Vector::new(2.0, 0.0, 0.0), Vector::x_norm(), 0.0},
+        opposite = {Vector::y_norm(), -Vector::y_norm(), PI},
+        diagonal = {Vector::new(1.0, 1.0, 0.0), Vector::x_norm(), PI / 4.0},
+    )]
+    fn the_angle_between_two_vectors(a: Vector, b: Vector, expected: f64) {
+        assert!(a.angle_between(b).approx_eq(expected));
+    }
+
+    #[test]
+    fn vectors_closer_than_epsilon_are_approximately_equal() {
+        let v = Vector::new(1.0, -2.0, 3.0);
+        assert!(v.approx_eq(&Vector::new(1.000001, -2.0, 2.999999)));
+        assert!(!v.approx_eq(&Vector::new(1.001, -2.0, 3.0)));
+        assert!(v.approx_eq_within(&Vector::new(1.001, -2.0, 3.0), 0.01));
+    }
 }
@@ -1,27 +1,39 @@
-use approx_eq::ApproxEq;
+use approx_eq::{ApproxEq, EPSILON};
 use colo_rs::colors::Color;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 use crate::{
+    bvh::Bvh,
+    environment::Environment,
     intersections::{Computation, Intersections},
-    lights::PointLight,
+    lights::{Light, PointLight},
     materials::Material,
+    octree::Octree,
     patterns::Pattern,
     rays::Ray,
     shapes::{Object, ObjectBuilder},
     transformations::Transformation,
-    tuples::{points::Point, Tuple},
+    tuples::{points::Point, vectors::Vector, Tuple},
 };
 
+const PARALLEL_INTERSECTION_THRESHOLD: usize = 64;
+
 #[derive(Debug)]
 pub struct World {
-    lights: Vec<PointLight>,
+    lights: Vec<Light>,
     objects: Vec<Object>,
+    bvh: Bvh,
+    octree: Option<Octree>,
+    background: Color,
+    environment: Option<Environment>,
+    transparent_shadows: bool,
+    shadow_bias: f64,
 }
 
 impl Default for World {
     fn default() -> Self {
         let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
-        let lights = vec![light];
+        let lights = vec![light.into()];
         let m1 = Material::new()
             .with_pattern(Pattern::new_solid_pattern(Color::new(0.8, 1.0, 0.6)))
             .with_diffuse(0.7)
@@ -30,7 +42,11 @@ impl Default for World {
         let t2 = Transformation::new_transform().scaling(0.5, 0.5, 0.5);
         let s2 = ObjectBuilder::new_sphere().with_transform(t2).build();
         let objects = vec![s1, s2];
-        Self { lights, objects }
+        Self {
+            lights,
+            ..Self::new()
+        }
+        .with_objects(objects)
     }
 }
 
@@ -39,20 +55,73 @@ impl World {
         Self {
             lights: Vec::new(),
             objects: Vec::new(),
+            bvh: Bvh::default(),
+            octree: None,
+            background: Color::black(),
+            environment: None,
+            transparent_shadows: false,
+            shadow_bias: EPSILON,
         }
     }
 
-    pub fn with_lights(mut self, lights: Vec<PointLight>) -> Self {
-        self.lights = lights;
+    pub fn with_lights<L: Into<Light>>(mut self, lights: Vec<L>) -> Self {
+        self.lights = lights.into_iter().map(Into::into).collect();
         self
     }
 
     pub fn with_objects(mut self, objects: Vec<Object>) -> Self {
         self.objects = objects;
+        self.rebuild_index();
         self
     }
 
-    pub fn lights(&self) -> &[PointLight] {
+    // The octree is built once per change to the object list and costs a
+    // containment test per object per level. In return each ray only visits
+    // the octants it crosses, which pays off when many objects sit in
+    // separate regions of the scene; otherwise the BVH is used.
+    pub fn with_octree(mut self, octree: bool) -> Self {
+        self.build_index(octree);
+        self
+    }
+
+    fn rebuild_index(&mut self) {
+        self.build_index(self.octree.is_some());
+    }
+
+    // Only the index that intersect_world will consult is built.
+    fn build_index(&mut self, octree: bool) {
+        if octree {
+            self.bvh = Bvh::default();
+            self.octree = Some(Octree::new(&self.objects));
+        } else {
+            self.bvh = Bvh::new(&self.objects);
+            self.octree = None;
+        }
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    // Surfaces far from the origin lose precision faster than EPSILON, so
+    // large scenes need a bigger offset to keep hits from shadowing themselves.
+    pub fn with_shadow_bias(mut self, shadow_bias: f64) -> Self {
+        self.shadow_bias = shadow_bias;
+        self
+    }
+
+    pub fn with_transparent_shadows(mut self, transparent_shadows: bool) -> Self {
+        self.transparent_shadows = transparent_shadows;
+        self
+    }
+
+    pub fn lights(&self) -> &[Light] {
         &self.lights
     }
 
@@ -60,112 +129,288 @@ impl World {
         &self.objects
     }
 
+    pub fn add_object(&mut self, object: Object) {
+        self.objects.push(object);
+        self.rebuild_index();
+    }
+
+    pub fn remove_object(&mut self, object: &Object) -> bool {
+        match self.objects.iter().position(|o| o == object) {
+            Some(idx) => {
+                self.objects.remove(idx);
+                self.rebuild_index();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    pub fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+
+    pub fn shadow_bias(&self) -> f64 {
+        self.shadow_bias
+    }
+
+    pub fn transparent_shadows(&self) -> bool {
+        self.transparent_shadows
+    }
+
     pub fn intersect_world(&self, ray: Ray) -> Intersections {
-        let mut xs = Intersections::new();
+        let candidates = match &self.octree {
+            Some(octree) => octree.candidates(&ray),
+            None => self.bvh.candidates(&ray),
+        };
+        let candidates = candidates
+            .into_iter()
+            .map(|idx| &self.objects[idx])
+            .collect::<Vec<_>>();
+        if candidates.len() >= PARALLEL_INTERSECTION_THRESHOLD {
+            Self::intersect_objects_parallel(&candidates, &ray)
+        } else {
+            Self::intersect_objects_serial(&candidates, &ray)
+        }
+    }
 
-        // if let Some(octree) = &self.octree {
-        //     xs = octree.intersects(&ray);
-        // } else {
-        self.objects
+    fn intersect_objects_serial<'a>(objects: &[&'a Object], ray: &Ray) -> Intersections<'a> {
+        let mut xs = Intersections::new();
+        objects
             .iter()
-            .for_each(|obj| xs.push_all(obj.intersects(&ray)));
-        // }
+            .for_each(|obj| xs.push_all(obj.intersects(ray)));
         xs.sort_by(|i1, i2| i1.t.total_cmp(&i2.t));
         xs
     }
 
+    fn intersect_objects_parallel<'a>(objects: &[&'a Object], ray: &Ray) -> Intersections<'a> {
+        objects
+            .par_iter()
+            .map(|obj| obj.intersects(ray))
+            .reduce(Intersections::new, Intersections::merge_sorted)
+    }
+
     pub fn shade_hit(&self, comps: Computation, remaining: usize) -> Color {
         let obj = comps.object;
+        let surface_color = obj
+            .material()
+            .pattern()
+            .color_at_hit(obj, comps.over_point, comps.uv);
         let color = self
             .lights()
             .iter()
+            .filter(|light| !matches!(light, Light::Ambient(_)))
             .map(|light| {
-                let in_shadow =
-                    obj.material().receive_shadows && self.is_shadowed(comps.over_point);
-                obj.material().lighting(
-                    *light,
-                    comps.over_point,
-                    comps.eye_v,
-                    comps.normal_v,
-                    in_shadow,
-                    &obj,
-                )
+                let lighting = |in_shadow| {
+                    obj.material().reflected_light(
+                        *light,
+                        surface_color,
+                        comps.over_point,
+                        comps.eye_v,
+                        comps.normal_v,
+                        in_shadow,
+                    )
+                };
+                if obj.material().receive_shadows() && self.transparent_shadows {
+                    let attenuation = self.shadow_attenuation(comps.over_point, light);
+                    let shadowed = lighting(true);
+                    let lit = lighting(false);
+                    &shadowed + &(&(&lit - &shadowed) * &attenuation)
+                } else if obj.material().receive_shadows() && matches!(light, Light::Area(_)) {
+                    let fraction = self.intensity_at(comps.over_point, light);
+                    let shadowed = lighting(true);
+                    let lit = lighting(false);
+                    &shadowed + &((&lit - &shadowed) * fraction)
+                } else {
+                    lighting(
+                        obj.material().receive_shadows()
+                            && self.is_shadowed(comps.over_point, light),
+                    )
+                }
             })
             .sum();
+        let fill: Color = self
+            .lights()
+            .iter()
+            .filter(|light| matches!(light, Light::Ambient(_)))
+            .map(|light| light.intensity())
+            .sum();
+        let color = &color + &((&surface_color * &fill) * obj.material().ambient());
+        let color = &color + &obj.material().emission();
+        let color = if let Some(environment) = &self.environment {
+            let ibl = &surface_color * &environment.irradiance(comps.normal_v);
+            &color + &(ibl * obj.material().diffuse())
+        } else {
+            color
+        };
         let reflected = self.reflected_color(&comps, remaining);
         let refracted = self.refracted_color(&comps, remaining);
-        let ref_sum = if obj.material().reflective > 0.0 && obj.material().transparency > 0.0 {
-            let reflectance = comps.schlick();
+        let ref_sum = if obj.material().reflective() > 0.0 && obj.material().transparency() > 0.0 {
+            let reflectance = comps.reflectance();
             let reflected_mod = &reflected * reflectance;
             let refracted_mod = &refracted * (1.0 - reflectance);
             &reflected_mod + &refracted_mod
+        } else if obj.material().fresnel() {
+            &(reflected * comps.reflectance()) + &refracted
         } else {
             &reflected + &refracted
         };
-        &color + &ref_sum
+        let surface = &color + &ref_sum;
+        let opacity = obj
+            .material()
+            .pattern()
+            .opacity_at_object(obj, comps.over_point);
+        if opacity < 1.0 && remaining > 0 {
+            let through_ray = Ray {
+                wavelength: comps.wavelength,
+                ..Ray::new(comps.under_point, -comps.eye_v)
+            };
+            let through = self.color_at(through_ray, remaining - 1);
+            &(surface * opacity) + &(through * (1.0 - opacity))
+        } else {
+            surface
+        }
+    }
+
+    pub fn pick(&self, r: Ray) -> Option<usize> {
+        let xs = self.intersect_world(r);
+        let hit = xs.hit()?;
+        self.objects.iter().position(|o| o.csg_includes(hit.object))
     }
 
     pub fn color_at(&self, r: Ray, remaining: usize) -> Color {
         let xs = self.intersect_world(r);
         if let Some(hit) = xs.hit() {
-            let comps = hit.prepare_computations(r, &xs);
+            let comps = hit.prepare_computations_with_bias(r, &xs, self.shadow_bias);
             self.shade_hit(comps, remaining)
+        } else if let Some(environment) = &self.environment {
+            environment.color_at(r.direction)
         } else {
-            Color::black()
+            self.background
         }
     }
 
-    pub fn is_shadowed(&self, p: Point) -> bool {
-        for light in self.lights() {
-            let v = light.position - p;
-            let distance = v.magnitude();
-            let direction = v.normalize();
-            let shadow_ray = Ray::new(p, direction);
-            let xs: Intersections = self.intersect_world(shadow_ray);
-            let mut shadowing_xs: Intersections = Intersections::new();
-            xs.iter()
-                .filter(|i| {
-                    let obj = i.object;
-                    obj.material().cast_shadows == true
-                })
-                .for_each(|i| shadowing_xs.push(*i));
-
-            let h = shadowing_xs.hit();
-            if let Some(h) = h {
-                if h.t < distance {
-                    return true;
-                }
+    pub fn is_shadowed(&self, p: Point, light: &Light) -> bool {
+        self.is_occluded(p, light.direction_from(p), light.distance_from(p))
+    }
+
+    pub fn intensity_at(&self, p: Point, light: &Light) -> f64 {
+        match light {
+            Light::Area(area) => {
+                let samples = area.samples();
+                let lit = samples
+                    .iter()
+                    .filter(|&&sample| {
+                        let to_sample = sample - p;
+                        !self.is_occluded(p, to_sample.normalize(), to_sample.magnitude())
+                    })
+                    .count();
+                lit as f64 / samples.len() as f64
             }
+            _ if self.is_shadowed(p, light) => 0.0,
+            _ => 1.0,
         }
-        false
+    }
+
+    fn is_occluded(&self, p: Point, direction: Vector, distance: f64) -> bool {
+        let shadow_ray = Ray::new(p, direction);
+        let xs: Intersections = self.intersect_world(shadow_ray);
+        let mut shadowing_xs: Intersections = Intersections::new();
+        xs.iter()
+            .filter(|i| {
+                let obj = i.object;
+                obj.material().cast_shadows()
+                    && obj
+                        .material()
+                        .pattern()
+                        .opacity_at_object(obj, shadow_ray.position(i.t))
+                        > 0.0
+            })
+            .for_each(|i| shadowing_xs.push(*i));
+
+        if let Some(h) = shadowing_xs.hit() {
+            h.t < distance
+        } else {
+            false
+        }
+    }
+
+    pub fn shadow_attenuation(&self, p: Point, light: &Light) -> Color {
+        match light {
+            Light::Area(area) => {
+                let samples = area.samples();
+                let attenuation: Color = samples
+                    .iter()
+                    .map(|&sample| {
+                        let to_sample = sample - p;
+                        self.attenuation_along(p, to_sample.normalize(), to_sample.magnitude())
+                    })
+                    .sum();
+                attenuation / samples.len() as f64
+            }
+            _ => self.attenuation_along(p, light.direction_from(p), light.distance_from(p)),
+        }
+    }
+
+    fn attenuation_along(&self, p: Point, direction: Vector, distance: f64) -> Color {
+        let shadow_ray = Ray::new(p, direction);
+        self.intersect_world(shadow_ray)
+            .iter()
+            .filter(|i| i.t > 0.0 && i.t < distance && i.object.material().cast_shadows())
+            .fold(Color::white(), |attenuation, i| {
+                let obj = i.object;
+                let point = shadow_ray.position(i.t);
+                let opacity = obj.material().pattern().opacity_at_object(obj, point);
+                let transmitted = if obj.material().transparency() > 0.0 {
+                    obj.material().pattern().pattern_at_object(obj, point)
+                        * obj.material().transparency()
+                } else {
+                    Color::black()
+                };
+                let filter = &(Color::white() * (1.0 - opacity)) + &(transmitted * opacity);
+                &attenuation * &filter
+            })
     }
 
     pub fn reflected_color(&self, comps: &Computation, remaining: usize) -> Color {
         let obj = comps.object;
-        if obj.material().reflective.approx_eq(0.0) || remaining == 0 {
+        if obj.material().reflective().approx_eq(0.0) || remaining == 0 {
             Color::black()
         } else {
-            let reflected_ray = Ray::new(comps.over_point, comps.reflect_v);
+            let reflected_ray = Ray {
+                wavelength: comps.wavelength,
+                ..Ray::new(comps.over_point, comps.reflect_v)
+            };
             let color = self.color_at(reflected_ray, remaining - 1);
-            color * obj.material().reflective
+            &color * &(obj.material().reflective_color() * obj.material().reflective())
         }
     }
 
     pub fn refracted_color(&self, comps: &Computation, remaining: usize) -> Color {
         let obj = comps.object;
-        if obj.material().transparency.approx_eq(0.0) || remaining == 0 {
+        if obj.material().transparency().approx_eq(0.0) || remaining == 0 {
             return Color::black();
         };
+        if obj.material().thin() {
+            let through_ray = Ray {
+                wavelength: comps.wavelength,
+                ..Ray::new(comps.under_point, -comps.eye_v)
+            };
+            return self.color_at(through_ray, remaining - 1) * obj.material().transparency();
+        }
         let n_ratio = comps.n1 / comps.n2;
-        let cos_i = comps.eye_v.dot(comps.normal_v);
-        let sin2_t = n_ratio * n_ratio * (1.0 - cos_i * cos_i);
-        if sin2_t > 1.0 {
-            Color::black()
-        } else {
-            let cos_t = f64::sqrt(1.0 - sin2_t);
-            let direction = comps.normal_v * (n_ratio * cos_i - cos_t) - comps.eye_v * n_ratio;
-            let refract_ray = Ray::new(comps.under_point, direction);
-            self.color_at(refract_ray, remaining - 1) * obj.material().transparency
+        match (-comps.eye_v).refract(comps.normal_v, n_ratio) {
+            Some(direction) => {
+                let refract_ray = Ray {
+                    wavelength: comps.wavelength,
+                    ..Ray::new(comps.under_point, direction)
+                };
+                self.color_at(refract_ray, remaining - 1) * obj.material().transparency()
+            }
+            None => Color::black(),
         }
     }
 }
@@ -173,7 +418,17 @@ impl World {
 #[cfg(test)]
 mod tests {
 
-    use crate::{intersections::Intersection, patterns::Pattern, tuples::vectors::Vector};
+    use std::f64::consts::PI;
+
+    use yare::parameterized;
+
+    use crate::{
+        camera::Camera,
+        canvas::Canvas,
+        intersections::Intersection,
+        lights::{AmbientLight, AreaLight, DirectionalLight},
+        patterns::Pattern,
+    };
 
     use super::*;
 
@@ -184,6 +439,121 @@ mod tests {
         assert_eq!(w.lights(), Vec::new());
     }
 
+    #[test]
+    fn adding_and_removing_objects() {
+        let mut w = World::new();
+        let sphere = ObjectBuilder::new_sphere().build();
+        let cube = ObjectBuilder::new_cube().build();
+        w.add_object(sphere.clone());
+        w.add_object(cube.clone());
+        assert_eq!(w.objects(), [sphere.clone(), cube.clone()]);
+        assert!(w.remove_object(&sphere));
+        assert_eq!(w.objects(), [cube]);
+        assert!(!w.remove_object(&sphere));
+    }
+
+    #[test]
+    fn parallel_and_serial_world_intersections_agree() {
+        let w = World::default();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let hits = |xs: Intersections| {
+            xs.iter()
+                .map(|i| (i.t, i.object as *const Object))
+                .collect::<Vec<_>>()
+        };
+        let objects = w.objects().iter().collect::<Vec<_>>();
+        let serial = hits(World::intersect_objects_serial(&objects, &r));
+        assert_eq!(serial.len(), 4);
+        assert_eq!(
+            serial,
+            hits(World::intersect_objects_parallel(&objects, &r))
+        );
+    }
+
+    #[test]
+    fn bvh_intersections_match_brute_force() {
+        let objects = (0..64)
+            .map(|i| {
+                let (x, y, z) = ((i % 4) as f64, ((i / 4) % 4) as f64, (i / 16) as f64);
+                ObjectBuilder::new_sphere()
+                    .with_transform(Transformation::new_transform().translation(
+                        x * 2.5,
+                        y * 2.5,
+                        z * 2.5,
+                    ))
+                    .build()
+            })
+            .chain([ObjectBuilder::new_plane().build()])
+            .collect::<Vec<_>>();
+        let w = World::new().with_objects(objects);
+        let all = w.objects().iter().collect::<Vec<_>>();
+        let rays = [
+            Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm()),
+            Ray::new(
+                Point::new(2.5, 5.0, -5.0),
+                Vector::new(0.1, 0.2, 1.0).normalize(),
+            ),
+            Ray::new(
+                Point::new(-5.0, 20.0, 3.0),
+                Vector::new(1.0, -1.0, 0.3).normalize(),
+            ),
+            Ray::new(Point::new(50.0, 50.0, 50.0), Vector::y_norm()),
+        ];
+        for r in rays {
+            let bvh = w.intersect_world(r);
+            let brute = World::intersect_objects_serial(&all, &r);
+            assert_eq!(bvh.len(), brute.len());
+            assert!(bvh
+                .iter()
+                .zip(brute.iter())
+                .all(|(a, b)| a.t == b.t && std::ptr::eq(a.object, b.object)));
+        }
+    }
+
+    #[test]
+    fn octree_and_bvh_worlds_render_identically() {
+        let camera = Camera::new(11, 11, PI / 2.0).with_transform(Transformation::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::zero(),
+            Vector::y_norm(),
+        ));
+        let without = camera.render(World::default());
+        let with = camera.render(World::default().with_octree(true));
+        assert_eq!(with, without);
+        assert_ne!(without, Canvas::new(11, 11));
+    }
+
+    #[test]
+    fn only_the_index_in_use_is_built() {
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        let w = World::default().with_octree(true);
+        assert!(w.bvh.candidates(&r).is_empty());
+        assert_eq!(w.octree.as_ref().unwrap().candidates(&r), vec![0, 1]);
+        let w = w.with_octree(false);
+        assert!(w.octree.is_none());
+        assert_eq!(w.bvh.candidates(&r).len(), 2);
+    }
+
+    #[test]
+    fn an_emissive_object_glows_once_without_any_light() {
+        let glowing = ObjectBuilder::new_sphere()
+            .with_material(
+                Material::new()
+                    .with_color(Color::black())
+                    .with_specular(0.0)
+                    .with_emission(Color::new(0.5, 0.25, 0.0)),
+            )
+            .build();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        let unlit = World::new().with_objects(vec![glowing.clone()]);
+        assert_eq!(unlit.color_at(r, 5), Color::new(0.5, 0.25, 0.0));
+        let lit = World::new().with_objects(vec![glowing]).with_lights(vec![
+            PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()),
+            PointLight::new(Point::new(0.0, 10.0, -10.0), Color::white()),
+        ]);
+        assert_eq!(lit.color_at(r, 5), Color::new(0.5, 0.25, 0.0));
+    }
+
     #[test]
     fn intersect_the_world_with_a_ray() {
         let w = World::default();
@@ -196,6 +566,37 @@ mod tests {
         assert_eq!(xs[3].t, 6.0);
     }
 
+    #[test]
+    fn picking_the_object_hit_by_a_ray() {
+        let w = World::default();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        assert_eq!(w.pick(r), Some(0));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::y_norm());
+        assert_eq!(w.pick(r), None);
+    }
+
+    #[test]
+    fn picking_a_grouped_object_returns_its_top_level_group() {
+        let mut w = World::default();
+        let s = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().translation(0.0, 0.0, -3.0))
+            .build();
+        w.add_object(ObjectBuilder::new_group().add_child(s).build());
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        assert_eq!(w.pick(r), Some(2));
+    }
+
+    #[test]
+    fn picking_tells_equal_objects_apart() {
+        let s = ObjectBuilder::new_sphere().build();
+        let w = World::new().with_objects(vec![s.clone(), s]);
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        let xs = w.intersect_world(r);
+        let hit = xs.hit().unwrap();
+        let picked = w.pick(r).unwrap();
+        assert!(std::ptr::eq(&w.objects[picked], hit.object));
+    }
+
     #[test]
     fn color_when_ray_misses() {
         let w = World::default();
@@ -204,6 +605,195 @@ mod tests {
         assert_eq!(c, Color::new(0.0, 0.0, 0.0));
     }
 
+    #[test]
+    fn color_when_ray_misses_is_the_background() {
+        let w = World::default().with_background(Color::new(0.2, 0.4, 0.8));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::y_norm());
+        let c = w.color_at(r, 5);
+        assert_eq!(c, Color::new(0.2, 0.4, 0.8));
+    }
+
+    #[test]
+    fn a_diffuse_sphere_is_lit_by_the_environment_from_above() {
+        let mut map = Canvas::new(8, 4);
+        for x in 0..8 {
+            map.write_pixel(x, 0, Color::white());
+            map.write_pixel(x, 1, Color::white());
+        }
+        let sphere = ObjectBuilder::new_sphere()
+            .with_material(Material::new().with_ambient(0.0).with_specular(0.0))
+            .build();
+        let w = World::new()
+            .with_objects(vec![sphere])
+            .with_environment(Environment::new(map));
+        let top = w.color_at(Ray::new(Point::new(0.0, 5.0, 0.0), -Vector::y_norm()), 5);
+        let bottom = w.color_at(Ray::new(Point::new(0.0, -5.0, 0.0), Vector::y_norm()), 5);
+        assert_eq!(top, Color::new(0.9, 0.9, 0.9));
+        assert_eq!(bottom, Color::black());
+    }
+
+    #[test]
+    fn an_ambient_light_fills_shadowed_surfaces() {
+        let blocker = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().translation(0.0, 0.0, 10.0))
+            .build();
+        let sphere = ObjectBuilder::new_sphere()
+            .with_material(
+                Material::new()
+                    .with_pattern(Pattern::new_solid_pattern(Color::new(1.0, 0.5, 0.0)))
+                    .with_ambient(0.5),
+            )
+            .build();
+        let w = World::new()
+            .with_objects(vec![blocker, sphere])
+            .with_lights(vec![
+                Light::from(PointLight::new(Point::new(0.0, 0.0, 20.0), Color::white())),
+                Light::from(AmbientLight::new(Color::new(0.4, 0.4, 0.4))),
+            ]);
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::z_norm());
+        assert_eq!(w.color_at(r, 5), Color::new(0.7, 0.35, 0.0));
+        let miss = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::y_norm());
+        assert_eq!(w.color_at(miss, 5), Color::black());
+    }
+
+    #[test]
+    fn escaping_reflected_ray_picks_up_the_background() {
+        let shape = ObjectBuilder::new_plane()
+            .with_material(Material::new().with_reflective(0.5))
+            .with_transform(Transformation::new_transform().translation(0.0, -1.0, 0.0))
+            .build();
+        let w = World::new()
+            .with_objects(vec![shape.clone()])
+            .with_background(Color::new(0.2, 0.4, 0.8));
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -f64::sqrt(2.0) / 2.0, f64::sqrt(2.0) / 2.0),
+        );
+        let i = Intersection::new(f64::sqrt(2.0), &shape);
+        let mut xs = Intersections::new();
+        xs.push(i);
+        let comps = i.prepare_computations(r, &xs);
+        let color = w.reflected_color(&comps, 5);
+        assert_eq!(color, Color::new(0.1, 0.2, 0.4));
+    }
+
+    #[test]
+    fn a_tinted_reflective_material_tints_its_reflection() {
+        let gold = Color::new(1.0, 0.78, 0.34);
+        let mirror = ObjectBuilder::new_plane()
+            .with_material(
+                Material::new()
+                    .with_reflective(1.0)
+                    .with_reflective_color(gold),
+            )
+            .with_transform(Transformation::new_transform().translation(0.0, -1.0, 0.0))
+            .build();
+        let white = ObjectBuilder::new_sphere()
+            .with_material(
+                Material::new()
+                    .with_color(Color::black())
+                    .with_specular(0.0)
+                    .with_emission(Color::white()),
+            )
+            .with_transform(Transformation::new_transform().translation(0.0, 3.0, 2.0))
+            .build();
+        let w = World::new().with_objects(vec![mirror.clone(), white]);
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -f64::sqrt(2.0) / 2.0, f64::sqrt(2.0) / 2.0),
+        );
+        let i = Intersection::new(f64::sqrt(2.0), &mirror);
+        let mut xs = Intersections::new();
+        xs.push(i);
+        let comps = i.prepare_computations(r, &xs);
+        assert_eq!(w.reflected_color(&comps, 5), gold);
+    }
+
+    #[test]
+    fn a_fresnel_floor_reflects_more_at_grazing_angles() {
+        let floor = ObjectBuilder::new_plane()
+            .with_material(
+                Material::new()
+                    .with_color(Color::black())
+                    .with_reflective(1.0)
+                    .with_refractive_index(1.5)
+                    .with_fresnel(true),
+            )
+            .build();
+        let w = World::new()
+            .with_objects(vec![floor.clone()])
+            .with_background(Color::white());
+        let reflected_at = |origin: Point| {
+            let r = Ray::new(origin, (Point::zero() - origin).normalize());
+            let i = Intersection::new((Point::zero() - origin).magnitude(), &floor);
+            let mut xs = Intersections::new();
+            xs.push(i);
+            let comps = i.prepare_computations(r, &xs);
+            w.shade_hit(comps, 5).as_255_bytes()[0]
+        };
+        let near_normal = reflected_at(Point::new(0.0, 10.0, -0.1));
+        let grazing = reflected_at(Point::new(0.0, 0.1, -10.0));
+        assert_eq!(near_normal, 10);
+        assert!(grazing > 128);
+    }
+
+    #[test]
+    fn a_thin_transparent_plane_does_not_bend_rays() {
+        let pane = |thin| {
+            ObjectBuilder::new_plane()
+                .with_material(
+                    Material::new()
+                        .with_transparency(1.0)
+                        .with_refractive_index(1.5)
+                        .with_thin(thin),
+                )
+                .build()
+        };
+        let target = ObjectBuilder::new_sphere()
+            .with_material(
+                Material::new()
+                    .with_color(Color::black())
+                    .with_specular(0.0)
+                    .with_emission(Color::new(0.2, 0.4, 0.8)),
+            )
+            .with_transform(
+                Transformation::new_transform()
+                    .scaling(0.5, 0.5, 0.5)
+                    .translation(0.0, -2.0, 2.0),
+            )
+            .build();
+        let r = Ray::new(
+            Point::new(0.0, 1.0, -1.0),
+            Vector::new(0.0, -f64::sqrt(2.0) / 2.0, f64::sqrt(2.0) / 2.0),
+        );
+        let refracted_through = |floor: Object| {
+            let w = World::new().with_objects(vec![floor.clone(), target.clone()]);
+            let xs = floor.intersects(&r);
+            let comps = xs[0].prepare_computations(r, &xs);
+            (comps.n1, comps.n2, w.refracted_color(&comps, 5))
+        };
+        assert_eq!(
+            refracted_through(pane(true)),
+            (1.0, 1.0, Color::new(0.2, 0.4, 0.8))
+        );
+        assert_eq!(refracted_through(pane(false)), (1.0, 1.5, Color::black()));
+    }
+
+    #[test]
+    fn a_shadow_bias_keeps_distant_surfaces_from_shadowing_themselves() {
+        let planet = ObjectBuilder::new_sphere()
+            .with_transform(Transformation::new_transform().scaling(1e10, 1e10, 1e10))
+            .build();
+        let light = PointLight::new(Point::new(0.0, 2e10, -2e10), Color::white());
+        let w = World::new()
+            .with_objects(vec![planet])
+            .with_lights(vec![light]);
+        let r = Ray::new(Point::new(0.0, 5.1e9, -5e10), Vector::z_norm());
+        assert_eq!(w.color_at(r, 5), Color::new(0.1, 0.1, 0.1));
+        let w = w.with_shadow_bias(1.0);
+        assert_ne!(w.color_at(r, 5), Color::new(0.1, 0.1, 0.1));
+    }
+
     #[test]
     fn color_when_ray_hits() {
         let w = World::default();
@@ -236,7 +826,7 @@ mod tests {
         let inner = w.objects().get(1).unwrap().clone();
         let inner_color = inner
             .material()
-            .pattern
+            .pattern()
             .pattern_at_object(&inner, Point::new(0.0, 0.0, 0.75));
         let r = Ray::new(Point::new(0.0, 0.0, 0.75), Vector::new(0.0, 0.0, -1.0));
         let c = w.color_at(r, 5);
@@ -247,28 +837,128 @@ mod tests {
     fn there_is_no_shadow_when_nothing_is_collinear_with_point_and_light() {
         let w = World::default();
         let p = Point::new(0.0, 10.0, 0.0);
-        assert_eq!(w.is_shadowed(p), false);
+        assert_eq!(w.is_shadowed(p, &w.lights()[0]), false);
     }
 
     #[test]
     fn the_shadow_when_an_object_is_between_point_and_light() {
         let w = World::default();
         let p = Point::new(10.0, -10.0, 10.0);
-        assert_eq!(w.is_shadowed(p), true);
+        assert_eq!(w.is_shadowed(p, &w.lights()[0]), true);
     }
 
     #[test]
     fn there_is_no_shadow_when_an_object_is_behind_the_light() {
         let w = World::default();
         let p = Point::new(-20.0, 20.0, -20.0);
-        assert_eq!(w.is_shadowed(p), false);
+        assert_eq!(w.is_shadowed(p, &w.lights()[0]), false);
     }
 
     #[test]
     fn there_is_no_shadow_when_an_object_is_behind_the_point() {
         let w = World::default();
         let p = Point::new(-2.0, 2.0, -2.0);
-        assert_eq!(w.is_shadowed(p), false);
+        assert_eq!(w.is_shadowed(p, &w.lights()[0]), false);
+    }
+
+    #[test]
+    fn shadows_are_tested_against_each_light_separately() {
+        let left: Light = PointLight::new(Point::new(-10.0, 0.0, 0.0), Color::white()).into();
+        let right: Light = PointLight::new(Point::new(10.0, 0.0, 0.0), Color::white()).into();
+        let w = World::new()
+            .with_objects(vec![ObjectBuilder::new_sphere().build()])
+            .with_lights(vec![left, right]);
+        let left_side = Point::new(-2.0, 0.0, 0.0);
+        let right_side = Point::new(2.0, 0.0, 0.0);
+        assert!(!w.is_shadowed(left_side, &left));
+        assert!(w.is_shadowed(left_side, &right));
+        assert!(w.is_shadowed(right_side, &left));
+        assert!(!w.is_shadowed(right_side, &right));
+    }
+
+    #[parameterized(
+        fully_shadowed = {Point::new(0.0, 0.0, 2.0), 0.0},
+        quarter_lit = {Point::new(1.0, -1.0, 2.0), 0.25},
+        half_lit = {Point::new(1.5, 0.0, 2.0), 0.5},
+        three_quarters_lit = {Point::new(1.25, 1.25, 3.0), 0.75},
+        fully_lit = {Point::new(0.0, 0.0, -2.0), 1.0},
+    )]
+    fn an_area_light_partially_shadows_a_point(p: Point, expected: f64) {
+        let light = AreaLight::new(
+            Point::new(-0.5, -0.5, -5.0),
+            Vector::x_norm(),
+            2,
+            Vector::y_norm(),
+            2,
+            Color::white(),
+        );
+        let w = World::default().with_lights(vec![light]);
+        assert!(w.intensity_at(p, &w.lights()[0]).approx_eq(expected));
+    }
+
+    #[parameterized(
+        fully_shadowed = {Point::new(0.0, 0.0, 2.0), 0.0},
+        quarter_lit = {Point::new(1.0, -1.0, 2.0), 0.25},
+        half_lit = {Point::new(1.5, 0.0, 2.0), 0.5},
+        fully_lit = {Point::new(0.0, 0.0, -2.0), 1.0},
+    )]
+    fn transparent_shadows_from_an_area_light_stay_soft(p: Point, expected: f64) {
+        let light = AreaLight::new(
+            Point::new(-0.5, -0.5, -5.0),
+            Vector::x_norm(),
+            2,
+            Vector::y_norm(),
+            2,
+            Color::white(),
+        );
+        let w = World::default()
+            .with_lights(vec![light])
+            .with_transparent_shadows(true);
+        assert_eq!(
+            w.shadow_attenuation(p, &w.lights()[0]),
+            Color::white() * expected
+        );
+    }
+
+    #[test]
+    fn a_directional_light_is_blocked_by_objects_at_any_distance() {
+        let light = DirectionalLight::new(Vector::new(0.0, -1.0, 0.0), Color::white());
+        let w = World::default().with_lights(vec![light]);
+        assert!(w.is_shadowed(Point::new(0.0, -1000.0, 0.0), &w.lights()[0]));
+        assert!(!w.is_shadowed(Point::new(0.0, 1000.0, 0.0), &w.lights()[0]));
+        assert!(!w.is_shadowed(Point::new(5.0, -1000.0, 0.0), &w.lights()[0]));
+    }
+
+    #[test]
+    fn transparent_objects_cast_colored_shadows() {
+        let glass = ObjectBuilder::new_sphere()
+            .with_material(
+                Material::new()
+                    .with_pattern(Pattern::new_solid_pattern(Color::red()))
+                    .with_transparency(0.5),
+            )
+            .build();
+        let light: Light = PointLight::new(Point::new(0.0, 10.0, 0.0), Color::white()).into();
+        let w = World::new()
+            .with_objects(vec![glass.clone()])
+            .with_lights(vec![light]);
+        let p = Point::new(0.0, -5.0, 0.0);
+        assert!(w.is_shadowed(p, &w.lights()[0]));
+        assert_eq!(w.shadow_attenuation(p, &light), Color::new(0.25, 0.0, 0.0));
+        let floor = ObjectBuilder::new_plane()
+            .with_transform(Transformation::new_transform().translation(0.0, -5.0, 0.0))
+            .build();
+        let w = w
+            .with_objects(vec![glass, floor.clone()])
+            .with_transparent_shadows(true);
+        let r = Ray::new(
+            Point::new(0.0, -4.0, -1.0),
+            Vector::new(0.0, -1.0, 1.0).normalize(),
+        );
+        let i = Intersection::new(f64::sqrt(2.0), &floor);
+        let comps = i.prepare_computations(r, &Intersections::from_iter([i]));
+        let color = w.shade_hit(comps, 0);
+        assert_eq!(color, Color::new(0.325, 0.1, 0.1));
     }
 
     #[test]
@@ -283,7 +973,7 @@ mod tests {
             .with_objects(vec![object_without_shadow, target_object])
             .with_lights(vec![light]);
         let p = Point::new(0.0, 0.0, 0.0);
-        assert_eq!(w.is_shadowed(p), false);
+        assert_eq!(w.is_shadowed(p, &w.lights()[0]), false);
     }
 
     #[test]
@@ -600,4 +1290,41 @@ mod tests {
         let c = w.shade_hit(comps, 5);
         assert_eq!(c, Color::new(0.93391, 0.69643, 0.69243));
     }
+
+    #[test]
+    fn rays_pass_through_the_cut_out_cells_of_a_pattern() {
+        let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::white());
+        let cutout = ObjectBuilder::new_sphere()
+            .with_material(
+                Material::new().with_pattern(
+                    Pattern::new_checker_pattern(
+                        Pattern::new_solid_pattern(Color::white()),
+                        Pattern::new_solid_pattern(Color::white()).with_opacity(0.0),
+                    )
+                    .with_transform(Transformation::new_transform().translation(0.0, 0.0, 0.5)),
+                ),
+            )
+            .build();
+        let behind = ObjectBuilder::new_plane()
+            .with_transform(
+                Transformation::new_transform()
+                    .rotation_x(PI / 2.0)
+                    .translation(0.0, 0.0, 5.0),
+            )
+            .with_material(
+                Material::new()
+                    .with_color(Color::red())
+                    .with_ambient(1.0)
+                    .with_diffuse(0.0)
+                    .with_specular(0.0),
+            )
+            .build();
+        let w = World::new()
+            .with_lights(vec![light])
+            .with_objects(vec![cutout, behind]);
+        let through_cut_out = Ray::new(Point::new(0.5, -0.5, -5.0), Vector::z_norm());
+        assert_eq!(w.color_at(through_cut_out, 5), Color::red());
+        let on_opaque_cell = Ray::new(Point::new(0.5, 0.5, -5.0), Vector::z_norm());
+        assert_ne!(w.color_at(on_opaque_cell, 5), Color::red());
+    }
 }
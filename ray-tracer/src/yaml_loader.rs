@@ -1,23 +1,41 @@
-use std::{fs, path::Path};
+use std::{
+    f64::consts::FRAC_PI_4,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use approx_eq::ApproxEq;
+use colo_rs::colors::Color;
 
 use serde::{
     de::{self, IntoDeserializer},
-    Deserialize, Deserializer,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 
 use crate::{
     camera::Camera,
-    lights::PointLight,
+    lights::{AmbientLight, AreaLight, DirectionalLight, Light, PointLight, SpotLight},
     materials::Material,
+    matrix::Matrix,
+    obj_parser::OBJParser,
     patterns::Pattern,
     ppm::PPM,
-    shapes::{CSGKind, Cap, Object, ObjectBuilder},
+    shapes::{CSGKind, Cap, Object, ObjectBuilder, Shape},
     transformations::Transformation,
+    tuples::{points::Point, vectors::Vector, Tuple},
     world::World,
 };
 
 trait IntoWithDefines<T> {
-    fn into_with_defines(self, defines: &[Define]) -> T;
+    fn into_with_defines(self, defines: &[Define]) -> Result<T>;
+}
+
+fn find_define<'a>(defines: &'a [Define], id: &str) -> Result<&'a Define> {
+    defines
+        .iter()
+        .find(|def| def.define == id)
+        .ok_or_else(|| anyhow!("Unknown definition '{id}'"))
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,14 +43,21 @@ trait IntoWithDefines<T> {
 enum SceneCommand {
     Add(Add),
     Define(Define),
+    Include(Include),
 }
 
 #[derive(Debug, Deserialize)]
+struct Include {
+    include: PathBuf,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 enum Add {
     AddLight(YamlLight),
     AddObject(YamlObject),
     AddCamera(YamlCamera),
+    AddSettings(YamlSettingsCommand),
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq)]
@@ -41,42 +66,84 @@ struct Define {
     extend: Option<DefinitionId>,
     transform: Option<Vec<YamlTransform>>,
     material: Option<YamlMaterial>,
+    pattern: Option<YamlPattern>,
 }
 
 impl Define {
-    fn expand(&self, defines: &[Define]) -> Self {
-        if let Some(ext_define) = &self.extend {
-            let ext_define = defines
+    fn expand(&self, defines: &[Define]) -> Result<Self> {
+        self.expand_visiting(defines, &mut Vec::new())
+    }
+
+    fn expand_visiting(
+        &self,
+        defines: &[Define],
+        extending: &mut Vec<DefinitionId>,
+    ) -> Result<Self> {
+        if extending.contains(&self.define) {
+            let cycle: Vec<&str> = extending
                 .iter()
-                .find(|def| def.define == *ext_define)
-                .unwrap()
-                .expand(defines);
+                .chain([&self.define])
+                .map(String::as_str)
+                .collect();
+            return Err(anyhow!("Extend cycle: {}", cycle.join(" -> ")));
+        }
+        if let Some(ext_id) = &self.extend {
+            extending.push(self.define.clone());
+            let ext_define = find_define(defines, ext_id)
+                .map_err(|e| anyhow!("{} extends it: {}", self.define, e))?
+                .expand_visiting(defines, extending)?;
+            extending.pop();
+            let missing = |kind: &str| {
+                anyhow!(
+                    "{} extends '{}' but has no {} of its own",
+                    self.define,
+                    ext_id,
+                    kind
+                )
+            };
             if let Some(mut ext_transform) = ext_define.transform {
-                let mut transform = self.transform.clone().unwrap();
+                let mut transform = self.transform.clone().ok_or_else(|| missing("transform"))?;
                 transform.append(&mut ext_transform);
-                Define {
+                Ok(Define {
                     define: self.define.clone(),
                     extend: None,
                     transform: Some(transform),
                     material: None,
-                }
+                    pattern: None,
+                })
             } else if let Some(ext_material) = ext_define.material {
-                Define {
+                Ok(Define {
                     define: self.define.clone(),
                     extend: None,
                     transform: None,
-                    material: Some(ext_material.merge(self.material.clone().unwrap())),
-                }
+                    material: Some(
+                        ext_material
+                            .merge(self.material.clone().ok_or_else(|| missing("material"))?),
+                    ),
+                    pattern: None,
+                })
+            } else if let Some(ext_pattern) = ext_define.pattern {
+                Ok(Define {
+                    define: self.define.clone(),
+                    extend: None,
+                    transform: None,
+                    material: None,
+                    pattern: Some(self.pattern.clone().unwrap_or(ext_pattern)),
+                })
             } else {
-                unreachable!()
+                Err(anyhow!(
+                    "{} extends '{}' but it defines nothing",
+                    self.define,
+                    ext_id
+                ))
             }
         } else {
-            self.clone()
+            Ok(self.clone())
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename = "camera", tag = "add")]
 struct YamlCamera {
     width: usize,
@@ -88,6 +155,20 @@ struct YamlCamera {
     up: [f64; 3],
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "add", rename_all = "kebab-case")]
+enum YamlSettingsCommand {
+    Settings(YamlSettings),
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct YamlSettings {
+    samples: Option<usize>,
+    max_depth: Option<usize>,
+    background: Option<[f64; 3]>,
+}
+
 fn de_fov<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
@@ -112,25 +193,145 @@ impl Into<Camera> for YamlCamera {
     }
 }
 
-#[derive(Debug, Deserialize)]
+fn to_array<T: Tuple>(t: T) -> [f64; 3] {
+    [t.x(), t.y(), t.z()]
+}
+
+impl From<&Camera> for YamlCamera {
+    fn from(camera: &Camera) -> Self {
+        // The rows of the view transform are left, true up and -forward, where
+        // true up only keeps the part of the original up across forward.
+        // Adding back a forward component restores a unit up vector that
+        // rebuilds the same transform.
+        let m = &camera.transform().matrix;
+        let row = |r: usize| Vector::new(m[(r, 0)], m[(r, 1)], m[(r, 2)]);
+        let forward = -row(2);
+        let true_up = row(1);
+        let up = true_up + forward * (1.0 - true_up.dot(true_up)).max(0.0).sqrt();
+        let from = camera.transform().inverse().unwrap() * &Point::zero();
+        Self {
+            width: camera.h_size(),
+            height: camera.v_size(),
+            field_of_view: camera.field_of_view(),
+            from: to_array(from),
+            to: to_array(from + forward),
+            up: to_array(up),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "add", rename_all = "kebab-case")]
 enum YamlLight {
-    #[serde(alias = "point-light")]
-    YamlPointLight { at: [f64; 3], intensity: [f64; 3] },
+    #[serde(rename = "point-light", alias = "yaml-point-light")]
+    Point { at: [f64; 3], intensity: [f64; 3] },
+    #[serde(rename = "spot-light")]
+    Spot {
+        at: [f64; 3],
+        direction: [f64; 3],
+        #[serde(rename = "inner-angle", deserialize_with = "de_fov")]
+        inner_angle: f64,
+        #[serde(rename = "outer-angle", deserialize_with = "de_fov")]
+        outer_angle: f64,
+        intensity: [f64; 3],
+    },
+    #[serde(rename = "directional-light")]
+    Directional {
+        direction: [f64; 3],
+        intensity: [f64; 3],
+    },
+    #[serde(rename = "ambient-light")]
+    Ambient { intensity: [f64; 3] },
+    #[serde(rename = "area-light")]
+    Area {
+        corner: [f64; 3],
+        uvec: [f64; 3],
+        usteps: usize,
+        vvec: [f64; 3],
+        vsteps: usize,
+        intensity: [f64; 3],
+    },
 }
 
-impl Into<crate::lights::PointLight> for YamlLight {
-    fn into(self) -> crate::lights::PointLight {
+impl Into<Light> for YamlLight {
+    fn into(self) -> Light {
         match self {
-            YamlLight::YamlPointLight { at, intensity } => crate::lights::PointLight {
-                position: at.into(),
-                intensity: intensity.into(),
+            YamlLight::Point { at, intensity } => {
+                PointLight::new(at.into(), intensity.into()).into()
+            }
+            YamlLight::Spot {
+                at,
+                direction,
+                inner_angle,
+                outer_angle,
+                intensity,
+            } => SpotLight::new(
+                at.into(),
+                direction.into(),
+                inner_angle,
+                outer_angle,
+                intensity.into(),
+            )
+            .into(),
+            YamlLight::Directional {
+                direction,
+                intensity,
+            } => DirectionalLight::new(direction.into(), intensity.into()).into(),
+            YamlLight::Ambient { intensity } => AmbientLight::new(intensity.into()).into(),
+            YamlLight::Area {
+                corner,
+                uvec,
+                usteps,
+                vvec,
+                vsteps,
+                intensity,
+            } => AreaLight::new(
+                corner.into(),
+                uvec.into(),
+                usteps,
+                vvec.into(),
+                vsteps,
+                intensity.into(),
+            )
+            .into(),
+        }
+    }
+}
+
+impl From<&Light> for YamlLight {
+    fn from(light: &Light) -> Self {
+        match light {
+            Light::Point(l) => YamlLight::Point {
+                at: to_array(l.position()),
+                intensity: l.intensity().into(),
+            },
+            Light::Spot(l) => YamlLight::Spot {
+                at: to_array(l.position),
+                direction: to_array(l.direction),
+                inner_angle: l.inner_angle,
+                outer_angle: l.outer_angle,
+                intensity: l.intensity.into(),
+            },
+            Light::Directional(l) => YamlLight::Directional {
+                direction: to_array(l.direction),
+                intensity: l.intensity.into(),
+            },
+            Light::Ambient(l) => YamlLight::Ambient {
+                intensity: l.intensity.into(),
+            },
+            Light::Area(l) => YamlLight::Area {
+                corner: to_array(l.corner),
+                uvec: to_array(l.uvec * l.usteps as f64),
+                usteps: l.usteps,
+                vvec: to_array(l.vvec * l.vsteps as f64),
+                vsteps: l.vsteps,
+                intensity: l.intensity.into(),
             },
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "add", rename_all = "kebab-case")]
 enum YamlObject {
     Test {
@@ -146,20 +347,28 @@ enum YamlObject {
         transform: Option<Vec<TransformOrReference>>,
     },
     Cone {
+        #[serde(skip_serializing_if = "Option::is_none")]
         min: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         max: Option<f64>,
         cap: Option<YamlCap>,
+        #[serde(rename = "half-angle", skip_serializing_if = "Option::is_none")]
+        half_angle: Option<f64>,
         material: Option<MaterialOrReference>,
         transform: Option<Vec<TransformOrReference>>,
     },
     Cylinder {
+        #[serde(skip_serializing_if = "Option::is_none")]
         min: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         max: Option<f64>,
         cap: Option<YamlCap>,
         material: Option<MaterialOrReference>,
         transform: Option<Vec<TransformOrReference>>,
     },
     Plane {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        extent: Option<[f64; 2]>,
         material: Option<MaterialOrReference>,
         transform: Option<Vec<TransformOrReference>>,
     },
@@ -191,21 +400,48 @@ enum YamlObject {
         right: Box<YamlObject>,
         transform: Option<Vec<TransformOrReference>>,
     },
+    Obj {
+        file: PathBuf,
+        material: Option<MaterialOrReference>,
+        transform: Option<Vec<TransformOrReference>>,
+    },
 }
 
-impl IntoWithDefines<Object> for YamlObject {
-    fn into_with_defines(self, defines: &[Define]) -> Object {
+impl YamlObject {
+    fn name(&self) -> &'static str {
         match self {
+            YamlObject::Test { .. } => "test",
+            YamlObject::Sphere { .. } => "sphere",
+            YamlObject::Cube { .. } => "cube",
+            YamlObject::Cone { .. } => "cone",
+            YamlObject::Cylinder { .. } => "cylinder",
+            YamlObject::Plane { .. } => "plane",
+            YamlObject::Triangle { .. } => "triangle",
+            YamlObject::SmoothTriangle { .. } => "smooth-triangle",
+            YamlObject::Group { .. } => "group",
+            YamlObject::CSG { .. } => "csg",
+            YamlObject::Obj { .. } => "obj",
+        }
+    }
+
+    fn into_object(self, defines: &[Define], resources: &Path) -> Result<Object> {
+        let name = self.name();
+        self.build_object(defines, resources)
+            .map_err(|e| anyhow!("{name}: {e}"))
+    }
+
+    fn build_object(self, defines: &[Define], resources: &Path) -> Result<Object> {
+        let object = match self {
             YamlObject::Test {
                 material,
                 transform,
             } => {
                 let mut builder = ObjectBuilder::new_test_shape();
                 if let Some(material) = material {
-                    builder = builder.with_material(material.into_with_defines(defines));
+                    builder = builder.with_material(material.into_with_defines(defines)?);
                 };
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
@@ -215,10 +451,10 @@ impl IntoWithDefines<Object> for YamlObject {
             } => {
                 let mut builder = ObjectBuilder::new_sphere();
                 if let Some(material) = material {
-                    builder = builder.with_material(material.into_with_defines(defines));
+                    builder = builder.with_material(material.into_with_defines(defines)?);
                 };
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
@@ -228,10 +464,10 @@ impl IntoWithDefines<Object> for YamlObject {
             } => {
                 let mut builder = ObjectBuilder::new_cube();
                 if let Some(material) = material {
-                    builder = builder.with_material(material.into_with_defines(defines));
+                    builder = builder.with_material(material.into_with_defines(defines)?);
                 };
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
@@ -239,10 +475,14 @@ impl IntoWithDefines<Object> for YamlObject {
                 min,
                 max,
                 cap,
+                half_angle,
                 material,
                 transform,
             } => {
                 let mut builder = ObjectBuilder::new_cone();
+                if let Some(half_angle) = half_angle {
+                    builder = builder.with_half_angle(half_angle);
+                };
                 if let Some(min) = min {
                     builder = builder.with_min(min);
                 };
@@ -253,10 +493,10 @@ impl IntoWithDefines<Object> for YamlObject {
                     builder = builder.with_cap(cap.into());
                 };
                 if let Some(material) = material {
-                    builder = builder.with_material(material.into_with_defines(defines));
+                    builder = builder.with_material(material.into_with_defines(defines)?);
                 };
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
@@ -278,23 +518,27 @@ impl IntoWithDefines<Object> for YamlObject {
                     builder = builder.with_cap(cap.into());
                 };
                 if let Some(material) = material {
-                    builder = builder.with_material(material.into_with_defines(defines));
+                    builder = builder.with_material(material.into_with_defines(defines)?);
                 };
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
             YamlObject::Plane {
+                extent,
                 material,
                 transform,
             } => {
                 let mut builder = ObjectBuilder::new_plane();
+                if let Some([half_x, half_z]) = extent {
+                    builder = builder.with_extent(half_x, half_z);
+                };
                 if let Some(material) = material {
-                    builder = builder.with_material(material.into_with_defines(defines));
+                    builder = builder.with_material(material.into_with_defines(defines)?);
                 };
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
@@ -310,10 +554,10 @@ impl IntoWithDefines<Object> for YamlObject {
                     .set_p2(p2.into())
                     .set_p3(p3.into());
                 if let Some(material) = material {
-                    builder = builder.with_material(material.into_with_defines(defines));
+                    builder = builder.with_material(material.into_with_defines(defines)?);
                 };
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
@@ -335,10 +579,10 @@ impl IntoWithDefines<Object> for YamlObject {
                     .set_n2(n2.into())
                     .set_n3(n3.into());
                 if let Some(material) = material {
-                    builder = builder.with_material(material.into_with_defines(defines));
+                    builder = builder.with_material(material.into_with_defines(defines)?);
                 };
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
@@ -346,13 +590,16 @@ impl IntoWithDefines<Object> for YamlObject {
                 children,
                 transform,
             } => {
-                let mut builder = children
-                    .into_iter()
-                    .fold(ObjectBuilder::new_group(), |b, child| {
-                        b.add_child(child.into_with_defines(defines))
-                    });
+                let mut builder =
+                    children
+                        .into_iter()
+                        .try_fold(ObjectBuilder::new_group(), |b, child| {
+                            Ok::<_, anyhow::Error>(
+                                b.add_child(child.into_object(defines, resources)?),
+                            )
+                        })?;
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
@@ -364,19 +611,113 @@ impl IntoWithDefines<Object> for YamlObject {
             } => {
                 let mut builder = ObjectBuilder::new_csg(
                     kind.into(),
-                    (*left).into_with_defines(defines),
-                    (*right).into_with_defines(defines),
+                    left.into_object(defines, resources)?,
+                    right.into_object(defines, resources)?,
                 );
                 if let Some(transform) = transform {
-                    builder = builder.with_transform(transform.into_with_defines(defines));
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
                 };
                 builder.build()
             }
-        }
+            YamlObject::Obj {
+                file,
+                material,
+                transform,
+            } => {
+                let mut builder = OBJParser::load_file(&resources.join(file))?.into_group();
+                if let Some(material) = material {
+                    builder = builder.with_material(material.into_with_defines(defines)?);
+                };
+                if let Some(transform) = transform {
+                    builder = builder.with_transform(transform.into_with_defines(defines)?);
+                };
+                builder.build()
+            }
+        };
+        Ok(object)
     }
 }
 
-#[derive(Debug, Deserialize)]
+impl TryFrom<&Object> for YamlObject {
+    type Error = anyhow::Error;
+
+    fn try_from(object: &Object) -> Result<Self> {
+        let material = Some(MaterialOrReference::Material(Box::new(
+            object.material().try_into()?,
+        )));
+        let transform = Some(object.transform().into());
+        let finite = |v: f64| v.is_finite().then_some(v);
+        let yaml_object = match object.shape() {
+            Shape::TestShape(_) => YamlObject::Test {
+                material,
+                transform,
+            },
+            Shape::Sphere(_) => YamlObject::Sphere {
+                material,
+                transform,
+            },
+            Shape::Cube(_) => YamlObject::Cube {
+                material,
+                transform,
+            },
+            Shape::Plane(p) => YamlObject::Plane {
+                extent: p.extent().map(|(half_x, half_z)| [half_x, half_z]),
+                material,
+                transform,
+            },
+            Shape::Cone(c) => YamlObject::Cone {
+                min: finite(c.min()),
+                max: finite(c.max()),
+                cap: Some(c.cap().into()),
+                half_angle: Some(c.half_angle()).filter(|&angle| !angle.approx_eq(FRAC_PI_4)),
+                material,
+                transform,
+            },
+            Shape::Cylinder(c) => YamlObject::Cylinder {
+                min: finite(c.min()),
+                max: finite(c.max()),
+                cap: Some(c.cap().into()),
+                material,
+                transform,
+            },
+            Shape::Triangle(t) => YamlObject::Triangle {
+                p1: to_array(t.p1()),
+                p2: to_array(t.p2()),
+                p3: to_array(t.p3()),
+                material,
+                transform,
+            },
+            Shape::SmoothTriangle(t) => YamlObject::SmoothTriangle {
+                p1: to_array(t.p1()),
+                p2: to_array(t.p2()),
+                p3: to_array(t.p3()),
+                n1: to_array(t.n1()),
+                n2: to_array(t.n2()),
+                n3: to_array(t.n3()),
+                material,
+                transform,
+            },
+            Shape::Group(g) => YamlObject::Group {
+                children: g
+                    .children()
+                    .iter()
+                    .map(YamlObject::try_from)
+                    .collect::<Result<_>>()?,
+                transform,
+            },
+            Shape::CSG(csg) => YamlObject::CSG {
+                kind: csg.kind().into(),
+                left: Box::new(csg.left().try_into()?),
+                right: Box::new(csg.right().try_into()?),
+                transform,
+            },
+            shape => return Err(anyhow!("Cannot save {shape:?} to YAML")),
+        };
+        Ok(yaml_object)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 enum YamlCap {
     Uncapped,
@@ -396,7 +737,18 @@ impl Into<Cap> for YamlCap {
     }
 }
 
-#[derive(Debug, Deserialize)]
+impl From<&Cap> for YamlCap {
+    fn from(cap: &Cap) -> Self {
+        match cap {
+            Cap::Uncapped => YamlCap::Uncapped,
+            Cap::TopCap => YamlCap::TopCap,
+            Cap::BottomCap => YamlCap::BottomCap,
+            Cap::Both => YamlCap::Both,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 enum YamlCSGKind {
     Union,
@@ -414,6 +766,16 @@ impl Into<CSGKind> for YamlCSGKind {
     }
 }
 
+impl From<&CSGKind> for YamlCSGKind {
+    fn from(kind: &CSGKind) -> Self {
+        match kind {
+            CSGKind::Union => YamlCSGKind::Union,
+            CSGKind::Intersection => YamlCSGKind::Intersection,
+            CSGKind::Difference => YamlCSGKind::Difference,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum TransformOrReference {
     Transform(YamlTransform),
@@ -431,7 +793,8 @@ impl<'de> Deserialize<'de> for TransformOrReference {
         match expr {
             Value::String(s) => Ok(TransformOrReference::Reference(s)),
             Value::Sequence(seq) => {
-                let transform = YamlTransform::deserialize(seq.into_deserializer()).unwrap();
+                let transform = YamlTransform::deserialize(seq.into_deserializer())
+                    .map_err(de::Error::custom)?;
                 Ok(TransformOrReference::Transform(transform))
             }
             _ => Err(de::Error::custom(format!(
@@ -442,31 +805,58 @@ impl<'de> Deserialize<'de> for TransformOrReference {
     }
 }
 
+impl Serialize for TransformOrReference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            TransformOrReference::Transform(t) => t.serialize(serializer),
+            TransformOrReference::Reference(r) => r.serialize(serializer),
+        }
+    }
+}
+
 impl IntoWithDefines<Transformation> for TransformOrReference {
-    fn into_with_defines(self, defines: &[Define]) -> Transformation {
+    fn into_with_defines(self, defines: &[Define]) -> Result<Transformation> {
         match self {
-            TransformOrReference::Transform(t) => t.into(),
+            TransformOrReference::Transform(t) => Ok(t.into()),
             TransformOrReference::Reference(r) => {
-                let define = defines.iter().find(|def| def.define == r).unwrap();
-                let transforms = define.transform.clone().unwrap();
-                transforms.into()
+                let define = find_define(defines, &r)?;
+                let transforms = define
+                    .transform
+                    .clone()
+                    .ok_or_else(|| anyhow!("Definition '{r}' is not a transform"))?;
+                Ok(transforms.into())
             }
         }
     }
 }
 
 impl IntoWithDefines<Transformation> for Vec<TransformOrReference> {
-    fn into_with_defines(self, defines: &[Define]) -> Transformation {
+    fn into_with_defines(self, defines: &[Define]) -> Result<Transformation> {
         let transformations: Vec<Transformation> = self
             .into_iter()
             .map(|tor| tor.into_with_defines(defines))
-            .collect();
+            .collect::<Result<_>>()?;
         let transformation = transformations
             .iter()
             .fold(Transformation::new_transform(), |t, m| {
                 Transformation::from(&m.matrix * &t.matrix)
             });
-        transformation
+        Ok(transformation)
+    }
+}
+
+impl From<&Transformation> for Vec<TransformOrReference> {
+    fn from(transformation: &Transformation) -> Self {
+        let m = &transformation.matrix;
+        let values = (0..4)
+            .flat_map(|row| (0..4).map(move |col| m[(row, col)]))
+            .collect();
+        vec![TransformOrReference::Transform(YamlTransform::Matrix {
+            values,
+        })]
     }
 }
 
@@ -511,6 +901,9 @@ enum YamlTransform {
         zx: f64,
         zy: f64,
     },
+    Matrix {
+        values: Vec<f64>,
+    },
 }
 
 impl<'de> Deserialize<'de> for YamlTransform {
@@ -529,6 +922,7 @@ impl<'de> Deserialize<'de> for YamlTransform {
             RotateY,
             RotateZ,
             Shear,
+            Matrix,
         }
 
         let expr = Vec::<Value>::deserialize(deserializer)?;
@@ -574,7 +968,7 @@ impl<'de> Deserialize<'de> for YamlTransform {
 
             Op::RotateX => match operands {
                 [Value::String(expr)] => YamlTransform::RotateX {
-                    angle: meval::eval_str(expr).unwrap(),
+                    angle: meval::eval_str(expr).map_err(de::Error::custom)?,
                 },
                 [Value::Number(angle)] => YamlTransform::RotateX {
                     angle: angle.as_f64().unwrap(),
@@ -588,7 +982,7 @@ impl<'de> Deserialize<'de> for YamlTransform {
 
             Op::RotateY => match operands {
                 [Value::String(expr)] => YamlTransform::RotateY {
-                    angle: meval::eval_str(expr).unwrap(),
+                    angle: meval::eval_str(expr).map_err(de::Error::custom)?,
                 },
                 [Value::Number(angle)] => YamlTransform::RotateY {
                     angle: angle.as_f64().unwrap(),
@@ -602,7 +996,7 @@ impl<'de> Deserialize<'de> for YamlTransform {
 
             Op::RotateZ => match operands {
                 [Value::String(expr)] => YamlTransform::RotateZ {
-                    angle: meval::eval_str(expr).unwrap(),
+                    angle: meval::eval_str(expr).map_err(de::Error::custom)?,
                 },
                 [Value::Number(angle)] => YamlTransform::RotateZ {
                     angle: angle.as_f64().unwrap(),
@@ -631,6 +1025,19 @@ impl<'de> Deserialize<'de> for YamlTransform {
                     )))
                 }
             },
+
+            Op::Matrix => match operands
+                .iter()
+                .map(Value::as_f64)
+                .collect::<Option<Vec<f64>>>()
+            {
+                Some(values) if values.len() == 16 => YamlTransform::Matrix { values },
+                _ => {
+                    return Err(de::Error::custom(format!(
+                        "Invalid transform matrix operands: {operands:?}",
+                    )))
+                }
+            },
         };
 
         Ok(transform)
@@ -655,13 +1062,42 @@ impl Into<Transformation> for YamlTransform {
                 zx,
                 zy,
             } => Transformation::new_transform().shearing(xy, xz, yx, yz, zx, zy),
+            YamlTransform::Matrix { values } => Transformation::from(Matrix::from(values, 4)),
         }
     }
 }
 
+impl Serialize for YamlTransform {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (op, operands) = match self {
+            YamlTransform::Translate { x, y, z } => ("translate", vec![*x, *y, *z]),
+            YamlTransform::Scale { x, y, z } => ("scale", vec![*x, *y, *z]),
+            YamlTransform::RotateX { angle } => ("rotate-x", vec![*angle]),
+            YamlTransform::RotateY { angle } => ("rotate-y", vec![*angle]),
+            YamlTransform::RotateZ { angle } => ("rotate-z", vec![*angle]),
+            YamlTransform::Shear {
+                xy,
+                xz,
+                yx,
+                yz,
+                zx,
+                zy,
+            } => ("shear", vec![*xy, *xz, *yx, *yz, *zx, *zy]),
+            YamlTransform::Matrix { values } => ("matrix", values.clone()),
+        };
+        let expr: Vec<serde_yml::Value> = std::iter::once(op.into())
+            .chain(operands.into_iter().map(Into::into))
+            .collect();
+        expr.serialize(serializer)
+    }
+}
+
 #[derive(Debug)]
 enum MaterialOrReference {
-    Material(YamlMaterial),
+    Material(Box<YamlMaterial>),
     Reference(DefinitionId),
 }
 
@@ -676,7 +1112,8 @@ impl<'de> Deserialize<'de> for MaterialOrReference {
         match expr {
             Value::String(s) => Ok(MaterialOrReference::Reference(s)),
             Value::Mapping(map) => {
-                let material = serde_yml::from_value(Value::Mapping(map)).unwrap();
+                let material =
+                    serde_yml::from_value(Value::Mapping(map)).map_err(de::Error::custom)?;
                 Ok(MaterialOrReference::Material(material))
             }
             _ => Err(de::Error::custom(format!(
@@ -687,23 +1124,39 @@ impl<'de> Deserialize<'de> for MaterialOrReference {
     }
 }
 
+impl Serialize for MaterialOrReference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            MaterialOrReference::Material(m) => m.serialize(serializer),
+            MaterialOrReference::Reference(r) => r.serialize(serializer),
+        }
+    }
+}
+
 impl IntoWithDefines<Material> for MaterialOrReference {
-    fn into_with_defines(self, defines: &[Define]) -> Material {
+    fn into_with_defines(self, defines: &[Define]) -> Result<Material> {
         match self {
-            MaterialOrReference::Material(m) => m.into_with_defines(defines),
+            MaterialOrReference::Material(m) => (*m).into_with_defines(defines),
             MaterialOrReference::Reference(r) => {
-                let define = defines.iter().find(|def| def.define == r).unwrap();
-                let material = define.material.clone().unwrap();
+                let define = find_define(defines, &r)?;
+                let material = define
+                    .material
+                    .clone()
+                    .ok_or_else(|| anyhow!("Definition '{r}' is not a material"))?;
                 material.into_with_defines(defines)
             }
         }
     }
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 struct YamlMaterial {
-    pattern: Option<YamlPattern>,
+    pattern: Option<PatternOrReference>,
+    color: Option<[f64; 3]>,
     diffuse: Option<f64>,
     ambient: Option<f64>,
     specular: Option<f64>,
@@ -718,7 +1171,12 @@ struct YamlMaterial {
 impl YamlMaterial {
     fn merge(&self, other: YamlMaterial) -> YamlMaterial {
         YamlMaterial {
-            pattern: other.pattern.or(self.pattern.clone()),
+            pattern: if other.color.is_some() && other.pattern.is_none() {
+                None
+            } else {
+                other.pattern.or(self.pattern.clone())
+            },
+            color: other.color.or(self.color),
             diffuse: other.diffuse.or(self.diffuse),
             ambient: other.ambient.or(self.ambient),
             specular: other.specular.or(self.specular),
@@ -733,11 +1191,19 @@ impl YamlMaterial {
 }
 
 impl IntoWithDefines<Material> for YamlMaterial {
-    fn into_with_defines(self, defines: &[Define]) -> Material {
+    fn into_with_defines(self, defines: &[Define]) -> Result<Material> {
+        let pattern = self
+            .pattern
+            .map(|pattern| pattern.into_with_defines(defines))
+            .transpose()?;
         let material = Material::new();
         let material = [
-            self.pattern.map(|pattern| {
-                Box::new(move |m: Material| m.with_pattern(pattern.into_with_defines(defines)))
+            self.color.map(|color| {
+                Box::new(move |m: Material| m.with_color(color.into()))
+                    as Box<dyn FnOnce(Material) -> Material>
+            }),
+            pattern.map(|pattern| {
+                Box::new(move |m: Material| m.with_pattern(pattern))
                     as Box<dyn FnOnce(Material) -> Material>
             }),
             self.diffuse.map(|diffuse| {
@@ -780,11 +1246,67 @@ impl IntoWithDefines<Material> for YamlMaterial {
         .into_iter()
         .flatten()
         .fold(material, |m, f| f(m));
-        material
+        Ok(material)
     }
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+impl TryFrom<&Material> for YamlMaterial {
+    type Error = anyhow::Error;
+
+    fn try_from(material: &Material) -> Result<Self> {
+        let color = material
+            .pattern()
+            .solid_color()
+            .ok_or_else(|| anyhow!("Only solid patterns can be saved to YAML"))?;
+        let defaults = Material::new();
+        let unsupported: Vec<&str> = [
+            ("emission", material.emission() != defaults.emission()),
+            (
+                "reflective-color",
+                material.reflective_color() != defaults.reflective_color(),
+            ),
+            ("fresnel", material.fresnel() != defaults.fresnel()),
+            ("thin", material.thin() != defaults.thin()),
+            (
+                "anisotropy",
+                material.anisotropy() != defaults.anisotropy()
+                    || material.anisotropy_direction() != defaults.anisotropy_direction(),
+            ),
+            ("dispersion", material.dispersion() != defaults.dispersion()),
+            (
+                "pattern opacity",
+                material.pattern() != &Pattern::new_solid_pattern(color),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, differs)| *differs)
+        .map(|(field, _)| field)
+        .collect();
+        if !unsupported.is_empty() {
+            return Err(anyhow!(
+                "Material fields cannot be saved to YAML: {}",
+                unsupported.join(", ")
+            ));
+        }
+        Ok(YamlMaterial {
+            pattern: Some(PatternOrReference::Pattern(YamlPattern::Solid {
+                color: color.into(),
+            })),
+            color: None,
+            diffuse: Some(material.diffuse()),
+            ambient: Some(material.ambient()),
+            specular: Some(material.specular()),
+            shininess: Some(material.shininess()),
+            reflective: Some(material.reflective()),
+            transparency: Some(material.transparency()),
+            refractive_index: Some(material.refractive_index()),
+            cast_shadows: Some(material.cast_shadows()),
+            receive_shadows: Some(material.receive_shadows()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "kind", rename_all = "kebab-case")]
 enum YamlPattern {
     Test,
@@ -792,18 +1314,18 @@ enum YamlPattern {
         color: [f64; 3],
     },
     Striped {
-        a: Box<YamlPattern>,
-        b: Box<YamlPattern>,
+        a: Box<PatternOrReference>,
+        b: Box<PatternOrReference>,
         transform: Option<Vec<TransformOrReference>>,
     },
     Checker {
-        a: Box<YamlPattern>,
-        b: Box<YamlPattern>,
+        a: Box<PatternOrReference>,
+        b: Box<PatternOrReference>,
         transform: Option<Vec<TransformOrReference>>,
     },
     Ring {
-        a: Box<YamlPattern>,
-        b: Box<YamlPattern>,
+        a: Box<PatternOrReference>,
+        b: Box<PatternOrReference>,
         transform: Option<Vec<TransformOrReference>>,
     },
     LinearGradient {
@@ -812,81 +1334,164 @@ enum YamlPattern {
         transform: Option<Vec<TransformOrReference>>,
     },
     Blend {
-        a: Box<YamlPattern>,
-        b: Box<YamlPattern>,
+        a: Box<PatternOrReference>,
+        b: Box<PatternOrReference>,
+        weight: Option<f64>,
         transform: Option<Vec<TransformOrReference>>,
     },
     Perturbed {
-        p: Box<YamlPattern>,
+        p: Box<PatternOrReference>,
+        transform: Option<Vec<TransformOrReference>>,
+    },
+    Noise {
+        a: [f64; 3],
+        b: [f64; 3],
+        scale: f64,
         transform: Option<Vec<TransformOrReference>>,
     },
 }
 
 impl IntoWithDefines<Pattern> for YamlPattern {
-    fn into_with_defines(self, defines: &[Define]) -> Pattern {
-        match self {
+    fn into_with_defines(self, defines: &[Define]) -> Result<Pattern> {
+        let pattern = match self {
             YamlPattern::Test => Pattern::new_test_pattern(),
             YamlPattern::Solid { color } => Pattern::new_solid_pattern(color.into()),
             YamlPattern::Striped { a, b, transform } => {
                 let mut pattern = Pattern::new_striped_pattern(
-                    a.into_with_defines(defines),
-                    b.into_with_defines(defines),
+                    a.into_with_defines(defines)?,
+                    b.into_with_defines(defines)?,
                 );
                 if let Some(transform) = transform {
-                    pattern = pattern.with_transform(transform.into_with_defines(defines));
+                    pattern = pattern.with_transform(transform.into_with_defines(defines)?);
                 }
                 pattern
             }
             YamlPattern::Checker { a, b, transform } => {
                 let mut pattern = Pattern::new_checker_pattern(
-                    a.into_with_defines(defines),
-                    b.into_with_defines(defines),
+                    a.into_with_defines(defines)?,
+                    b.into_with_defines(defines)?,
                 );
                 if let Some(transform) = transform {
-                    pattern = pattern.with_transform(transform.into_with_defines(defines));
+                    pattern = pattern.with_transform(transform.into_with_defines(defines)?);
                 }
                 pattern
             }
             YamlPattern::Ring { a, b, transform } => {
                 let mut pattern = Pattern::new_ring_pattern(
-                    a.into_with_defines(defines),
-                    b.into_with_defines(defines),
+                    a.into_with_defines(defines)?,
+                    b.into_with_defines(defines)?,
                 );
                 if let Some(transform) = transform {
-                    pattern = pattern.with_transform(transform.into_with_defines(defines));
+                    pattern = pattern.with_transform(transform.into_with_defines(defines)?);
                 }
                 pattern
             }
             YamlPattern::LinearGradient { a, b, transform } => {
                 let mut pattern = Pattern::new_linear_gradient(a.into(), b.into());
                 if let Some(transform) = transform {
-                    pattern = pattern.with_transform(transform.into_with_defines(defines));
+                    pattern = pattern.with_transform(transform.into_with_defines(defines)?);
                 }
                 pattern
             }
-            YamlPattern::Blend { a, b, transform } => {
-                let mut pattern = Pattern::new_blending_pattern(
-                    a.into_with_defines(defines),
-                    b.into_with_defines(defines),
+            YamlPattern::Blend {
+                a,
+                b,
+                weight,
+                transform,
+            } => {
+                let mut pattern = Pattern::new_blending_pattern_weighted(
+                    a.into_with_defines(defines)?,
+                    b.into_with_defines(defines)?,
+                    weight.unwrap_or(0.5),
                 );
                 if let Some(transform) = transform {
-                    pattern = pattern.with_transform(transform.into_with_defines(defines));
+                    pattern = pattern.with_transform(transform.into_with_defines(defines)?);
                 }
                 pattern
             }
             YamlPattern::Perturbed { p, transform } => {
-                let mut pattern = Pattern::new_perturbed_pattern(p.into_with_defines(defines));
+                let mut pattern = Pattern::new_perturbed_pattern(p.into_with_defines(defines)?);
                 if let Some(transform) = transform {
-                    pattern = pattern.with_transform(transform.into_with_defines(defines));
+                    pattern = pattern.with_transform(transform.into_with_defines(defines)?);
                 }
                 pattern
             }
+            YamlPattern::Noise {
+                a,
+                b,
+                scale,
+                transform,
+            } => {
+                let mut pattern = Pattern::new_noise_pattern(a.into(), b.into(), scale);
+                if let Some(transform) = transform {
+                    pattern = pattern.with_transform(transform.into_with_defines(defines)?);
+                }
+                pattern
+            }
+        };
+        Ok(pattern)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PatternOrReference {
+    Pattern(YamlPattern),
+    Reference(DefinitionId),
+}
+
+impl<'de> Deserialize<'de> for PatternOrReference {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde_yml::Value;
+
+        let expr = Value::deserialize(deserializer)?;
+        match expr {
+            Value::String(s) => Ok(PatternOrReference::Reference(s)),
+            Value::Mapping(map) => {
+                let pattern =
+                    serde_yml::from_value(Value::Mapping(map)).map_err(de::Error::custom)?;
+                Ok(PatternOrReference::Pattern(pattern))
+            }
+            _ => Err(de::Error::custom(format!(
+                "Invalid value for pattern : {:?}",
+                expr
+            ))),
+        }
+    }
+}
+
+impl Serialize for PatternOrReference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            PatternOrReference::Pattern(p) => p.serialize(serializer),
+            PatternOrReference::Reference(r) => r.serialize(serializer),
         }
     }
 }
 
-impl IntoWithDefines<Pattern> for Box<YamlPattern> {
-    fn into_with_defines(self, defines: &[Define]) -> Pattern {
+impl IntoWithDefines<Pattern> for PatternOrReference {
+    fn into_with_defines(self, defines: &[Define]) -> Result<Pattern> {
+        match self {
+            PatternOrReference::Pattern(p) => p.into_with_defines(defines),
+            PatternOrReference::Reference(r) => {
+                let define = find_define(defines, &r)?;
+                let pattern = define
+                    .pattern
+                    .clone()
+                    .ok_or_else(|| anyhow!("Definition '{r}' is not a pattern"))?;
+                pattern.into_with_defines(defines)
+            }
+        }
+    }
+}
+
+impl IntoWithDefines<Pattern> for Box<PatternOrReference> {
+    fn into_with_defines(self, defines: &[Define]) -> Result<Pattern> {
         (*self).into_with_defines(defines)
     }
 }
@@ -895,8 +1500,9 @@ type DefinitionId = String;
 
 pub struct YamlLoader {
     camera: Camera,
-    lights: Vec<PointLight>,
+    lights: Vec<Light>,
     objects: Vec<Object>,
+    background: Option<Color>,
 }
 
 fn extract_commands(
@@ -906,49 +1512,193 @@ fn extract_commands(
     Vec<YamlLight>,
     Vec<YamlObject>,
     Vec<Define>,
+    YamlSettings,
 ) {
     scene.into_iter().fold(
-        (None, Vec::new(), Vec::new(), Vec::new()),
-        |(mut camera, mut lights, mut objects, mut defines), command| {
+        (
+            None,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            YamlSettings::default(),
+        ),
+        |(mut camera, mut lights, mut objects, mut defines, mut settings), command| {
             match command {
                 SceneCommand::Add(Add::AddCamera(c)) => camera = Some(c),
+                SceneCommand::Add(Add::AddSettings(YamlSettingsCommand::Settings(s))) => {
+                    settings = s
+                }
                 SceneCommand::Add(Add::AddLight(l)) => lights.push(l),
                 SceneCommand::Add(Add::AddObject(o)) => objects.push(o),
                 SceneCommand::Define(d) => defines.push(d),
+                SceneCommand::Include(_) => {}
             }
-            (camera, lights, objects, defines)
+            (camera, lights, objects, defines, settings)
         },
     )
 }
 
+fn read_scene(path: &Path, including: &mut Vec<PathBuf>) -> Result<Vec<SceneCommand>> {
+    let yaml_str = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read scene {}: {}", path.display(), e))?;
+    let canonical = path.canonicalize()?;
+    if including.contains(&canonical) {
+        let cycle: Vec<String> = including
+            .iter()
+            .chain([&canonical])
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(anyhow!("Include cycle: {}", cycle.join(" -> ")));
+    }
+    let scene: Vec<SceneCommand> = serde_yml::from_str(yaml_str.as_str())
+        .map_err(|e| anyhow!("Cannot parse scene {}: {}", path.display(), e))?;
+
+    including.push(canonical);
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let mut commands = Vec::new();
+    for command in scene {
+        match command {
+            SceneCommand::Include(include) => {
+                commands.extend(read_scene(&parent.join(include.include), including)?)
+            }
+            command => commands.push(command),
+        }
+    }
+    including.pop();
+    Ok(commands)
+}
+
 impl YamlLoader {
     pub fn from(path: &Path) -> Self {
-        let yaml_str = fs::read_to_string(path).unwrap();
-        let scene: Vec<SceneCommand> = serde_yml::from_str(yaml_str.as_str()).unwrap();
+        Self::load(path).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        let (camera, lights, objects, defines) = extract_commands(scene);
-        let defines: Vec<Define> = defines.iter().map(|def| def.expand(&defines)).collect();
-        let camera: Camera = camera.unwrap().into();
-        let lights: Vec<PointLight> = lights.into_iter().map(Into::into).collect();
+    pub fn load(path: &Path) -> Result<Self> {
+        let resources = path.parent().unwrap_or(Path::new(""));
+        Self::load_with_resources(path, resources)
+    }
+
+    pub fn load_with_resources(path: &Path, resources: &Path) -> Result<Self> {
+        let scene = read_scene(path, &mut Vec::new())?;
+
+        let (camera, lights, objects, defines, settings) = extract_commands(scene);
+        let defines: Vec<Define> = defines
+            .iter()
+            .map(|def| def.expand(&defines))
+            .collect::<Result<_>>()
+            .map_err(|e| anyhow!("Cannot load scene {}: {}", path.display(), e))?;
+        let camera = camera.ok_or(anyhow!("Scene {} has no camera", path.display()))?;
+        if camera.width == 0 || camera.height == 0 {
+            return Err(anyhow!(
+                "Scene {} has an empty camera ({}x{})",
+                path.display(),
+                camera.width,
+                camera.height
+            ));
+        }
+        let mut camera: Camera = camera.into();
+        if let Some(samples) = settings.samples {
+            camera = camera.with_samples(samples);
+        }
+        if let Some(max_depth) = settings.max_depth {
+            camera = camera.with_max_depth(max_depth);
+        }
+        let background = settings.background.map(Color::from);
+        let lights: Vec<Light> = lights.into_iter().map(Into::into).collect();
         let objects: Vec<Object> = objects
             .into_iter()
-            .map(|o| o.into_with_defines(&defines))
-            .collect();
-        Self {
+            .map(|o| o.into_object(&defines, resources))
+            .collect::<Result<_>>()
+            .map_err(|e| anyhow!("Cannot load scene {}: {}", path.display(), e))?;
+        Ok(Self {
             camera,
             lights,
             objects,
-        }
+            background,
+        })
     }
 
-    pub fn to_ppm(&self, path: &Path) {
-        let w = World::new()
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn world(&self) -> World {
+        let world = World::new()
             .with_lights(self.lights.clone())
             .with_objects(self.objects.clone());
-        println!("{:#?}", &w);
-        let canvas = self.camera.render(w);
+        match self.background {
+            Some(background) => world.with_background(background),
+            None => world,
+        }
+    }
+
+    pub fn to_ppm(&self, path: &Path) -> Result<()> {
+        let canvas = self.camera.render(self.world());
         let ppm = PPM::from(canvas);
-        fs::write(path, ppm.to_string()).unwrap();
+        fs::write(path, ppm.to_string())
+            .map_err(|e| anyhow!("Cannot write image {}: {}", path.display(), e))
+    }
+}
+
+impl TryFrom<&Path> for YamlLoader {
+    type Error = anyhow::Error;
+
+    fn try_from(path: &Path) -> Result<Self> {
+        Self::load(path)
+    }
+}
+
+pub struct YamlSaver<'a> {
+    camera: &'a Camera,
+    world: &'a World,
+}
+
+impl<'a> YamlSaver<'a> {
+    pub fn new(camera: &'a Camera, world: &'a World) -> Self {
+        Self { camera, world }
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        let defaults = World::new();
+        let unsupported: Vec<&str> = [
+            ("environment", self.world.environment().is_some()),
+            (
+                "shadow-bias",
+                self.world.shadow_bias() != defaults.shadow_bias(),
+            ),
+            (
+                "transparent-shadows",
+                self.world.transparent_shadows() != defaults.transparent_shadows(),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, differs)| *differs)
+        .map(|(setting, _)| setting)
+        .collect();
+        if !unsupported.is_empty() {
+            return Err(anyhow!(
+                "World settings cannot be saved to YAML: {}",
+                unsupported.join(", ")
+            ));
+        }
+        let mut scene = vec![
+            Add::AddCamera(self.camera.into()),
+            Add::AddSettings(YamlSettingsCommand::Settings(YamlSettings {
+                samples: Some(self.camera.samples()),
+                max_depth: Some(self.camera.max_depth()),
+                background: Some(self.world.background().into()),
+            })),
+        ];
+        scene.extend(self.world.lights().iter().map(|l| Add::AddLight(l.into())));
+        for object in self.world.objects() {
+            scene.push(Add::AddObject(object.try_into()?));
+        }
+        Ok(serde_yml::to_string(&scene)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_yaml()?)
+            .map_err(|e| anyhow!("Cannot write scene {}: {}", path.display(), e))
     }
 }
 
@@ -958,8 +1708,16 @@ mod tests {
     use core::panic;
     use std::f64::consts::PI;
 
+    use yare::parameterized;
+
+    use crate::{canvas::Canvas, environment::Environment};
+
     use super::*;
 
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{name}", std::process::id()))
+    }
+
     #[test]
     fn test_deserialize() {
         let yml_str = r#"
@@ -1031,9 +1789,10 @@ mod tests {
     #[test]
     fn yaml_materials_can_be_merged() {
         let mat_1 = YamlMaterial {
-            pattern: Some(YamlPattern::Solid {
+            pattern: Some(PatternOrReference::Pattern(YamlPattern::Solid {
                 color: [1.0, 1.0, 1.0],
-            }),
+            })),
+            color: None,
             diffuse: Some(10.0),
             ambient: None,
             specular: Some(1.0),
@@ -1045,9 +1804,10 @@ mod tests {
             receive_shadows: Some(true),
         };
         let mat_2 = YamlMaterial {
-            pattern: Some(YamlPattern::Solid {
+            pattern: Some(PatternOrReference::Pattern(YamlPattern::Solid {
                 color: [0.404, 0.01, 0.9],
-            }),
+            })),
+            color: None,
             diffuse: None,
             ambient: Some(20.0),
             specular: Some(2.0),
@@ -1060,9 +1820,10 @@ mod tests {
         };
         let mat_3 = mat_1.merge(mat_2);
         let expected = YamlMaterial {
-            pattern: Some(YamlPattern::Solid {
+            pattern: Some(PatternOrReference::Pattern(YamlPattern::Solid {
                 color: [0.404, 0.01, 0.9],
-            }),
+            })),
+            color: None,
             diffuse: Some(10.0),
             ambient: Some(20.0),
             specular: Some(2.0),
@@ -1076,6 +1837,38 @@ mod tests {
         assert_eq!(mat_3, expected);
     }
 
+    #[test]
+    fn a_material_color_is_shorthand_for_a_solid_pattern() {
+        let yml_str = r#"
+color: [1, 0, 0]
+diffuse: 0.5
+"#;
+        let material: YamlMaterial = serde_yml::from_str(yml_str).unwrap();
+        assert_eq!(material.color, Some([1.0, 0.0, 0.0]));
+        let material: Material = material.into_with_defines(&[]).unwrap();
+        assert_eq!(
+            material.pattern(),
+            &Pattern::new_solid_pattern(Color::new(1.0, 0.0, 0.0))
+        );
+        assert_eq!(material.diffuse(), 0.5);
+    }
+
+    #[test]
+    fn an_explicit_pattern_overrides_the_material_color() {
+        let yml_str = r#"
+color: [1, 0, 0]
+pattern:
+  kind: solid
+  color: [0, 0, 1]
+"#;
+        let material: YamlMaterial = serde_yml::from_str(yml_str).unwrap();
+        let material: Material = material.into_with_defines(&[]).unwrap();
+        assert_eq!(
+            material.pattern(),
+            &Pattern::new_solid_pattern(Color::new(0.0, 0.0, 1.0))
+        );
+    }
+
     #[test]
     fn defines_can_be_inherited() {
         let yml_str = r#"
@@ -1096,16 +1889,17 @@ mod tests {
       color: [ 0.537, 0.831, 0.914 ]
         "#;
         let commands: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
-        let (_, _, _, defines) = extract_commands(commands);
+        let (_, _, _, defines, _) = extract_commands(commands);
         assert_eq!(defines.len(), 2);
         let white_material = Define {
             define: "white-material".to_string(),
             extend: None,
             transform: None,
             material: Some(YamlMaterial {
-                pattern: Some(YamlPattern::Solid {
+                pattern: Some(PatternOrReference::Pattern(YamlPattern::Solid {
                     color: [1.0, 1.0, 1.0],
-                }),
+                })),
+                color: None,
                 diffuse: Some(0.7),
                 ambient: Some(0.1),
                 specular: Some(0.0),
@@ -1116,15 +1910,17 @@ mod tests {
                 cast_shadows: None,
                 receive_shadows: None,
             }),
+            pattern: None,
         };
         let blue_material = Define {
             define: "blue-material".to_string(),
             extend: None,
             transform: None,
             material: Some(YamlMaterial {
-                pattern: Some(YamlPattern::Solid {
+                pattern: Some(PatternOrReference::Pattern(YamlPattern::Solid {
                     color: [0.537, 0.831, 0.914],
-                }),
+                })),
+                color: None,
                 diffuse: Some(0.7),
                 ambient: Some(0.1),
                 specular: Some(0.0),
@@ -1135,12 +1931,62 @@ mod tests {
                 cast_shadows: None,
                 receive_shadows: None,
             }),
+            pattern: None,
         };
-        let defines: Vec<Define> = defines.iter().map(|def| def.expand(&defines)).collect();
+        let defines: Vec<Define> = defines
+            .iter()
+            .map(|def| def.expand(&defines).unwrap())
+            .collect();
         assert_eq!(defines[0], white_material);
         assert_eq!(defines[1], blue_material);
     }
 
+    #[test]
+    fn patterns_can_be_defined_and_referenced() {
+        let yml_str = r#"
+- define: my-checker
+  pattern:
+    kind: checker
+    a:
+      kind: solid
+      color: [1, 1, 1]
+    b:
+      kind: solid
+      color: [0, 0, 0]
+    transform:
+    - [scale, 0.25, 0.25, 0.25]
+- define: checkered-material
+  material:
+    pattern: my-checker
+    diffuse: 0.7
+- add: sphere
+  material: checkered-material
+- add: cube
+  material:
+    pattern: my-checker
+    reflective: 0.3
+"#;
+        let commands: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
+        let (_, _, objects, defines, _) = extract_commands(commands);
+        let defines: Vec<Define> = defines
+            .iter()
+            .map(|def| def.expand(&defines).unwrap())
+            .collect();
+        let objects: Vec<Object> = objects
+            .into_iter()
+            .map(|o| o.into_object(&defines, Path::new("")).unwrap())
+            .collect();
+        let expected = Pattern::new_checker_pattern(
+            Pattern::new_solid_pattern(Color::white()),
+            Pattern::new_solid_pattern(Color::black()),
+        )
+        .with_transform(Transformation::new_transform().scaling(0.25, 0.25, 0.25));
+        assert_eq!(objects[0].material().pattern(), &expected);
+        assert_eq!(objects[1].material().pattern(), &expected);
+        assert_eq!(objects[0].material().diffuse(), 0.7);
+        assert_eq!(objects[1].material().reflective(), 0.3);
+    }
+
     #[test]
     fn can_use_math_expressions_in_fields() {
         let yml_str = r#"
@@ -1191,4 +2037,388 @@ mod tests {
             panic!("wrong command in yaml");
         }
     }
+
+    #[test]
+    fn the_legacy_point_light_spelling_still_parses() {
+        let yml_str = r#"
+- add: yaml-point-light
+  at: [-10, 10, -10]
+  intensity: [1, 1, 1]
+"#;
+        let commands: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
+        let Some(SceneCommand::Add(Add::AddLight(light))) = commands.into_iter().next() else {
+            panic!("wrong command in yaml");
+        };
+        assert!(matches!(light, YamlLight::Point { .. }));
+    }
+
+    #[test]
+    fn an_area_light_is_deserialized_with_its_sample_counts() {
+        let yml_str = r#"
+- add: area-light
+  corner: [-1, 2, 4]
+  uvec: [2, 0, 0]
+  usteps: 10
+  vvec: [0, 2, 0]
+  vsteps: 5
+  intensity: [1.5, 1.5, 1.5]
+"#;
+        let commands: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
+        assert_eq!(commands.len(), 1);
+        let Some(SceneCommand::Add(Add::AddLight(light))) = commands.into_iter().next() else {
+            panic!("wrong command in yaml");
+        };
+        assert!(matches!(
+            light,
+            YamlLight::Area {
+                usteps: 10,
+                vsteps: 5,
+                ..
+            }
+        ));
+        let Light::Area(area) = light.into() else {
+            panic!("wrong light kind");
+        };
+        assert_eq!(area.samples().len(), 50);
+        assert_eq!(area.position, Point::new(0.0, 3.0, 4.0));
+        assert_eq!(area.intensity, Color::new(1.5, 1.5, 1.5));
+    }
+
+    #[test]
+    fn a_cone_half_angle_is_loaded_and_saved() {
+        let yml_str = r#"
+- add: cone
+  min: 0
+  max: 1
+  half-angle: 0.3
+"#;
+        let commands: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
+        let Some(SceneCommand::Add(Add::AddObject(object))) = commands.into_iter().next() else {
+            panic!("wrong command in yaml");
+        };
+        let object = object.into_object(&[], Path::new(".")).unwrap();
+        let Shape::Cone(cone) = object.shape() else {
+            panic!("wrong shape");
+        };
+        assert!(cone.half_angle().approx_eq(0.3));
+
+        let YamlObject::Cone { half_angle, .. } = YamlObject::try_from(&object).unwrap() else {
+            panic!("wrong yaml object");
+        };
+        assert!(half_angle.unwrap().approx_eq(0.3));
+    }
+
+    #[test]
+    fn a_saved_scene_loads_back_into_the_same_world() {
+        let yml_str = r#"
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 0.785
+  from: [-6, 6, -10]
+  to: [6, 0, 6]
+  up: [-0.45, 1, 0]
+- add: point-light
+  at: [50, 100, -50]
+  intensity: [1, 1, 1]
+- add: sphere
+  material:
+    pattern:
+      kind: solid
+      color: [1, 0.2, 0.2]
+    diffuse: 0.7
+    reflective: 0.3
+  transform:
+    - [scale, 0.5, 0.5, 0.5]
+    - [translate, 1, 0.5, -1]
+- add: cylinder
+  min: 0
+  max: 2
+  cap: both
+  transform:
+    - [rotate-x, 1.5707963267948966]
+"#;
+        let path = temp_path("ray-tracer-yaml-round-trip.yml");
+        fs::write(&path, yml_str).unwrap();
+        let loaded = YamlLoader::load(&path).unwrap();
+
+        let saved = YamlSaver::new(loaded.camera(), &loaded.world())
+            .to_yaml()
+            .unwrap();
+        fs::write(&path, saved).unwrap();
+        let reloaded = YamlLoader::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(reloaded.lights, loaded.lights);
+        assert_eq!(reloaded.objects, loaded.objects);
+        assert_eq!(reloaded.camera.transform(), loaded.camera.transform());
+        assert_eq!(reloaded.camera.h_size(), 100);
+        assert_eq!(reloaded.camera.v_size(), 50);
+        assert!(reloaded.camera.field_of_view().approx_eq(0.785));
+    }
+
+    #[test]
+    fn saving_an_unsupported_pattern_is_an_error() {
+        let camera = Camera::new(10, 10, PI / 3.0);
+        let world = World::new().with_objects(vec![ObjectBuilder::new_sphere()
+            .with_material(Material::new().with_pattern(Pattern::new_test_pattern()))
+            .build()]);
+        assert!(YamlSaver::new(&camera, &world).to_yaml().is_err());
+    }
+
+    #[test]
+    fn saving_unsupported_material_fields_is_an_error() {
+        let materials = [
+            (Material::new().with_emission(Color::white()), "emission"),
+            (Material::new().with_fresnel(true), "fresnel"),
+            (Material::new().with_dispersion(0.1), "dispersion"),
+            (
+                Material::new()
+                    .with_pattern(Pattern::new_solid_pattern(Color::white()).with_opacity(0.5)),
+                "pattern opacity",
+            ),
+        ];
+        for (material, field) in materials {
+            let error = YamlMaterial::try_from(&material).unwrap_err();
+            assert!(error.to_string().contains(field), "{error}");
+        }
+        assert!(YamlMaterial::try_from(&Material::new()).is_ok());
+    }
+
+    #[test]
+    fn saving_unsupported_world_settings_is_an_error() {
+        let camera = Camera::new(10, 10, PI / 2.0);
+        let worlds = [
+            (
+                World::new().with_environment(Environment::new(Canvas::new(1, 1))),
+                "environment",
+            ),
+            (World::new().with_shadow_bias(0.1), "shadow-bias"),
+            (
+                World::new().with_transparent_shadows(true),
+                "transparent-shadows",
+            ),
+        ];
+        for (world, setting) in worlds {
+            let error = YamlSaver::new(&camera, &world).to_yaml().unwrap_err();
+            assert!(error.to_string().contains(setting), "{error}");
+        }
+        assert!(YamlSaver::new(&camera, &World::new()).to_yaml().is_ok());
+    }
+
+    #[test]
+    fn loading_malformed_yaml_reports_the_file_and_the_problem() {
+        let path = temp_path("ray-tracer-malformed.yml");
+        fs::write(
+            &path,
+            "- add: sphere\n  material:\n    diffuse: [not, a, number]\n",
+        )
+        .unwrap();
+        let err = YamlLoader::try_from(path.as_path()).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        let message = err.to_string();
+        assert!(message.contains("Cannot parse scene"));
+        assert!(message.contains("ray-tracer-malformed.yml"));
+    }
+
+    #[test]
+    fn loading_a_missing_file_is_an_error() {
+        let path = temp_path("ray-tracer-does-not-exist.yml");
+        let err = YamlLoader::try_from(path.as_path()).err().unwrap();
+        assert!(err.to_string().contains("Cannot read scene"));
+    }
+
+    #[test]
+    fn an_included_file_provides_its_defines() {
+        let dir = temp_path("ray-tracer-yaml-include");
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::write(
+            dir.join("lib/materials.yml"),
+            r#"
+- define: red-material
+  material:
+    color: [1, 0, 0]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("scene.yml"),
+            r#"
+- include: lib/materials.yml
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 0.785
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+- add: sphere
+  material: red-material
+"#,
+        )
+        .unwrap();
+        let loader = YamlLoader::load(&dir.join("scene.yml"));
+        fs::remove_dir_all(&dir).unwrap();
+        let world = loader.unwrap().world();
+        assert_eq!(
+            world.objects()[0].material().pattern(),
+            &Pattern::new_solid_pattern(Color::new(1.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn an_include_cycle_is_an_error() {
+        let dir = temp_path("ray-tracer-yaml-include-cycle");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.yml"), "- include: b.yml\n").unwrap();
+        fs::write(dir.join("b.yml"), "- include: a.yml\n").unwrap();
+        let result = YamlLoader::load(&dir.join("a.yml"));
+        fs::remove_dir_all(&dir).unwrap();
+        let err = result.err().unwrap().to_string();
+        assert!(err.starts_with("Include cycle"));
+        assert!(err.contains("a.yml -> "));
+    }
+
+    #[test]
+    fn a_dangling_reference_names_the_definition_and_the_object() {
+        let path = temp_path("ray-tracer-yaml-dangling.yml");
+        fs::write(
+            &path,
+            r#"
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 0.785
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+- add: group
+  children:
+  - add: sphere
+    material: no-such-material
+"#,
+        )
+        .unwrap();
+        let result = YamlLoader::load(&path);
+        fs::remove_file(&path).unwrap();
+        let err = result.err().unwrap().to_string();
+        assert!(err.contains("Unknown definition 'no-such-material'"));
+        assert!(err.contains("group: sphere"));
+    }
+
+    #[test]
+    fn extending_an_unknown_definition_is_an_error() {
+        let yml_str = r#"
+- define: child
+  extend: missing-parent
+  material:
+    diffuse: 0.5
+"#;
+        let commands: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
+        let (_, _, _, defines, _) = extract_commands(commands);
+        let err = defines[0].expand(&defines).err().unwrap().to_string();
+        assert!(err.contains("child"));
+        assert!(err.contains("Unknown definition 'missing-parent'"));
+    }
+
+    #[parameterized(
+        transform_without_transform = {
+            "- define: parent\n  transform:\n    - [scale, 2, 2, 2]\n- define: child\n  extend: parent\n",
+            "child extends 'parent' but has no transform of its own"
+        },
+        material_without_material = {
+            "- define: parent\n  material:\n    diffuse: 0.5\n- define: child\n  extend: parent\n",
+            "child extends 'parent' but has no material of its own"
+        },
+        empty_parent = {
+            "- define: parent\n- define: child\n  extend: parent\n  material:\n    diffuse: 0.5\n",
+            "child extends 'parent' but it defines nothing"
+        },
+        cycle = {
+            "- define: child\n  extend: parent\n  material:\n    diffuse: 0.5\n- define: parent\n  extend: child\n  material:\n    ambient: 0.5\n",
+            "Extend cycle: child -> parent -> child"
+        },
+    )]
+    fn invalid_extends_are_errors(yml_str: &str, expected: &str) {
+        let commands: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
+        let (_, _, _, defines, _) = extract_commands(commands);
+        let child = defines.iter().find(|def| def.define == "child").unwrap();
+        let err = child.expand(&defines).err().unwrap().to_string();
+        assert!(err.contains(expected), "{err}");
+    }
+
+    #[test]
+    fn render_settings_flow_into_the_camera_and_world() {
+        let path = temp_path("ray-tracer-yaml-settings.yml");
+        fs::write(
+            &path,
+            r#"
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 0.785
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+- add: settings
+  samples: 3
+  max-depth: 2
+  background: [0.1, 0.2, 0.3]
+"#,
+        )
+        .unwrap();
+        let loader = YamlLoader::load(&path);
+        fs::remove_file(&path).unwrap();
+        let loader = loader.unwrap();
+        assert_eq!(loader.camera().samples(), 3);
+        assert_eq!(loader.camera().max_depth(), 2);
+        assert_eq!(loader.world().background(), Color::new(0.1, 0.2, 0.3));
+    }
+
+    #[test]
+    fn missing_render_settings_keep_the_defaults() {
+        let yml_str = r#"
+- add: settings
+  samples: 2
+"#;
+        let scene: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
+        let (_, _, _, _, settings) = extract_commands(scene);
+        assert_eq!(settings.samples, Some(2));
+        assert_eq!(settings.max_depth, None);
+        assert_eq!(settings.background, None);
+    }
+
+    #[test]
+    fn an_obj_file_is_loaded_as_a_group() {
+        let resources = std::env::temp_dir();
+        let obj_path = temp_path("ray-tracer-yaml-quad.obj");
+        fs::write(&obj_path, "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n").unwrap();
+        let yml_str = format!(
+            "- add: obj\n  file: {}\n  transform:\n    - [translate, 0, 1, 0]\n",
+            obj_path.file_name().unwrap().to_str().unwrap()
+        );
+        let scene: Vec<SceneCommand> = serde_yml::from_str(&yml_str).unwrap();
+        let (_, _, objects, _, _) = extract_commands(scene);
+        let object = objects
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_object(&[], &resources)
+            .unwrap();
+        fs::remove_file(obj_path).unwrap();
+        assert!(matches!(object.shape(), Shape::Group(_)));
+        assert_eq!(object.child_count(), 2);
+    }
+
+    #[test]
+    fn a_missing_obj_file_is_an_error() {
+        let yml_str = r#"
+- add: obj
+  file: does-not-exist.obj
+"#;
+        let scene: Vec<SceneCommand> = serde_yml::from_str(yml_str).unwrap();
+        let (_, _, objects, _, _) = extract_commands(scene);
+        let object = objects.into_iter().next().unwrap();
+        assert!(object.into_object(&[], Path::new("")).is_err());
+    }
 }
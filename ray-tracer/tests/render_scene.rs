@@ -0,0 +1,64 @@
+use std::{env, path::PathBuf};
+
+use ray_tracer::{
+    canvas::Canvas,
+    ppm::PPM,
+    render_scene,
+    yaml_loader::{YamlLoader, YamlSaver},
+    RenderSettings,
+};
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("{}-{name}", std::process::id()))
+}
+
+fn cover_scene() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../scene-drawing/scenes/cover.yml");
+    path
+}
+
+#[test]
+fn rendering_the_cover_scene_end_to_end() {
+    let out = temp_path("ray-tracer-render-scene-cover.ppm");
+    let stats = render_scene(
+        &cover_scene(),
+        &out,
+        RenderSettings::new().with_size(32, 24),
+    )
+    .unwrap();
+    assert_eq!(stats.width, 32);
+    assert_eq!(stats.height, 24);
+    assert_eq!(stats.lights, 2);
+    let canvas = Canvas::from(
+        std::fs::read_to_string(&out)
+            .unwrap()
+            .parse::<PPM>()
+            .unwrap(),
+    );
+    assert_eq!(canvas.width(), 32);
+    assert_eq!(canvas.height(), 24);
+    std::fs::remove_file(out).unwrap();
+}
+
+#[test]
+fn rendering_reports_errors_instead_of_panicking() {
+    let out = temp_path("ray-tracer-render-scene-missing.ppm");
+    let missing = PathBuf::from("does-not-exist.yml");
+    assert!(render_scene(&missing, &out, RenderSettings::new()).is_err());
+    let unsupported = temp_path("ray-tracer-render-scene.bmp");
+    assert!(render_scene(&cover_scene(), &unsupported, RenderSettings::new()).is_err());
+}
+
+#[test]
+fn the_cover_scene_survives_a_save_and_load() {
+    let loaded = YamlLoader::load(&cover_scene()).unwrap();
+    let world = loaded.world();
+    let path = temp_path("ray-tracer-cover-round-trip.yml");
+    YamlSaver::new(loaded.camera(), &world).save(&path).unwrap();
+    let reloaded = YamlLoader::load(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(reloaded.world().lights(), world.lights());
+    assert_eq!(reloaded.world().objects(), world.objects());
+    assert_eq!(reloaded.camera().transform(), loaded.camera().transform());
+}
@@ -131,7 +131,8 @@ fn main() {
 
     let world = World::new()
         .with_objects(vec![floor, roof, left_wall, right_wall, s1, s2, s3, s4, s5])
-        .with_lights(vec![l1, l2]);
+        .with_lights(vec![l1, l2])
+        .with_transparent_shadows(true);
 
     let width = 1024;
     let height = 768;
@@ -1,7 +1,7 @@
-use std::{env, path::PathBuf};
+use std::{env, path::PathBuf, process};
 
 use clap::Parser;
-use ray_tracer::yaml_loader::YamlLoader;
+use ray_tracer::{render_scene, RenderSettings};
 
 #[derive(Debug, Parser)]
 struct Args {
@@ -23,8 +23,17 @@ fn main() {
     scene.push(args.scene_file_name);
     let mut output = args.target_path.clone();
     output.push(args.output_file_name);
-    let loader = YamlLoader::from(&scene);
-    loader.to_ppm(&output);
+    let settings = RenderSettings::new().with_resources(args.resources_path);
+    match render_scene(&scene, &output, settings) {
+        Ok(stats) => println!(
+            "Rendered {}x{} image ({} objects, {} lights) in {:.2?}",
+            stats.width, stats.height, stats.objects, stats.lights, stats.elapsed
+        ),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
 }
 
 fn get_default_path(folder: &str) -> PathBuf {
@@ -45,9 +45,10 @@ fn main() {
             let object = hit.object;
             let normal = object.normal_at(point, Intersection::new(1.0, &object));
             let eye = -r.direction;
-            let color = object
-                .material()
-                .lighting(light, point, eye, normal, false, &object);
+            let color =
+                object
+                    .material()
+                    .lighting(light.into(), point, eye, normal, false, &object);
             let mut canvas = canvas_mutex.lock().unwrap();
             canvas.write_pixel(x, y, color);
         }
@@ -47,12 +47,9 @@ fn main() {
 
     while p.position.y() > 0.0 {
         let p_draw_x = p.position.x().round() as usize;
-        let p_draw_y = if canvas.height() > p.position.y().round() as usize {
-            canvas.height() - p.position.y().round() as usize
-        } else {
-            canvas.height()
-        };
-        canvas.write_pixel(p_draw_x, p_draw_y, Color::red());
+        if let Some(p_draw_y) = canvas.height().checked_sub(p.position.y().round() as usize) {
+            canvas.write_pixel(p_draw_x, p_draw_y, Color::red());
+        }
         tick(&mut p, &e);
     }
     let ppm = PPM::from(canvas);